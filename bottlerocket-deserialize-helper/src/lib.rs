@@ -0,0 +1,594 @@
+/*!
+This helper library deserializes settings values from JSON, producing errors that point users at
+the problem in their input.
+
+Each [`DeserializeError`] carries the path of the field that failed to deserialize, e.g.
+`static-pods.my-pod.enabled`, rather than requiring users to search the input themselves.
+Types that implement [`KnownNames`], as `#[model]` does for settings models, can be deserialized
+with [`from_model_value`] or [`from_model_str`]; when one of their fields or enum variants isn't
+recognized, the error also suggests the known names that most closely match it.
+
+```
+use bottlerocket_deserialize_helper::{KnownNames, Names, PathSegment};
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+struct Kubernetes {
+    cluster_name: Option<String>,
+    cluster_domain: Option<String>,
+}
+
+impl KnownNames for Kubernetes {
+    fn known_names(path: &[PathSegment]) -> Option<Names> {
+        match path {
+            [] => Some(Names::Fields(&["cluster-name", "cluster-domain"])),
+            _ => None,
+        }
+    }
+}
+
+let err = bottlerocket_deserialize_helper::from_model_value::<Kubernetes>(
+    serde_json::json!({"cluster-nme": "my-cluster"}),
+)
+.unwrap_err();
+assert_eq!(err.path(), "cluster-nme");
+assert_eq!(err.suggestions(), &["cluster-name"]);
+```
+*/
+
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::Value;
+use serde_path_to_error::Segment;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::{self, Display, Formatter};
+
+/// The most suggestions included in an error.
+const MAX_SUGGESTIONS: usize = 3;
+
+/// Deserializes a `T` from a JSON value, reporting the path of the field at fault on failure.
+pub fn from_value<T: DeserializeOwned>(value: Value) -> Result<T, DeserializeError> {
+    serde_path_to_error::deserialize(value).map_err(DeserializeError::from)
+}
+
+/// Deserializes a `T` from a JSON string, reporting the path of the field at fault on failure.
+pub fn from_str<'a, T: Deserialize<'a>>(s: &'a str) -> Result<T, DeserializeError> {
+    let mut deserializer = serde_json::Deserializer::from_str(s);
+    let value = serde_path_to_error::deserialize(&mut deserializer)?;
+    // Reject trailing characters after the value, as `serde_json::from_str` does.
+    deserializer.end().map_err(DeserializeError::from)?;
+    Ok(value)
+}
+
+/// Deserializes a `T` from a JSON value like [`from_value`], also suggesting the names `T` knows
+/// when a field or variant isn't recognized.
+pub fn from_model_value<T: DeserializeOwned + KnownNames>(
+    value: Value,
+) -> Result<T, DeserializeError> {
+    // Deserialize from a reference so that an unrecognized variant can be found in the input.
+    serde_path_to_error::deserialize(&value)
+        .map_err(|e| DeserializeError::from(e).with_suggestions::<T>(Some(&value)))
+}
+
+/// Deserializes a `T` from a JSON string like [`from_str`], also suggesting the names `T` knows
+/// when a field or variant isn't recognized.
+pub fn from_model_str<'a, T: Deserialize<'a> + KnownNames>(
+    s: &'a str,
+) -> Result<T, DeserializeError> {
+    let mut deserializer = serde_json::Deserializer::from_str(s);
+    let value = serde_path_to_error::deserialize(&mut deserializer).map_err(|e| {
+        // The input is only parsed again to find an unrecognized variant in it.
+        let input = serde_json::from_str(s).ok();
+        DeserializeError::from(e).with_suggestions::<T>(input.as_ref())
+    })?;
+    deserializer.end().map_err(DeserializeError::from)?;
+    Ok(value)
+}
+
+/// Types that know the names of the fields and enum variants they accept, so that deserialization
+/// errors can suggest them when a name isn't recognized.
+///
+/// `#[model]` implements this for settings models.
+/// Types with their own `Deserialize` implementations can delegate to the types they deserialize
+/// through, or return `None` if nothing is known.
+pub trait KnownNames {
+    /// Returns the names accepted by the struct or enum at `path` within this type, if known.
+    fn known_names(_path: &[PathSegment]) -> Option<Names> {
+        None
+    }
+}
+
+/// The names accepted by a struct or enum.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Names {
+    /// The names of a struct's fields.
+    Fields(&'static [&'static str]),
+    /// The names of an enum's variants.
+    Variants(&'static [&'static str]),
+}
+
+impl<T: KnownNames> KnownNames for Option<T> {
+    fn known_names(path: &[PathSegment]) -> Option<Names> {
+        T::known_names(path)
+    }
+}
+
+impl<T: KnownNames> KnownNames for Box<T> {
+    fn known_names(path: &[PathSegment]) -> Option<Names> {
+        T::known_names(path)
+    }
+}
+
+impl<T: KnownNames> KnownNames for Vec<T> {
+    fn known_names(path: &[PathSegment]) -> Option<Names> {
+        match path {
+            [PathSegment::Index(_), rest @ ..] => T::known_names(rest),
+            _ => None,
+        }
+    }
+}
+
+impl<K, V: KnownNames, S> KnownNames for HashMap<K, V, S> {
+    fn known_names(path: &[PathSegment]) -> Option<Names> {
+        match path {
+            [PathSegment::Key(_), rest @ ..] => V::known_names(rest),
+            _ => None,
+        }
+    }
+}
+
+impl<K, V: KnownNames> KnownNames for BTreeMap<K, V> {
+    fn known_names(path: &[PathSegment]) -> Option<Names> {
+        match path {
+            [PathSegment::Key(_), rest @ ..] => V::known_names(rest),
+            _ => None,
+        }
+    }
+}
+
+/// Lets `#[model]` look up the names known by the type of each field, if it has any.
+#[doc(hidden)]
+pub mod __private {
+    use super::{KnownNames, Names, PathSegment};
+    use std::marker::PhantomData;
+
+    /// Stands in for a field's type.
+    /// Method resolution picks [`Known`] for types that implement [`KnownNames`], and falls back
+    /// to [`Unknown`] by taking another reference for types that don't.
+    pub struct Probe<T: ?Sized>(PhantomData<T>);
+
+    impl<T: ?Sized> Probe<T> {
+        #[allow(clippy::new_without_default)]
+        pub fn new() -> Self {
+            Self(PhantomData)
+        }
+    }
+
+    pub trait Known {
+        fn known_names(&self, path: &[PathSegment]) -> Option<Names>;
+    }
+
+    impl<T: KnownNames> Known for Probe<T> {
+        fn known_names(&self, path: &[PathSegment]) -> Option<Names> {
+            T::known_names(path)
+        }
+    }
+
+    pub trait Unknown {
+        fn known_names(&self, path: &[PathSegment]) -> Option<Names>;
+    }
+
+    impl<T: ?Sized> Unknown for &Probe<T> {
+        fn known_names(&self, _path: &[PathSegment]) -> Option<Names> {
+            None
+        }
+    }
+}
+
+/// A step along the path to a field within a settings value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PathSegment {
+    /// A field name, map key, or enum variant name.
+    Key(String),
+    /// An index into a sequence.
+    Index(usize),
+}
+
+impl From<&str> for PathSegment {
+    fn from(key: &str) -> Self {
+        Self::Key(key.to_string())
+    }
+}
+
+impl From<String> for PathSegment {
+    fn from(key: String) -> Self {
+        Self::Key(key)
+    }
+}
+
+impl From<usize> for PathSegment {
+    fn from(index: usize) -> Self {
+        Self::Index(index)
+    }
+}
+
+impl Display for PathSegment {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Key(key) => f.write_str(key),
+            Self::Index(index) => write!(f, "{}", index),
+        }
+    }
+}
+
+/// An error encountered while deserializing a settings value.
+#[derive(Debug)]
+pub struct DeserializeError {
+    path: String,
+    // Boxed slices keep the error small, since it's embedded in other error types.
+    segments: Box<[PathSegment]>,
+    suggestions: Box<[String]>,
+    source: serde_json::Error,
+}
+
+impl DeserializeError {
+    /// Returns the path of the field at fault, or an empty string if the problem is with the value
+    /// as a whole.
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// Returns the steps along the path to the field at fault, from the root of the value.
+    pub fn segments(&self) -> &[PathSegment] {
+        &self.segments
+    }
+
+    /// Returns the known names that most closely match an unrecognized field or variant, closest
+    /// first.
+    pub fn suggestions(&self) -> &[String] {
+        &self.suggestions
+    }
+
+    /// Returns the underlying error from `serde_json`.
+    pub fn inner(&self) -> &serde_json::Error {
+        &self.source
+    }
+
+    /// Adds suggestions from the names `T` knows, if the error is for a name `T` doesn't know.
+    /// `input` is the value being deserialized, which holds the name of an unrecognized variant.
+    fn with_suggestions<T: KnownNames>(mut self, input: Option<&Value>) -> Self {
+        // An unrecognized field is the last step along the path.
+        if let Some((PathSegment::Key(name), parent)) = self.segments.split_last() {
+            if let Some(Names::Fields(fields)) = T::known_names(parent) {
+                if !fields.contains(&name.as_str()) {
+                    self.suggestions = suggest(name, fields).into_boxed_slice();
+                    return self;
+                }
+            }
+        }
+
+        // An unrecognized variant is the value found at the path.
+        if let Some(Names::Variants(variants)) = T::known_names(&self.segments) {
+            if let Some(name) = input
+                .and_then(|input| value_at(input, &self.segments))
+                .and_then(variant_name)
+            {
+                if !variants.contains(&name) {
+                    self.suggestions = suggest(name, variants).into_boxed_slice();
+                }
+            }
+        }
+        self
+    }
+}
+
+impl From<serde_json::Error> for DeserializeError {
+    fn from(source: serde_json::Error) -> Self {
+        Self {
+            path: String::new(),
+            segments: Box::default(),
+            suggestions: Box::default(),
+            source,
+        }
+    }
+}
+
+impl From<serde_path_to_error::Error<serde_json::Error>> for DeserializeError {
+    fn from(e: serde_path_to_error::Error<serde_json::Error>) -> Self {
+        // The root of the value is displayed as ".", which isn't useful to users.
+        let path = match e.path().to_string() {
+            root if root == "." => String::new(),
+            path => path,
+        };
+        let segments = e
+            .path()
+            .iter()
+            .map_while(|segment| match segment {
+                Segment::Seq { index } => Some(PathSegment::Index(*index)),
+                Segment::Map { key } | Segment::Enum { variant: key } => {
+                    Some(PathSegment::Key(key.clone()))
+                }
+                // Nothing past an unknown segment can be located.
+                Segment::Unknown => None,
+            })
+            .collect();
+        Self {
+            path,
+            segments,
+            ..Self::from(e.into_inner())
+        }
+    }
+}
+
+impl Display for DeserializeError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if !self.path.is_empty() {
+            write!(f, "invalid value for '{}': ", self.path)?;
+        }
+        write!(f, "{}", self.source)?;
+        match &*self.suggestions {
+            [] => Ok(()),
+            [suggestion] => write!(f, " (did you mean '{}'?)", suggestion),
+            suggestions => write!(f, " (did you mean one of '{}'?)", suggestions.join("', '")),
+        }
+    }
+}
+
+impl std::error::Error for DeserializeError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.source)
+    }
+}
+
+/// Returns the candidates that are similar to `name`, most similar first.
+pub fn suggest<S: AsRef<str>>(name: &str, candidates: &[S]) -> Vec<String> {
+    // Allow roughly one typo for every three characters.
+    let max_distance = (name.chars().count() / 3).max(1);
+
+    let mut similar: Vec<(usize, &str)> = candidates
+        .iter()
+        .map(AsRef::as_ref)
+        .map(|candidate| (edit_distance(name, candidate), candidate))
+        .filter(|(distance, _)| *distance <= max_distance)
+        .collect();
+    // The sort is stable, so equally similar candidates keep their original order.
+    similar.sort_by_key(|(distance, _)| *distance);
+
+    similar
+        .into_iter()
+        .take(MAX_SUGGESTIONS)
+        .map(|(_, candidate)| candidate.to_string())
+        .collect()
+}
+
+/// Returns the part of `value` found by following `segments`, if there is one.
+fn value_at<'a>(value: &'a Value, segments: &[PathSegment]) -> Option<&'a Value> {
+    segments
+        .iter()
+        .try_fold(value, |value, segment| match segment {
+            PathSegment::Key(key) => value.get(key),
+            PathSegment::Index(index) => value.get(index),
+        })
+}
+
+/// Returns the name of the enum variant given by `value`, which is either the name itself or, for
+/// variants with data, the only key of an object.
+fn variant_name(value: &Value) -> Option<&str> {
+    match value {
+        Value::String(name) => Some(name),
+        Value::Object(object) if object.len() == 1 => object.keys().next().map(String::as_str),
+        _ => None,
+    }
+}
+
+/// Returns the edit distance between `a` and `b`, counting insertions, deletions, substitutions,
+/// and transpositions of adjacent characters, which are common typos.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    // `distances[i][j]` is the distance between the first `i` chars of `a` and the first `j` of
+    // `b`.
+    let mut distances = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in distances.iter_mut().enumerate() {
+        if let Some(first) = row.first_mut() {
+            *first = i;
+        }
+    }
+    if let Some(first_row) = distances.first_mut() {
+        for (j, distance) in first_row.iter_mut().enumerate() {
+            *distance = j;
+        }
+    }
+
+    let distance = |distances: &Vec<Vec<usize>>, i: usize, j: usize| {
+        distances
+            .get(i)
+            .and_then(|row| row.get(j))
+            .copied()
+            .unwrap_or_default()
+    };
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let (a_char, b_char) = (a.get(i - 1), b.get(j - 1));
+            let mut best = (distance(&distances, i - 1, j) + 1)
+                .min(distance(&distances, i, j - 1) + 1)
+                .min(distance(&distances, i - 1, j - 1) + usize::from(a_char != b_char));
+            if i > 1 && j > 1 && a_char == b.get(j - 2) && a.get(i - 2) == b_char {
+                best = best.min(distance(&distances, i - 2, j - 2) + 1);
+            }
+            if let Some(cell) = distances.get_mut(i).and_then(|row| row.get_mut(j)) {
+                *cell = best;
+            }
+        }
+    }
+
+    distance(&distances, a.len(), b.len())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use serde_json::json;
+    use std::collections::HashMap;
+
+    #[derive(Debug, Deserialize)]
+    #[serde(deny_unknown_fields, rename_all = "kebab-case")]
+    #[allow(dead_code)]
+    struct StaticPod {
+        enabled: Option<bool>,
+        manifest: Option<String>,
+    }
+
+    #[derive(Debug, Deserialize)]
+    #[serde(deny_unknown_fields, rename_all = "kebab-case")]
+    #[allow(dead_code)]
+    struct Kubernetes {
+        cluster_name: Option<String>,
+        cluster_domain: Option<String>,
+        static_pods: Option<HashMap<String, StaticPod>>,
+        mode: Option<Mode>,
+    }
+
+    #[derive(Debug, Deserialize)]
+    #[serde(rename_all = "kebab-case")]
+    enum Mode {
+        Tls,
+        Aws,
+    }
+
+    impl KnownNames for StaticPod {
+        fn known_names(path: &[PathSegment]) -> Option<Names> {
+            match path {
+                [] => Some(Names::Fields(&["enabled", "manifest"])),
+                _ => None,
+            }
+        }
+    }
+
+    impl KnownNames for Kubernetes {
+        fn known_names(path: &[PathSegment]) -> Option<Names> {
+            match path {
+                [] => Some(Names::Fields(&[
+                    "cluster-name",
+                    "cluster-domain",
+                    "static-pods",
+                    "mode",
+                ])),
+                [PathSegment::Key(field), rest @ ..] if field == "static-pods" => {
+                    HashMap::<String, StaticPod>::known_names(rest)
+                }
+                [PathSegment::Key(field), rest @ ..] if field == "mode" => Mode::known_names(rest),
+                _ => None,
+            }
+        }
+    }
+
+    impl KnownNames for Mode {
+        fn known_names(path: &[PathSegment]) -> Option<Names> {
+            match path {
+                [] => Some(Names::Variants(&["tls", "aws"])),
+                _ => None,
+            }
+        }
+    }
+
+    #[test]
+    fn test_edit_distance() {
+        assert_eq!(edit_distance("", ""), 0);
+        assert_eq!(edit_distance("abc", ""), 3);
+        assert_eq!(edit_distance("cluster-nme", "cluster-name"), 1);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+        assert_eq!(edit_distance("tsl", "tls"), 1);
+    }
+
+    #[test]
+    fn test_unknown_field_path_and_suggestions() {
+        let err = from_model_value::<Kubernetes>(json!({"cluster-nme": "foo"})).unwrap_err();
+        assert_eq!(err.path(), "cluster-nme");
+        assert_eq!(err.suggestions(), &["cluster-name"]);
+        assert!(err
+            .to_string()
+            .starts_with("invalid value for 'cluster-nme': unknown field `cluster-nme`"));
+        assert!(err.to_string().ends_with("(did you mean 'cluster-name'?)"));
+    }
+
+    #[test]
+    fn test_no_suggestions_without_known_names() {
+        let err = from_value::<Kubernetes>(json!({"cluster-nme": "foo"})).unwrap_err();
+        assert_eq!(err.path(), "cluster-nme");
+        assert!(err.suggestions().is_empty());
+
+        let err = from_str::<Kubernetes>(r#"{"mode": "tsl"}"#).unwrap_err();
+        assert_eq!(err.path(), "mode");
+        assert!(err.suggestions().is_empty());
+    }
+
+    #[test]
+    fn test_nested_path() {
+        let err = from_model_value::<Kubernetes>(json!({
+            "static-pods": {"my-pod": {"enabled": "yes"}}
+        }))
+        .unwrap_err();
+        assert_eq!(err.path(), "static-pods.my-pod.enabled");
+        assert_eq!(
+            err.segments(),
+            &[
+                PathSegment::Key("static-pods".to_string()),
+                PathSegment::Key("my-pod".to_string()),
+                PathSegment::Key("enabled".to_string()),
+            ]
+        );
+        assert!(err.suggestions().is_empty());
+
+        let err = from_model_str::<Kubernetes>(r#"{"static-pods": {"my-pod": {"enabld": true}}}"#)
+            .unwrap_err();
+        assert_eq!(err.path(), "static-pods.my-pod.enabld");
+        assert_eq!(err.suggestions(), &["enabled"]);
+    }
+
+    #[test]
+    fn test_unknown_variant_suggestions() {
+        let err = from_model_value::<Kubernetes>(json!({"mode": "tsl"})).unwrap_err();
+        assert_eq!(err.path(), "mode");
+        assert_eq!(err.suggestions(), &["tls"]);
+
+        let err = from_model_str::<Kubernetes>(r#"{"mode": "tsl"}"#).unwrap_err();
+        assert_eq!(err.suggestions(), &["tls"]);
+
+        // A value of the wrong type isn't an unrecognized variant.
+        let err = from_model_value::<Kubernetes>(json!({"mode": 1})).unwrap_err();
+        assert!(err.suggestions().is_empty());
+    }
+
+    #[test]
+    fn test_no_similar_names() {
+        let err = from_model_value::<Kubernetes>(json!({"something-else": 1})).unwrap_err();
+        assert!(err.suggestions().is_empty());
+        assert!(!err.to_string().contains("did you mean"));
+    }
+
+    #[test]
+    fn test_root_errors_have_no_path() {
+        let err = from_model_value::<Kubernetes>(json!("foo")).unwrap_err();
+        assert_eq!(err.path(), "");
+
+        let err = from_model_str::<Kubernetes>("{} trailing").unwrap_err();
+        assert_eq!(err.path(), "");
+    }
+
+    #[test]
+    fn test_multiple_suggestions() {
+        assert_eq!(
+            suggest(
+                "seed",
+                &["version-lock", "speed", "seeds", "seen", "need", "sed"]
+            ),
+            vec!["speed", "seeds", "seen"]
+        );
+        assert_eq!(
+            suggest("cluster-dns", &["cluster-name", "cluster-dns-ip"]),
+            vec!["cluster-dns-ip"]
+        );
+    }
+}
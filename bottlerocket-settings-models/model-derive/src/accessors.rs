@@ -0,0 +1,197 @@
+//! Generates accessor methods and builders for model structs.
+use super::ModelField;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote};
+use syn::ItemStruct;
+
+/// Generates a getter and a `with_<field>` method for each field of the model.
+pub(crate) fn impl_accessors(
+    node: &ItemStruct,
+    fields: &[ModelField],
+    add_option: bool,
+) -> TokenStream2 {
+    let name = &node.ident;
+    let (impl_generics, ty_generics, where_clause) = node.generics.split_for_impl();
+
+    let methods = fields.iter().map(|field| {
+        let ident = &field.ident;
+        let ty = &field.ty;
+        let with_ident = format_ident!("with_{}", ident);
+        let getter_doc = format!("Returns the value of `{}`.", ident);
+        let with_doc = format!("Sets `{}`, returning the modified value.", ident);
+
+        if add_option {
+            quote! {
+                #[doc = #getter_doc]
+                pub fn #ident(&self) -> Option<&#ty> {
+                    self.#ident.as_ref()
+                }
+
+                #[doc = #with_doc]
+                pub fn #with_ident(mut self, #ident: #ty) -> Self {
+                    self.#ident = Some(#ident);
+                    self
+                }
+            }
+        } else {
+            quote! {
+                #[doc = #getter_doc]
+                pub fn #ident(&self) -> &#ty {
+                    &self.#ident
+                }
+
+                #[doc = #with_doc]
+                pub fn #with_ident(mut self, #ident: #ty) -> Self {
+                    self.#ident = #ident;
+                    self
+                }
+            }
+        }
+    });
+
+    quote! {
+        impl #impl_generics #name #ty_generics #where_clause {
+            #(#methods)*
+        }
+    }
+}
+
+/// Generates a `<Struct>Builder` type and its `<Struct>BuilderError`.
+pub(crate) fn impl_builder(
+    node: &ItemStruct,
+    fields: &[ModelField],
+    add_option: bool,
+) -> TokenStream2 {
+    let vis = &node.vis;
+    let name = &node.ident;
+    let builder = format_ident!("{}Builder", name);
+    let error = format_ident!("{}BuilderError", name);
+    let (impl_generics, ty_generics, where_clause) = node.generics.split_for_impl();
+    let generics = &node.generics;
+
+    let builder_doc = format!(
+        "Builds a [`{}`], checking that required fields are set.",
+        name
+    );
+    let error_doc = format!(
+        "Returned when building a [`{}`] without setting all of its required fields.",
+        name
+    );
+    let new_doc = format!("Returns a [`{}`] with no fields set.", builder);
+
+    let builder_fields = fields.iter().map(|field| {
+        let ident = &field.ident;
+        let ty = &field.ty;
+        quote!(#ident: Option<#ty>)
+    });
+
+    let setters = fields.iter().map(|field| {
+        let ident = &field.ident;
+        let ty = &field.ty;
+        let doc = format!("Sets `{}`.", ident);
+        quote! {
+            #[doc = #doc]
+            pub fn #ident(mut self, #ident: #ty) -> Self {
+                self.#ident = Some(#ident);
+                self
+            }
+        }
+    });
+
+    // Without `add_option`, there is nothing to fall back to for an unset field.
+    let (required, optional): (Vec<_>, Vec<_>) = fields
+        .iter()
+        .partition(|field| field.required || !add_option);
+    let required: Vec<_> = required.iter().map(|field| &field.ident).collect();
+    let optional: Vec<_> = optional.iter().map(|field| &field.ident).collect();
+    let required_names: Vec<_> = required.iter().map(|ident| ident.to_string()).collect();
+    let all: Vec<_> = fields.iter().map(|field| &field.ident).collect();
+    let required_values = required.iter().map(|ident| {
+        if add_option {
+            quote!(Some(#ident))
+        } else {
+            quote!(#ident)
+        }
+    });
+
+    let build_body = if required.is_empty() {
+        quote! {
+            Ok(#name { #(#optional,)* })
+        }
+    } else {
+        quote! {
+            match (#(#required,)*) {
+                (#(Some(#required),)*) => Ok(#name {
+                    #(#required: #required_values,)*
+                    #(#optional,)*
+                }),
+                (#(#required,)*) => {
+                    let mut missing = Vec::new();
+                    #(
+                        if #required.is_none() {
+                            missing.push(#required_names);
+                        }
+                    )*
+                    Err(#error { missing })
+                }
+            }
+        }
+    };
+
+    quote! {
+        #[doc = #builder_doc]
+        #[derive(Debug, Default)]
+        #vis struct #builder #generics #where_clause {
+            #(#builder_fields,)*
+        }
+
+        impl #impl_generics #builder #ty_generics #where_clause {
+            #[doc = #new_doc]
+            pub fn new() -> Self {
+                Self::default()
+            }
+
+            #(#setters)*
+
+            /// Constructs the model, returning an error listing any required fields that were not
+            /// set.
+            pub fn build(self) -> ::std::result::Result<#name #ty_generics, #error> {
+                let Self { #(#all,)* } = self;
+                #build_body
+            }
+        }
+
+        impl #impl_generics #name #ty_generics #where_clause {
+            #[doc = #new_doc]
+            pub fn builder() -> #builder #ty_generics {
+                #builder::new()
+            }
+        }
+
+        #[doc = #error_doc]
+        #[derive(Debug, Clone, PartialEq, Eq)]
+        #vis struct #error {
+            missing: Vec<&'static str>,
+        }
+
+        impl #error {
+            /// Returns the names of the required fields that were not set.
+            pub fn missing_fields(&self) -> &[&'static str] {
+                &self.missing
+            }
+        }
+
+        impl std::fmt::Display for #error {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                write!(
+                    f,
+                    "Missing required fields for '{}': {}",
+                    stringify!(#name),
+                    self.missing.join(", ")
+                )
+            }
+        }
+
+        impl std::error::Error for #error {}
+    }
+}
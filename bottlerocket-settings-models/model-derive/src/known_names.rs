@@ -0,0 +1,139 @@
+//! Generates the `KnownNames` implementation used to suggest names in deserialization errors.
+use super::{kebab_case, EnumTagging, ModelField};
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::ext::IdentExt;
+use syn::{Fields, Generics, Ident, ItemEnum, ItemStruct, Type};
+
+/// Generates the `KnownNames` implementation for a struct, which knows the names of its fields
+/// and, through their types, the names known at each field.
+/// `renamed` says whether the model's `rename_all` rule was applied, without which the serialized
+/// names aren't known.
+pub(crate) fn impl_struct_known_names(
+    node: &ItemStruct,
+    fields: &[ModelField],
+    renamed: bool,
+) -> TokenStream2 {
+    let body = if renamed {
+        let fields: Vec<_> = fields
+            .iter()
+            .map(|field| (field.serialized_name(), &field.ty))
+            .collect();
+        Some(fields_body(&fields))
+    } else {
+        None
+    };
+    impl_known_names(&node.ident, &node.generics, body)
+}
+
+/// Generates the `KnownNames` implementation for an enum, which knows the names of its variants
+/// and, for variants with data, the names known within them.
+/// Only externally tagged variants are found along the path to a field, so the names of other
+/// enums aren't known.
+pub(crate) fn impl_enum_known_names(
+    node: &ItemEnum,
+    tagging: &EnumTagging,
+    renamed: bool,
+) -> TokenStream2 {
+    let body = if renamed && *tagging == EnumTagging::External {
+        Some(variants_body(node))
+    } else {
+        None
+    };
+    impl_known_names(&node.ident, &node.generics, body)
+}
+
+/// Generates the implementation itself, which relies on the trait's default of knowing no names
+/// if there's no `body`.
+fn impl_known_names(name: &Ident, generics: &Generics, body: Option<TokenStream2>) -> TokenStream2 {
+    let helper = quote!(::bottlerocket_deserialize_helper);
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let method = body.map(|body| {
+        quote! {
+            fn known_names(
+                path: &[#helper::PathSegment],
+            ) -> Option<#helper::Names> {
+                #[allow(unused_imports)]
+                use #helper::__private::{Known as _, Unknown as _};
+                #body
+            }
+        }
+    });
+    quote! {
+        impl #impl_generics #helper::KnownNames for #name #ty_generics #where_clause {
+            #method
+        }
+    }
+}
+
+/// Generates a match on `path` that returns the names of the given fields if it's empty, or
+/// otherwise the names known by the type of the field it leads into.
+fn fields_body(fields: &[(String, &Type)]) -> TokenStream2 {
+    let helper = quote!(::bottlerocket_deserialize_helper);
+    let names = fields.iter().map(|(name, _)| name);
+    let arms = fields.iter().map(|(name, ty)| {
+        let lookup = lookup(ty);
+        quote!(#name => #lookup,)
+    });
+
+    quote! {
+        match path {
+            [] => Some(#helper::Names::Fields(&[#(#names),*])),
+            [#helper::PathSegment::Key(field), rest @ ..] => match field.as_str() {
+                #(#arms)*
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+}
+
+/// Generates a match on `path` that returns the names of the enum's variants if it's empty, or
+/// otherwise the names known within the variant it leads into.
+fn variants_body(node: &ItemEnum) -> TokenStream2 {
+    let helper = quote!(::bottlerocket_deserialize_helper);
+    let names = node
+        .variants
+        .iter()
+        .map(|variant| kebab_case(&variant.ident));
+    let arms = node.variants.iter().filter_map(|variant| {
+        let name = kebab_case(&variant.ident);
+        match &variant.fields {
+            Fields::Named(named) => {
+                // Struct variants are renamed with the same rule as struct fields.
+                let fields: Vec<_> = named
+                    .named
+                    .iter()
+                    .filter_map(|field| field.ident.as_ref().map(|ident| (ident, &field.ty)))
+                    .map(|(ident, ty)| (ident.unraw().to_string().replace('_', "-"), ty))
+                    .collect();
+                let body = fields_body(&fields);
+                Some(quote!(#name => { let path = rest; #body }))
+            }
+            Fields::Unnamed(unnamed) if unnamed.unnamed.len() == 1 => {
+                let lookup = unnamed.unnamed.first().map(|field| lookup(&field.ty));
+                Some(quote!(#name => #lookup,))
+            }
+            _ => None,
+        }
+    });
+
+    quote! {
+        match path {
+            [] => Some(#helper::Names::Variants(&[#(#names),*])),
+            [#helper::PathSegment::Key(variant), rest @ ..] => match variant.as_str() {
+                #(#arms)*
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+}
+
+/// Generates a lookup of the names known at `rest` within `ty`, which finds none if `ty` doesn't
+/// implement `KnownNames`.
+fn lookup(ty: &Type) -> TokenStream2 {
+    let helper = quote!(::bottlerocket_deserialize_helper);
+    quote!((&#helper::__private::Probe::<#ty>::new()).known_names(rest))
+}
@@ -1,8 +1,8 @@
 /*!
 # Overview
 
-This module provides a attribute-style procedural macro, `model`, that makes sure a struct is
-ready to be used as an API model.
+This module provides a attribute-style procedural macro, `model`, that makes sure a struct or enum
+is ready to be used as an API model.
 
 The goal is to reduce cognitive overhead when reading models.
 We do this by automatically specifying required attributes on structs and fields.
@@ -35,20 +35,227 @@ This can be disabled by specifying the argument `add_option = false`.
 
 Fields are all wrapped in `Option<...>`.
 Similar to the `serde` attribute added to fields, this is because we don't want users to have to specify fields they aren't changing, and can be disabled the same way, by specifying `add_option = false`.
+
+## Enums
+
+`model` can also be placed on enums, which get the same visibility and derives as structs.
+Variants are renamed to kebab-case, and so are the fields of struct variants, which are wrapped in
+`Option<...>` the same way as struct fields unless `add_option = false` is specified.
+Unknown fields are denied if the enum has any struct variants.
+
+By default, variants are externally tagged, as in serde.
+Specify `tag = "bla"` for internally tagged variants, `tag` and `content = "bla"` together for
+adjacently tagged variants, or `untagged = true` for untagged variants.
+When `impl_default = true` is given, the default variant must be marked with `#[default]`.
+
+Untagged enums with struct variants also require `add_option = false`.
+Serde uses the first variant that deserializes successfully, and a variant whose fields are all
+optional would accept any subset of them, hiding the variants after it.
+
+Fields of struct variants can be given `#[validate(...)]` attributes, as described below; the other
+field attributes are only supported on structs.
+
+```
+# use bottlerocket_model_derive::model;
+#[model(tag = "mode")]
+enum ExampleLogin {
+    Anonymous,
+    BasicAuth { user_name: String, password: String },
+}
+
+let auth: ExampleLogin = serde_json::from_str(r#"{"mode": "basic-auth", "user-name": "foo"}"#).unwrap();
+assert_eq!(
+    auth,
+    ExampleLogin::BasicAuth { user_name: Some("foo".to_string()), password: None }
+);
+```
+
+# Generated code
+
+## Accessors
+
+Specifying the argument `accessors = true` generates a getter for each field, returning a reference
+to the field's value, as well as a `with_<field>` method that sets the field and returns the struct.
+This allows the struct to be read and constructed by other crates without going through a
+serialized form, even if its fields are not public.
+
+## Builder
+
+Specifying the argument `builder = true` generates a `<Struct>Builder` type, returned by
+`<Struct>::builder()`, with a setter method for each field.
+Fields can be marked with `#[builder(required)]`; `build()` returns a `<Struct>BuilderError` listing
+every required field that was not set.
+When `add_option = false` is specified, all fields are required.
+
+```
+# use bottlerocket_model_derive::model;
+#[model(builder = true)]
+struct ExampleSettings {
+    #[builder(required)]
+    name: String,
+    count: u32,
+}
+
+let settings = ExampleSettings::builder().name("example".to_string()).build().unwrap();
+assert_eq!(settings.name, Some("example".to_string()));
+assert_eq!(settings.count, None);
+
+let err = ExampleSettings::builder().count(3).build().unwrap_err();
+assert_eq!(err.missing_fields(), &["name"]);
+```
+
+## Validation
+
+Fields can declare constraints with `#[validate(...)]` attributes, which generate an implementation
+of `bottlerocket_settings_sdk::model::ValidateFields`; every constraint is checked, and each
+violation is reported along with the path of the offending field.
+Settings models return `FieldChecks::declared()` from `SettingsModel::field_checks` so that the
+settings SDK checks incoming values before calling their `set` and `validate`.
+Constraints are only checked for fields that are set.
+For enums, the constraints on the fields of whichever struct variant is held are checked.
+
+The following constraints are supported, and several can be given in one attribute:
+* `range(min = ..., max = ...)`: the value must be within the given (inclusive) bounds.
+* `len(min = ..., max = ...)`: the value's `len()` must be within the given (inclusive) bounds.
+* `requires = "other_field"`: if the field is set, then `other_field` must also be set.
+* `custom = path::to::function`: the function is called with a reference to the value and must
+  return `Ok(())`; the `Display` output of any error it returns is reported.
+* `nested`: the value's own `ValidateFields` constraints are checked.
+  `Option`, `Vec`, and maps of nested values are supported.
+
+The implementation is only generated for models with `validate`, `deprecated_alias`, or `immutable`
+attributes, so crates whose models have none don't need to depend on the settings SDK.
+Models that need checks which can't be expressed as field constraints implement `ValidateFields`
+themselves.
+The generated code refers to the settings SDK as `::bottlerocket_settings_sdk`; crates which use
+another name for it can specify its path with `crate = "..."`.
+
+```
+# use bottlerocket_model_derive::model;
+use bottlerocket_settings_sdk::model::ValidateFields;
+
+#[model]
+struct ExampleSettings {
+    #[validate(range(min = 1, max = 10))]
+    count: u32,
+    #[validate(len(max = 2), requires = "count")]
+    servers: Vec<String>,
+}
+
+let settings = ExampleSettings {
+    count: None,
+    servers: Some(vec!["a".to_string(), "b".to_string(), "c".to_string()]),
+};
+assert_eq!(
+    settings.check_fields().unwrap_err().to_string(),
+    "'servers' requires 'count' to be set; 'servers' must have a length of at most 2"
+);
+```
+
+## Deprecated aliases
+
+A field can be marked with `#[deprecated_alias = "old_name"]` (more than once, if needed) when it has
+been renamed.
+The old name is still accepted when deserializing, but the field is always serialized under its
+current name.
+The struct's `ValidateFields` implementation finds each old name used in a serialized value, and the
+settings SDK reports a warning for each one used in a value passed to `set` or `validate`.
+Fields marked `#[validate(nested)]` are checked for their own deprecated names.
+
+```
+# use bottlerocket_model_derive::model;
+use bottlerocket_settings_sdk::model::ValidateFields;
+
+#[model]
+struct ExampleSettings {
+    #[deprecated_alias = "creds"]
+    credentials: Vec<String>,
+}
+
+let value = serde_json::json!({"creds": ["foo"]});
+let settings: ExampleSettings = serde_json::from_value(value.clone()).unwrap();
+assert_eq!(serde_json::to_string(&settings).unwrap(), r#"{"credentials":["foo"]}"#);
+assert_eq!(
+    ExampleSettings::find_deprecated_aliases(&value)[0].to_string(),
+    "'creds' is deprecated; use 'credentials' instead"
+);
+```
+
+## Immutable fields
+
+Fields that must not change once they have been set can be marked with `#[immutable]`.
+Their names are returned by the generated `ValidateFields::immutable_fields`, and the settings SDK
+rejects a `set` that changes or removes any of them from the current value.
+Fields that have not been set yet, as well as generated values, are unaffected.
+
+```
+# use bottlerocket_model_derive::model;
+use bottlerocket_settings_sdk::model::ValidateFields;
+
+#[model]
+struct ExampleSettings {
+    #[immutable]
+    seed: u32,
+    version_lock: String,
+}
+
+assert_eq!(ExampleSettings::immutable_fields(), &["seed"]);
+```
+
+## Known names
+
+Every model implements `bottlerocket_deserialize_helper::KnownNames`, which returns the names of a
+struct's fields or an enum's variants, and, through the types of its fields, the names known at any
+path within it.
+Settings models require it, so that the settings SDK can suggest the closest known names when a
+value has a field or variant that isn't recognized.
+Names are only known for models that use the serde attributes added by `model`, and, for enums,
+only for externally tagged variants.
+Crates which use `model` must depend on `bottlerocket-deserialize-helper`.
+
+```
+# use bottlerocket_model_derive::model;
+use bottlerocket_deserialize_helper::{KnownNames, Names, PathSegment};
+
+#[model]
+struct ExampleContainer {
+    image_source: String,
+}
+
+#[model]
+struct ExampleSettings {
+    containers: Vec<ExampleContainer>,
+}
+
+assert_eq!(
+    ExampleSettings::known_names(&[PathSegment::from("containers"), PathSegment::from(0)]),
+    Some(Names::Fields(&["image-source"]))
+);
+```
 */
 
 extern crate proc_macro;
 
+mod accessors;
+mod known_names;
+mod validate;
+
 use darling::{ast::NestedMeta, FromMeta};
 use proc_macro::TokenStream;
-use quote::ToTokens;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{quote, ToTokens};
+use syn::ext::IdentExt;
 use syn::visit_mut::{self, VisitMut};
-use syn::{parse_quote, Attribute, Field, ItemStruct, Visibility};
+use syn::{
+    parse_quote, Attribute, Expr, ExprLit, Field, Fields, Ident, Item, ItemEnum, ItemStruct, Lit,
+    LitStr, Meta, MetaNameValue, Path, Type, Variant, Visibility,
+};
 
 /// Define a `#[model]` attribute that can be placed on structs to be used in an API model.
 /// Model requirements are automatically applied to the struct and its fields.
 /// (The attribute must be placed on sub-structs; it can't be recursively applied to structs
 /// referenced in the given struct.)
+/// Enums are supported as well; see the crate documentation.
 #[proc_macro_attribute]
 pub fn model(args: TokenStream, input: TokenStream) -> TokenStream {
     // Parse args
@@ -60,10 +267,14 @@ pub fn model(args: TokenStream, input: TokenStream) -> TokenStream {
     let mut helper = ModelHelper::from(args);
 
     // Parse and modify source
-    let mut ast: ItemStruct =
-        syn::parse(input).expect("Unable to parse item `model` was placed on - is it a struct?");
-    helper.visit_item_struct_mut(&mut ast);
-    ast.into_token_stream().into()
+    let item: Item = syn::parse(input)
+        .expect("Unable to parse item `model` was placed on - is it a struct or enum?");
+    let output = match item {
+        Item::Struct(ast) => helper.expand_struct(ast),
+        Item::Enum(ast) => helper.expand_enum(ast),
+        _ => panic!("`model` can only be placed on structs and enums"),
+    };
+    output.into()
 }
 
 /// Store any args given by the user inside `#[model(...)]`.
@@ -73,6 +284,13 @@ struct ParsedArgs {
     rename: Option<String>,
     impl_default: Option<bool>,
     add_option: Option<bool>,
+    accessors: Option<bool>,
+    builder: Option<bool>,
+    tag: Option<String>,
+    content: Option<String>,
+    untagged: Option<bool>,
+    #[darling(rename = "crate")]
+    krate: Option<Path>,
 }
 
 /// Stores the user's requested options, plus any defaults for unspecified options.
@@ -81,16 +299,173 @@ struct ModelHelper {
     rename: Option<String>,
     impl_default: bool,
     add_option: bool,
+    accessors: bool,
+    builder: bool,
+    tagging: EnumTagging,
+    krate: Path,
+}
+
+/// How the variants of an enum are represented once serialized.
+#[derive(Debug, PartialEq)]
+enum EnumTagging {
+    /// `{"variant": {...}}`, serde's default.
+    External,
+    /// `{"tag": "variant", ...}`
+    Internal { tag: String },
+    /// `{"tag": "variant", "content": {...}}`
+    Adjacent { tag: String, content: String },
+    /// The variant's value alone; the first variant that deserializes successfully is used.
+    Untagged,
 }
 
 /// Takes the user's requested options and sets default values for anything unspecified.
 impl From<ParsedArgs> for ModelHelper {
     fn from(args: ParsedArgs) -> Self {
+        let tagging = match (args.tag, args.content, args.untagged.unwrap_or(false)) {
+            (None, None, false) => EnumTagging::External,
+            (Some(tag), None, false) => EnumTagging::Internal { tag },
+            (Some(tag), Some(content), false) => EnumTagging::Adjacent { tag, content },
+            (None, None, true) => EnumTagging::Untagged,
+            (None, Some(_), _) => panic!("`content` can only be given along with `tag`"),
+            (Some(_), _, true) => panic!("`untagged` cannot be given along with `tag`"),
+        };
+
         // Add any default values
         ModelHelper {
             rename: args.rename,
             impl_default: args.impl_default.unwrap_or(false),
             add_option: args.add_option.unwrap_or(true),
+            accessors: args.accessors.unwrap_or(false),
+            builder: args.builder.unwrap_or(false),
+            tagging,
+            krate: args
+                .krate
+                .unwrap_or_else(|| parse_quote!(::bottlerocket_settings_sdk)),
+        }
+    }
+}
+
+impl ModelHelper {
+    /// Applies the model requirements to a struct and generates any requested code for it.
+    fn expand_struct(&mut self, mut ast: ItemStruct) -> TokenStream2 {
+        if self.tagging != EnumTagging::External {
+            panic!("`tag`, `content`, and `untagged` can only be given for enums");
+        }
+
+        // Collect the fields as written by the user before we modify them.
+        let fields = ModelField::take_all(&mut ast.fields);
+        // Fields are only renamed by our serde attribute.
+        let renamed = !is_attr_set("serde", &ast.attrs);
+        self.visit_item_struct_mut(&mut ast);
+        add_deprecated_aliases(&mut ast, &fields);
+
+        let mut output = ast.to_token_stream();
+        output.extend(known_names::impl_struct_known_names(&ast, &fields, renamed));
+        if self.accessors {
+            output.extend(accessors::impl_accessors(&ast, &fields, self.add_option));
+        }
+        if self.builder {
+            output.extend(accessors::impl_builder(&ast, &fields, self.add_option));
+        }
+        if fields.iter().any(|field| {
+            !field.validations.is_empty() || !field.deprecated_aliases.is_empty() || field.immutable
+        }) {
+            output.extend(validate::impl_validate_fields(
+                &ast,
+                &fields,
+                self.add_option,
+                &self.krate,
+            ));
+        }
+        output
+    }
+
+    /// Applies the model requirements to an enum.
+    fn expand_enum(&mut self, mut ast: ItemEnum) -> TokenStream2 {
+        if self.accessors || self.builder {
+            panic!("`accessors` and `builder` can only be given for structs");
+        }
+
+        // Collect the fields of struct variants as written by the user before we modify them.
+        let mut variants = Vec::new();
+        for variant in ast.variants.iter_mut() {
+            if !matches!(variant.fields, Fields::Named(_)) {
+                continue;
+            }
+            if self.tagging == EnumTagging::Untagged && self.add_option {
+                // Every field would be optional, so the first struct variant would accept any
+                // subset of its own fields, including the fields of later variants that share
+                // their names.
+                panic!("struct variants of `untagged` enums require `add_option = false`");
+            }
+
+            let fields = ModelField::take_all(&mut variant.fields);
+            if fields.iter().any(|field| {
+                field.required || !field.deprecated_aliases.is_empty() || field.immutable
+            }) {
+                panic!(
+                    "`builder`, `deprecated_alias`, and `immutable` attributes can only be used on struct fields"
+                );
+            }
+            let prefix = match &self.tagging {
+                EnumTagging::External => Some(kebab_case(&variant.ident)),
+                EnumTagging::Adjacent { content, .. } => Some(content.clone()),
+                EnumTagging::Internal { .. } | EnumTagging::Untagged => None,
+            };
+            variants.push((variant.ident.clone(), prefix, fields));
+        }
+
+        // Variants are only renamed by our serde attribute, and the fields of struct variants by
+        // the ones we add to them.
+        let renamed = !is_attr_set("serde", &ast.attrs)
+            && ast
+                .variants
+                .iter()
+                .all(|variant| !is_attr_set("serde", &variant.attrs));
+        self.visit_item_enum_mut(&mut ast);
+        let mut output = ast.to_token_stream();
+        output.extend(known_names::impl_enum_known_names(
+            &ast,
+            &self.tagging,
+            renamed,
+        ));
+        if variants
+            .iter()
+            .any(|(_, _, fields)| fields.iter().any(|field| !field.validations.is_empty()))
+        {
+            output.extend(validate::impl_enum_validate_fields(
+                &ast,
+                &variants,
+                self.add_option,
+                &self.krate,
+            ));
+        }
+        output
+    }
+
+    /// Wraps a field in `Option<...>` and skips serializing it when unset, unless `add_option` is
+    /// false.
+    fn update_field(&self, node: &mut Field) {
+        // Add our serde attribute, if the user hasn't set one
+        if self.add_option {
+            if !is_attr_set("serde", &node.attrs) {
+                node.attrs.push(parse_quote!(
+                    #[serde(skip_serializing_if = "Option::is_none")]
+                ));
+            }
+
+            // Wrap each field's type in `Option<...>`
+            let ty = &node.ty;
+            node.ty = parse_quote!(Option<#ty>);
+        }
+    }
+
+    /// Returns the derive attribute added to models that don't specify their own.
+    fn derive_attr(&self) -> Attribute {
+        if self.impl_default {
+            parse_quote!(#[derive(Clone, Debug, Default, PartialEq, serde::Serialize, serde::Deserialize)])
+        } else {
+            parse_quote!(#[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)])
         }
     }
 }
@@ -122,11 +497,7 @@ impl VisitMut for ModelHelper {
         // Add our derives, if the user hasn't set any
         if !is_attr_set("derive", &node.attrs) {
             // Derive Default, if the user requested
-            let attr = if self.impl_default {
-                parse_quote!(#[derive(Clone, Debug, Default, PartialEq, serde::Serialize, serde::Deserialize)])
-            } else {
-                parse_quote!(#[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)])
-            };
+            let attr = self.derive_attr();
             // Rust 1.52 added a legacy_derive_helpers warning (soon to be an error) that yells if
             // you use an attribute macro before the derive macro that introduces it.  We should
             // always put derive macros at the start of the list to avoid this.
@@ -137,30 +508,201 @@ impl VisitMut for ModelHelper {
         visit_mut::visit_item_struct_mut(self, node);
     }
 
-    // Visit field definitions in structs.
-    fn visit_field_mut(&mut self, node: &mut Field) {
+    // Visit enum definitions.
+    fn visit_item_enum_mut(&mut self, node: &mut ItemEnum) {
         if let Visibility::Inherited = node.vis {
             node.vis = parse_quote!(pub)
         }
 
         // Add our serde attribute, if the user hasn't set one
-        if self.add_option {
+        if !is_attr_set("serde", &node.attrs) {
+            let mut args = Vec::new();
+            // Unknown fields can only appear in struct variants.
+            if node
+                .variants
+                .iter()
+                .any(|variant| matches!(variant.fields, Fields::Named(_)))
+            {
+                args.push(quote!(deny_unknown_fields));
+            }
+            args.push(quote!(rename_all = "kebab-case"));
+            if let Some(ref rename_to) = self.rename {
+                args.push(quote!(rename = #rename_to));
+            }
+            match &self.tagging {
+                EnumTagging::External => {}
+                EnumTagging::Internal { tag } => args.push(quote!(tag = #tag)),
+                EnumTagging::Adjacent { tag, content } => {
+                    args.push(quote!(tag = #tag, content = #content))
+                }
+                EnumTagging::Untagged => args.push(quote!(untagged)),
+            }
+            node.attrs.push(parse_quote!(#[serde(#(#args),*)]));
+        }
+
+        // Add our derives, if the user hasn't set any
+        if !is_attr_set("derive", &node.attrs) {
+            // Derive Default, if the user requested; the default variant must be marked with
+            // `#[default]`.
+            let attr = self.derive_attr();
+            // As with structs, derive macros must come first.
+            node.attrs.insert(0, attr);
+        }
+
+        // Let the default implementation do its thing, recursively.
+        visit_mut::visit_item_enum_mut(self, node);
+    }
+
+    // Visit variant definitions in enums.
+    fn visit_variant_mut(&mut self, node: &mut Variant) {
+        // Fields of tuple variants are left as they are; they hold the variant's value directly.
+        if let Fields::Named(fields) = &mut node.fields {
+            // The rename rule given on an enum only applies to variant names, so struct variants
+            // need their own rule for their fields.
             if !is_attr_set("serde", &node.attrs) {
-                node.attrs.push(parse_quote!(
-                    #[serde(skip_serializing_if = "Option::is_none")]
-                ));
+                node.attrs
+                    .push(parse_quote!(#[serde(rename_all = "kebab-case")]));
             }
 
-            // Wrap each field's type in `Option<...>`
-            let ty = &node.ty;
-            node.ty = parse_quote!(Option<#ty>);
+            // Fields of enum variants can't be given a visibility.
+            for field in fields.named.iter_mut() {
+                self.update_field(field);
+            }
         }
+    }
+
+    // Visit field definitions in structs.
+    fn visit_field_mut(&mut self, node: &mut Field) {
+        if let Visibility::Inherited = node.vis {
+            node.vis = parse_quote!(pub)
+        }
+
+        self.update_field(node);
 
         // Let the default implementation do its thing, recursively.
         visit_mut::visit_field_mut(self, node);
     }
 }
 
+/// A field of the model, as written by the user.
+#[derive(Debug)]
+struct ModelField {
+    ident: Ident,
+    /// The field's type before it is wrapped in `Option<...>`.
+    ty: Type,
+    /// Whether the field was marked with `#[builder(required)]`.
+    required: bool,
+    /// Constraints given in `#[validate(...)]` attributes.
+    validations: Vec<validate::FieldValidation>,
+    /// Old names given in `#[deprecated_alias = "..."]` attributes.
+    deprecated_aliases: Vec<LitStr>,
+    /// Whether the field was marked with `#[immutable]`.
+    immutable: bool,
+}
+
+impl ModelField {
+    /// Collects the named fields of a struct or enum variant, removing any attributes that only
+    /// have meaning to the `model` macro.
+    fn take_all(fields: &mut Fields) -> Vec<ModelField> {
+        let named = match fields {
+            Fields::Named(named) => named,
+            _ => return Vec::new(),
+        };
+
+        named
+            .named
+            .iter_mut()
+            .map(|field| {
+                let mut required = false;
+                let mut validations = Vec::new();
+                let mut deprecated_aliases = Vec::new();
+                let mut immutable = false;
+                field.attrs.retain(|attr| {
+                    if attr.path().is_ident("builder") {
+                        attr.parse_nested_meta(|meta| {
+                            if meta.path.is_ident("required") {
+                                required = true;
+                                Ok(())
+                            } else {
+                                Err(meta.error("unsupported `builder` option"))
+                            }
+                        })
+                        .expect("Unable to parse `builder` attribute on model field");
+                        false
+                    } else if attr.path().is_ident("validate") {
+                        validations.extend(
+                            validate::parse_attr(attr)
+                                .expect("Unable to parse `validate` attribute on model field"),
+                        );
+                        false
+                    } else if attr.path().is_ident("deprecated_alias") {
+                        let alias = match &attr.meta {
+                            Meta::NameValue(MetaNameValue {
+                                value:
+                                    Expr::Lit(ExprLit {
+                                        lit: Lit::Str(alias),
+                                        ..
+                                    }),
+                                ..
+                            }) => alias.clone(),
+                            _ => panic!("Expected `#[deprecated_alias = \"...\"]` on model field"),
+                        };
+                        deprecated_aliases.push(alias);
+                        false
+                    } else if attr.path().is_ident("immutable") {
+                        attr.meta
+                            .require_path_only()
+                            .expect("Expected `#[immutable]` on model field");
+                        immutable = true;
+                        false
+                    } else {
+                        true
+                    }
+                });
+
+                ModelField {
+                    ident: field
+                        .ident
+                        .clone()
+                        .expect("Named struct fields always have an identifier"),
+                    ty: field.ty.clone(),
+                    required,
+                    validations,
+                    deprecated_aliases,
+                    immutable,
+                }
+            })
+            .collect()
+    }
+
+    /// The name of the field once serialized, which matches the `rename_all` rule added to models.
+    fn serialized_name(&self) -> String {
+        self.ident.unraw().to_string().replace('_', "-")
+    }
+}
+
+/// Lets each field be deserialized from its deprecated names, if it has any.
+fn add_deprecated_aliases(node: &mut ItemStruct, fields: &[ModelField]) {
+    for (field, model_field) in node.fields.iter_mut().zip(fields) {
+        for alias in &model_field.deprecated_aliases {
+            field.attrs.push(parse_quote!(#[serde(alias = #alias)]));
+        }
+    }
+}
+
+/// Returns the name of an enum variant once serialized, which matches the `rename_all` rule added to
+/// models.
+fn kebab_case(ident: &Ident) -> String {
+    let mut name = String::new();
+    for (i, c) in ident.unraw().to_string().chars().enumerate() {
+        if c.is_uppercase() && i > 0 {
+            name.push('-');
+        }
+        name.extend(c.to_lowercase());
+    }
+    name
+}
+
 /// Checks whether an attribute named `attr_name` (e.g. "serde") is set in the given list of
 /// `syn::Attribute`s.
 fn is_attr_set(attr_name: &'static str, attrs: &[Attribute]) -> bool {
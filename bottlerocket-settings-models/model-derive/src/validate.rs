@@ -0,0 +1,367 @@
+//! Generates `ValidateFields` implementations from `#[validate(...)]` field attributes.
+use super::ModelField;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote};
+use syn::ext::IdentExt;
+use syn::{Attribute, Expr, Ident, ItemEnum, ItemStruct, LitStr, Path};
+
+/// A constraint given in a `#[validate(...)]` attribute.
+#[derive(Debug)]
+pub(crate) enum FieldValidation {
+    /// `range(min = ..., max = ...)`: the value must fall within the given bounds.
+    Range {
+        min: Option<Expr>,
+        max: Option<Expr>,
+    },
+    /// `len(min = ..., max = ...)`: the value's `len()` must fall within the given bounds.
+    Len {
+        min: Option<Expr>,
+        max: Option<Expr>,
+    },
+    /// `requires = "other_field"`: if the value is set, then `other_field` must be set.
+    Requires(Ident),
+    /// `custom = path::to::function`: the function must return `Ok(())` for the value.
+    Custom(Path),
+    /// `nested`: the value's own field constraints are checked.
+    Nested,
+}
+
+/// Parses every constraint from a `#[validate(...)]` attribute.
+pub(crate) fn parse_attr(attr: &Attribute) -> syn::Result<Vec<FieldValidation>> {
+    let mut validations = Vec::new();
+    attr.parse_nested_meta(|meta| {
+        if meta.path.is_ident("range") || meta.path.is_ident("len") {
+            let (mut min, mut max) = (None, None);
+            meta.parse_nested_meta(|bound| {
+                if bound.path.is_ident("min") {
+                    min = Some(bound.value()?.parse()?);
+                    Ok(())
+                } else if bound.path.is_ident("max") {
+                    max = Some(bound.value()?.parse()?);
+                    Ok(())
+                } else {
+                    Err(bound.error("expected `min` or `max`"))
+                }
+            })?;
+            if min.is_none() && max.is_none() {
+                return Err(meta.error("expected `min` and/or `max`"));
+            }
+            validations.push(if meta.path.is_ident("range") {
+                FieldValidation::Range { min, max }
+            } else {
+                FieldValidation::Len { min, max }
+            });
+            Ok(())
+        } else if meta.path.is_ident("requires") {
+            let other: LitStr = meta.value()?.parse()?;
+            validations.push(FieldValidation::Requires(other.parse()?));
+            Ok(())
+        } else if meta.path.is_ident("custom") {
+            let input = meta.value()?;
+            let path = if input.peek(LitStr) {
+                input.parse::<LitStr>()?.parse()?
+            } else {
+                input.parse()?
+            };
+            validations.push(FieldValidation::Custom(path));
+            Ok(())
+        } else if meta.path.is_ident("nested") {
+            validations.push(FieldValidation::Nested);
+            Ok(())
+        } else {
+            Err(meta.error("unsupported `validate` option"))
+        }
+    })?;
+    Ok(validations)
+}
+
+/// Generates an implementation of `ValidateFields` which checks each field's constraints.
+///
+/// `krate` is the path to the settings SDK crate.
+pub(crate) fn impl_validate_fields(
+    node: &ItemStruct,
+    fields: &[ModelField],
+    add_option: bool,
+    krate: &Path,
+) -> TokenStream2 {
+    let sdk = quote!(#krate::model);
+    let name = &node.ident;
+    let (impl_generics, ty_generics, where_clause) = node.generics.split_for_impl();
+    let checks = field_checks(fields, add_option, &sdk, |ident| quote!(self.#ident));
+    let deprecated_aliases = collect_deprecated_aliases(fields, krate);
+    let immutable_fields = immutable_fields(fields);
+
+    quote! {
+        impl #impl_generics #sdk::ValidateFields for #name #ty_generics #where_clause {
+            fn collect_field_errors(
+                &self,
+                path: &#sdk::FieldPath,
+                errors: &mut Vec<#sdk::FieldError>,
+            ) {
+                #(#checks)*
+            }
+
+            #deprecated_aliases
+
+            #immutable_fields
+        }
+    }
+}
+
+/// Generates `immutable_fields`, which lists the name of each field marked `#[immutable]`.
+fn immutable_fields(fields: &[ModelField]) -> Option<TokenStream2> {
+    if fields.iter().all(|field| !field.immutable) {
+        return None;
+    }
+
+    let immutable = fields
+        .iter()
+        .filter(|field| field.immutable)
+        .map(ModelField::serialized_name);
+
+    Some(quote! {
+        fn immutable_fields() -> &'static [&'static str] {
+            &[#(#immutable),*]
+        }
+    })
+}
+
+/// Generates `collect_deprecated_aliases`, which reports the deprecated names given to fields, and
+/// checks the fields of nested models for their own deprecated names.
+fn collect_deprecated_aliases(fields: &[ModelField], krate: &Path) -> Option<TokenStream2> {
+    let nested: Vec<_> = fields
+        .iter()
+        .filter(|field| {
+            field
+                .validations
+                .iter()
+                .any(|validation| matches!(validation, FieldValidation::Nested))
+        })
+        .collect();
+    if nested.is_empty()
+        && fields
+            .iter()
+            .all(|field| field.deprecated_aliases.is_empty())
+    {
+        return None;
+    }
+
+    let sdk = quote!(#krate::model);
+    let pairs = fields.iter().flat_map(|field| {
+        let serialized_name = field.serialized_name();
+        field
+            .deprecated_aliases
+            .iter()
+            .map(move |alias| quote!((#alias, #serialized_name)))
+    });
+    let nested = nested.iter().map(|field| {
+        let ty = &field.ty;
+        let serialized_name = field.serialized_name();
+        let aliases = &field.deprecated_aliases;
+        // The nested value may have been given under any of the field's names.
+        quote! {
+            for name in [#serialized_name, #(#aliases),*] {
+                if let Some(field) = value.get(name) {
+                    <#ty as #sdk::ValidateFields>::collect_deprecated_aliases(
+                        field,
+                        &path.join(name),
+                        warnings,
+                    );
+                }
+            }
+        }
+    });
+
+    Some(quote! {
+        fn collect_deprecated_aliases(
+            value: &#krate::__private::serde_json::Value,
+            path: &#sdk::FieldPath,
+            warnings: &mut Vec<#sdk::DeprecationWarning>,
+        ) {
+            warnings.extend(#sdk::DeprecationWarning::find_all(value, path, &[#(#pairs),*]));
+            #(#nested)*
+        }
+    })
+}
+
+/// Generates an implementation of `ValidateFields` for an enum which checks the constraints on the
+/// fields of whichever struct variant the value holds.
+///
+/// `variants` pairs each struct variant with its fields and the path its fields are serialized
+/// under, relative to the enum's own path.
+pub(crate) fn impl_enum_validate_fields(
+    node: &ItemEnum,
+    variants: &[(Ident, Option<String>, Vec<ModelField>)],
+    add_option: bool,
+    krate: &Path,
+) -> TokenStream2 {
+    let sdk = quote!(#krate::model);
+    let name = &node.ident;
+    let (impl_generics, ty_generics, where_clause) = node.generics.split_for_impl();
+
+    let arms = variants.iter().filter_map(|(variant, prefix, fields)| {
+        // Fields are bound under a prefix so that they can't shadow the generated code's variables.
+        let binding = |ident: &Ident| format_ident!("variant_{}", ident.unraw());
+        let checks: Vec<_> = field_checks(fields, add_option, &sdk, |ident| {
+            let binding = binding(ident);
+            quote!((*#binding))
+        })
+        .collect();
+        if checks.is_empty() {
+            return None;
+        }
+
+        let bindings = fields.iter().map(|field| {
+            let ident = &field.ident;
+            let binding = binding(ident);
+            quote!(#ident: #binding)
+        });
+        let path = match prefix {
+            Some(prefix) => quote!(&path.join(#prefix)),
+            None => quote!(path),
+        };
+        Some(quote! {
+            #[allow(unused_variables)]
+            Self::#variant { #(#bindings),* } => {
+                let path: &#sdk::FieldPath = #path;
+                #(#checks)*
+            }
+        })
+    });
+
+    quote! {
+        impl #impl_generics #sdk::ValidateFields for #name #ty_generics #where_clause {
+            fn collect_field_errors(
+                &self,
+                path: &#sdk::FieldPath,
+                errors: &mut Vec<#sdk::FieldError>,
+            ) {
+                #[allow(unreachable_patterns)]
+                match self {
+                    #(#arms)*
+                    _ => {}
+                }
+            }
+        }
+    }
+}
+
+/// Generates the checks for each field's constraints, reading each field's value with `access`.
+fn field_checks<'a, F>(
+    fields: &'a [ModelField],
+    add_option: bool,
+    sdk: &'a TokenStream2,
+    access: F,
+) -> impl Iterator<Item = TokenStream2> + 'a
+where
+    F: Fn(&Ident) -> TokenStream2 + 'a,
+{
+    fields.iter().filter_map(move |field| {
+        let ident = &field.ident;
+        let serialized_name = field.serialized_name();
+
+        let mut value_checks = Vec::new();
+        let mut presence_checks = Vec::new();
+        for validation in &field.validations {
+            match validation {
+                FieldValidation::Range { min, max } => {
+                    if let Some(min) = min {
+                        value_checks.push(quote! {
+                            if *value < #min {
+                                errors.push(#sdk::FieldError::new(
+                                    &field_path,
+                                    format!("must be at least {}", #min),
+                                ));
+                            }
+                        });
+                    }
+                    if let Some(max) = max {
+                        value_checks.push(quote! {
+                            if *value > #max {
+                                errors.push(#sdk::FieldError::new(
+                                    &field_path,
+                                    format!("must be at most {}", #max),
+                                ));
+                            }
+                        });
+                    }
+                }
+                FieldValidation::Len { min, max } => {
+                    if let Some(min) = min {
+                        value_checks.push(quote! {
+                            if value.len() < #min {
+                                errors.push(#sdk::FieldError::new(
+                                    &field_path,
+                                    format!("must have a length of at least {}", #min),
+                                ));
+                            }
+                        });
+                    }
+                    if let Some(max) = max {
+                        value_checks.push(quote! {
+                            if value.len() > #max {
+                                errors.push(#sdk::FieldError::new(
+                                    &field_path,
+                                    format!("must have a length of at most {}", #max),
+                                ));
+                            }
+                        });
+                    }
+                }
+                FieldValidation::Custom(path) => {
+                    value_checks.push(quote! {
+                        if let Err(e) = #path(value) {
+                            errors.push(#sdk::FieldError::new(&field_path, e.to_string()));
+                        }
+                    });
+                }
+                FieldValidation::Nested => {
+                    value_checks.push(quote! {
+                        #sdk::ValidateFields::collect_field_errors(value, &field_path, errors);
+                    });
+                }
+                // Fields are always present when they aren't wrapped in `Option`.
+                FieldValidation::Requires(_) if !add_option => {}
+                FieldValidation::Requires(other) => {
+                    let message = format!(
+                        "requires '{}' to be set",
+                        other.to_string().replace('_', "-")
+                    );
+                    let other_value = access(other);
+                    presence_checks.push(quote! {
+                        if value.is_some() && #other_value.is_none() {
+                            errors.push(#sdk::FieldError::new(&field_path, #message));
+                        }
+                    });
+                }
+            }
+        }
+
+        if value_checks.is_empty() && presence_checks.is_empty() {
+            return None;
+        }
+
+        let field_value = access(ident);
+        let value = if add_option {
+            quote!(#field_value.as_ref())
+        } else {
+            quote!(Some(&#field_value))
+        };
+        let value_checks = (!value_checks.is_empty()).then(|| {
+            quote! {
+                if let Some(value) = value {
+                    #(#value_checks)*
+                }
+            }
+        });
+
+        Some(quote! {
+            {
+                let field_path = path.join(#serialized_name);
+                let value = #value;
+                #(#presence_checks)*
+                #value_checks
+            }
+        })
+    })
+}
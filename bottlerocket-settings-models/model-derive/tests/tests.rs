@@ -0,0 +1,673 @@
+use bottlerocket_model_derive::model;
+
+#[model(impl_default = true, accessors = true, builder = true)]
+struct OptionalSettings {
+    #[builder(required)]
+    name: String,
+    #[builder(required)]
+    servers: Vec<String>,
+    count: u32,
+}
+
+#[model(add_option = false, accessors = true, builder = true)]
+struct PlainSettings {
+    name: String,
+    count: u32,
+}
+
+#[test]
+fn optional_accessors() {
+    let settings = OptionalSettings::default()
+        .with_name("foo".to_string())
+        .with_count(3);
+
+    assert_eq!(settings.name(), Some(&"foo".to_string()));
+    assert_eq!(settings.count(), Some(&3));
+    assert_eq!(settings.servers(), None);
+}
+
+#[test]
+fn plain_accessors() {
+    let settings = PlainSettings {
+        name: "foo".to_string(),
+        count: 1,
+    }
+    .with_count(2);
+
+    assert_eq!(settings.name(), "foo");
+    assert_eq!(*settings.count(), 2);
+}
+
+#[test]
+fn builder_sets_fields() {
+    let settings = OptionalSettings::builder()
+        .name("foo".to_string())
+        .servers(vec!["bar".to_string()])
+        .build()
+        .unwrap();
+
+    assert_eq!(
+        settings,
+        OptionalSettings {
+            name: Some("foo".to_string()),
+            servers: Some(vec!["bar".to_string()]),
+            count: None,
+        }
+    );
+}
+
+#[test]
+fn builder_reports_all_missing_fields() {
+    let err = OptionalSettings::builder().count(1).build().unwrap_err();
+    assert_eq!(err.missing_fields(), &["name", "servers"]);
+    assert_eq!(
+        err.to_string(),
+        "Missing required fields for 'OptionalSettings': name, servers"
+    );
+}
+
+#[test]
+fn builder_without_options_requires_all_fields() {
+    let err = PlainSettings::builder()
+        .name("foo".to_string())
+        .build()
+        .unwrap_err();
+    assert_eq!(err.missing_fields(), &["count"]);
+
+    let settings = PlainSettings::builder()
+        .name("foo".to_string())
+        .count(2)
+        .build()
+        .unwrap();
+    assert_eq!(
+        settings,
+        PlainSettings {
+            name: "foo".to_string(),
+            count: 2,
+        }
+    );
+}
+
+#[test]
+fn builder_attribute_is_not_serialized() {
+    let settings = OptionalSettings::builder()
+        .name("foo".to_string())
+        .servers(vec![])
+        .build()
+        .unwrap();
+    assert_eq!(
+        serde_json::to_string(&settings).unwrap(),
+        r#"{"name":"foo","servers":[]}"#
+    );
+}
+
+mod builder_with_result_alias {
+    use super::*;
+
+    // Extensions commonly shadow `Result` with an alias for their own error type.
+    #[allow(dead_code)]
+    type Result<T> = std::result::Result<T, std::convert::Infallible>;
+
+    #[model(builder = true)]
+    struct AliasedSettings {
+        #[builder(required)]
+        name: String,
+    }
+
+    #[test]
+    fn builder_ignores_result_alias() {
+        let settings = AliasedSettings::builder()
+            .name("foo".to_string())
+            .build()
+            .unwrap();
+        assert_eq!(settings.name, Some("foo".to_string()));
+    }
+}
+
+mod validation {
+    use super::*;
+    use bottlerocket_settings_sdk::model::{FieldError, FieldPath, ValidateFields};
+    use std::collections::HashMap;
+
+    fn not_localhost(value: &String) -> Result<(), String> {
+        if value == "localhost" {
+            Err("must not be localhost".to_string())
+        } else {
+            Ok(())
+        }
+    }
+
+    #[model(impl_default = true)]
+    struct Endpoint {
+        #[validate(custom = not_localhost)]
+        host: String,
+        #[validate(range(min = 1, max = 65535))]
+        port: u32,
+    }
+
+    #[model(impl_default = true)]
+    struct ValidatedSettings {
+        #[validate(range(min = 0, max = 100))]
+        low: i32,
+        #[validate(range(min = 0, max = 100), requires = "low")]
+        high: i32,
+        #[validate(len(min = 1, max = 2))]
+        names: Vec<String>,
+        #[validate(nested)]
+        endpoints: HashMap<String, Endpoint>,
+        #[validate(nested)]
+        primary: Endpoint,
+    }
+
+    #[model(add_option = false)]
+    struct PlainValidatedSettings {
+        #[validate(len(max = 3))]
+        name: String,
+    }
+
+    #[test]
+    fn valid_settings() {
+        let settings = ValidatedSettings {
+            low: Some(10),
+            high: Some(90),
+            names: Some(vec!["foo".to_string()]),
+            ..Default::default()
+        };
+        assert!(settings.check_fields().is_ok());
+        assert!(ValidatedSettings::default().check_fields().is_ok());
+    }
+
+    #[test]
+    fn reports_all_violations() {
+        let settings = ValidatedSettings {
+            low: None,
+            high: Some(101),
+            names: Some(vec![]),
+            endpoints: Some(HashMap::from([(
+                "api".to_string(),
+                Endpoint {
+                    host: Some("localhost".to_string()),
+                    port: Some(0),
+                },
+            )])),
+            primary: Some(Endpoint {
+                host: Some("example.com".to_string()),
+                port: Some(70000),
+            }),
+        };
+
+        let err = settings.check_fields().unwrap_err();
+        assert_eq!(
+            err.errors(),
+            &[
+                FieldError::new("high", "requires 'low' to be set"),
+                FieldError::new("high", "must be at most 100"),
+                FieldError::new("names", "must have a length of at least 1"),
+                FieldError::new(
+                    FieldPath::new(["endpoints", "api", "host"]),
+                    "must not be localhost"
+                ),
+                FieldError::new(
+                    FieldPath::new(["endpoints", "api", "port"]),
+                    "must be at least 1"
+                ),
+                FieldError::new(FieldPath::new(["primary", "port"]), "must be at most 65535"),
+            ]
+        );
+    }
+
+    #[test]
+    fn validates_fields_without_options() {
+        let settings = PlainValidatedSettings {
+            name: "toolong".to_string(),
+        };
+        assert_eq!(
+            settings.check_fields().unwrap_err().errors(),
+            &[FieldError::new("name", "must have a length of at most 3")]
+        );
+    }
+
+    mod renamed_sdk {
+        use bottlerocket_model_derive::model;
+        use bottlerocket_settings_sdk as sdk;
+        use sdk::model::ValidateFields;
+
+        #[model(crate = "sdk")]
+        struct RenamedSdkSettings {
+            #[validate(range(max = 1))]
+            count: u32,
+        }
+
+        #[test]
+        fn uses_given_sdk_path() {
+            let settings = RenamedSdkSettings { count: Some(2) };
+            assert!(settings.check_fields().is_err());
+        }
+    }
+}
+
+mod enums {
+    use super::*;
+
+    #[model(impl_default = true)]
+    enum Strategy {
+        #[default]
+        Uuid,
+        VolumeMounts,
+    }
+
+    #[model(tag = "mode")]
+    enum Login {
+        Anonymous,
+        BasicAuth { user_name: String, password: String },
+        Token { token: String },
+    }
+
+    #[model(tag = "type", content = "value")]
+    enum Adjacent {
+        Count(u32),
+        Named { long_name: String },
+    }
+
+    #[model(untagged = true)]
+    enum OneOrMany {
+        One(String),
+        Many(Vec<String>),
+    }
+
+    #[model]
+    enum External {
+        Plain,
+        Detailed { some_field: u32 },
+    }
+
+    #[model(add_option = false, tag = "mode")]
+    enum Required {
+        Token { token: String },
+    }
+
+    #[test]
+    fn unit_variants_are_kebab_case() {
+        assert_eq!(Strategy::default(), Strategy::Uuid);
+        assert_eq!(
+            serde_json::to_string(&Strategy::VolumeMounts).unwrap(),
+            r#""volume-mounts""#
+        );
+        assert_eq!(
+            serde_json::from_str::<Strategy>(r#""uuid""#).unwrap(),
+            Strategy::Uuid
+        );
+    }
+
+    #[test]
+    fn internally_tagged_variants() {
+        let auth: Login =
+            serde_json::from_str(r#"{"mode": "basic-auth", "user-name": "foo"}"#).unwrap();
+        assert_eq!(
+            auth,
+            Login::BasicAuth {
+                user_name: Some("foo".to_string()),
+                password: None,
+            }
+        );
+        assert_eq!(
+            serde_json::to_string(&auth).unwrap(),
+            r#"{"mode":"basic-auth","user-name":"foo"}"#
+        );
+        assert_eq!(
+            serde_json::from_str::<Login>(r#"{"mode": "anonymous"}"#).unwrap(),
+            Login::Anonymous
+        );
+        serde_json::from_str::<Login>(r#"{"mode": "token", "user-name": "foo"}"#).unwrap_err();
+    }
+
+    #[test]
+    fn adjacently_tagged_variants() {
+        let value = Adjacent::Named {
+            long_name: Some("foo".to_string()),
+        };
+        let serialized = serde_json::to_string(&value).unwrap();
+        assert_eq!(
+            serialized,
+            r#"{"type":"named","value":{"long-name":"foo"}}"#
+        );
+        assert_eq!(
+            serde_json::from_str::<Adjacent>(&serialized).unwrap(),
+            value
+        );
+        assert_eq!(
+            serde_json::from_str::<Adjacent>(r#"{"type":"count","value":3}"#).unwrap(),
+            Adjacent::Count(3)
+        );
+    }
+
+    #[test]
+    fn untagged_variants() {
+        assert_eq!(
+            serde_json::from_str::<OneOrMany>(r#""foo""#).unwrap(),
+            OneOrMany::One("foo".to_string())
+        );
+        assert_eq!(
+            serde_json::from_str::<OneOrMany>(r#"["foo"]"#).unwrap(),
+            OneOrMany::Many(vec!["foo".to_string()])
+        );
+    }
+
+    #[test]
+    fn externally_tagged_variants() {
+        assert_eq!(
+            serde_json::from_str::<External>(r#""plain""#).unwrap(),
+            External::Plain
+        );
+        assert_eq!(
+            serde_json::from_str::<External>(r#"{"detailed": {"some-field": 1}}"#).unwrap(),
+            External::Detailed {
+                some_field: Some(1)
+            }
+        );
+        serde_json::from_str::<External>(r#"{"detailed": {"other": 1}}"#).unwrap_err();
+    }
+
+    #[test]
+    fn variant_fields_without_options() {
+        serde_json::from_str::<Required>(r#"{"mode": "token"}"#).unwrap_err();
+        assert_eq!(
+            serde_json::from_str::<Required>(r#"{"mode": "token", "token": "t"}"#).unwrap(),
+            Required::Token {
+                token: "t".to_string()
+            }
+        );
+    }
+
+    mod validation {
+        use super::*;
+        use bottlerocket_settings_sdk::model::{FieldError, FieldPath, ValidateFields};
+
+        #[model]
+        enum ExternalLimit {
+            Unlimited,
+            Limited {
+                #[validate(range(min = 1))]
+                max_count: u32,
+            },
+        }
+
+        #[model(tag = "mode")]
+        enum InternalLimit {
+            Limited {
+                #[validate(range(min = 1), requires = "max_count")]
+                min_count: u32,
+                max_count: u32,
+            },
+        }
+
+        #[model(tag = "type", content = "value")]
+        enum AdjacentLimit {
+            Limited {
+                #[validate(len(max = 1))]
+                path: String,
+            },
+        }
+
+        #[model(untagged = true, add_option = false)]
+        enum UntaggedLimit {
+            Both {
+                min_count: u32,
+                #[validate(range(max = 10))]
+                max_count: u32,
+            },
+            Max {
+                #[validate(range(max = 5))]
+                max_count: u32,
+            },
+        }
+
+        #[test]
+        fn checks_held_variant() {
+            assert!(ExternalLimit::Unlimited.check_fields().is_ok());
+            let limit: ExternalLimit =
+                serde_json::from_str(r#"{"limited": {"max-count": 0}}"#).unwrap();
+            assert_eq!(
+                limit.check_fields().unwrap_err().errors(),
+                &[FieldError::new(
+                    FieldPath::new(["limited", "max-count"]),
+                    "must be at least 1"
+                )]
+            );
+        }
+
+        #[test]
+        fn uses_serialized_paths() {
+            let limit: InternalLimit =
+                serde_json::from_str(r#"{"mode": "limited", "min-count": 0}"#).unwrap();
+            assert_eq!(
+                limit.check_fields().unwrap_err().errors(),
+                &[
+                    FieldError::new("min-count", "requires 'max-count' to be set"),
+                    FieldError::new("min-count", "must be at least 1"),
+                ]
+            );
+
+            let limit: AdjacentLimit =
+                serde_json::from_str(r#"{"type": "limited", "value": {"path": "ab"}}"#).unwrap();
+            assert_eq!(
+                limit.check_fields().unwrap_err().errors(),
+                &[FieldError::new(
+                    FieldPath::new(["value", "path"]),
+                    "must have a length of at most 1"
+                )]
+            );
+        }
+
+        #[test]
+        fn untagged_struct_variants() {
+            let limit: UntaggedLimit = serde_json::from_str(r#"{"max-count": 8}"#).unwrap();
+            assert_eq!(limit, UntaggedLimit::Max { max_count: 8 });
+            assert_eq!(
+                limit.check_fields().unwrap_err().errors(),
+                &[FieldError::new("max-count", "must be at most 5")]
+            );
+
+            let limit: UntaggedLimit =
+                serde_json::from_str(r#"{"min-count": 1, "max-count": 8}"#).unwrap();
+            assert!(limit.check_fields().is_ok());
+        }
+    }
+}
+
+mod deprecated_aliases {
+    use super::*;
+    use bottlerocket_settings_sdk::model::ValidateFields;
+
+    #[model(impl_default = true)]
+    struct RenamedSettings {
+        #[deprecated_alias = "kernel"]
+        #[deprecated_alias = "kernel-args"]
+        kernel_parameters: Vec<String>,
+        #[deprecated_alias = "creds"]
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        credentials: Vec<String>,
+        unchanged: bool,
+    }
+
+    #[test]
+    fn accepts_old_names() {
+        let settings: RenamedSettings =
+            serde_json::from_str(r#"{"kernel-args": ["quiet"], "creds": []}"#).unwrap();
+        assert_eq!(
+            settings,
+            RenamedSettings {
+                kernel_parameters: Some(vec!["quiet".to_string()]),
+                credentials: Some(vec![]),
+                unchanged: None,
+            }
+        );
+    }
+
+    #[test]
+    fn serializes_new_names() {
+        let settings: RenamedSettings = serde_json::from_str(r#"{"kernel": ["quiet"]}"#).unwrap();
+        assert_eq!(
+            serde_json::to_string(&settings).unwrap(),
+            r#"{"kernel-parameters":["quiet"]}"#
+        );
+    }
+
+    #[model(impl_default = true)]
+    struct ParentSettings {
+        #[validate(nested)]
+        #[deprecated_alias = "child"]
+        children: Vec<RenamedSettings>,
+    }
+
+    fn deprecations<T: ValidateFields>(value: serde_json::Value) -> Vec<String> {
+        T::find_deprecated_aliases(&value)
+            .iter()
+            .map(ToString::to_string)
+            .collect()
+    }
+
+    #[test]
+    fn finds_deprecated_aliases() {
+        assert_eq!(
+            deprecations::<RenamedSettings>(
+                serde_json::json!({"kernel-args": [], "creds": [], "unchanged": true})
+            ),
+            vec![
+                "'kernel-args' is deprecated; use 'kernel-parameters' instead",
+                "'creds' is deprecated; use 'credentials' instead",
+            ]
+        );
+        assert!(deprecations::<RenamedSettings>(serde_json::json!({"credentials": []})).is_empty());
+    }
+
+    #[test]
+    fn finds_nested_deprecated_aliases() {
+        assert_eq!(
+            deprecations::<ParentSettings>(serde_json::json!({
+                "child": [{"kernel": []}, {"credentials": []}, {"creds": []}]
+            })),
+            vec![
+                "'child' is deprecated; use 'children' instead",
+                "'child.0.kernel' is deprecated; use 'child.0.kernel-parameters' instead",
+                "'child.2.creds' is deprecated; use 'child.2.credentials' instead",
+            ]
+        );
+    }
+}
+
+mod immutable {
+    use super::*;
+    use bottlerocket_settings_sdk::model::ValidateFields;
+
+    #[model(impl_default = true)]
+    struct ImmutableSettings {
+        #[immutable]
+        seed: u32,
+        #[immutable]
+        r#type: String,
+        ignore_waves: bool,
+    }
+
+    #[test]
+    fn lists_immutable_fields() {
+        assert_eq!(ImmutableSettings::immutable_fields(), &["seed", "type"]);
+    }
+}
+
+mod known_names {
+    use super::*;
+    use bottlerocket_deserialize_helper::{from_model_value, KnownNames, Names, PathSegment};
+    use std::collections::HashMap;
+
+    #[model]
+    struct Container {
+        source: String,
+        r#type: String,
+    }
+
+    #[model]
+    enum Source {
+        Registry,
+        LocalFile { file_path: String },
+        Mirror(Container),
+    }
+
+    #[model(tag = "mode")]
+    enum Tagged {
+        Anonymous,
+        Token { token: String },
+    }
+
+    #[model]
+    struct ClusterSettings {
+        cluster_name: String,
+        containers: HashMap<String, Container>,
+        sources: Vec<Source>,
+        tagged: Tagged,
+    }
+
+    fn path(segments: &[&str]) -> Vec<PathSegment> {
+        segments.iter().map(|&segment| segment.into()).collect()
+    }
+
+    #[test]
+    fn knows_field_names() {
+        assert_eq!(
+            ClusterSettings::known_names(&[]),
+            Some(Names::Fields(&[
+                "cluster-name",
+                "containers",
+                "sources",
+                "tagged"
+            ]))
+        );
+        assert_eq!(
+            ClusterSettings::known_names(&path(&["containers", "my-container"])),
+            Some(Names::Fields(&["source", "type"]))
+        );
+        assert_eq!(ClusterSettings::known_names(&path(&["cluster-name"])), None);
+        assert_eq!(ClusterSettings::known_names(&path(&["unknown"])), None);
+    }
+
+    #[test]
+    fn knows_externally_tagged_variant_names() {
+        let mut sources = vec![PathSegment::from("sources"), PathSegment::from(0)];
+        assert_eq!(
+            ClusterSettings::known_names(&sources),
+            Some(Names::Variants(&["registry", "local-file", "mirror"]))
+        );
+        sources.push("local-file".into());
+        assert_eq!(
+            ClusterSettings::known_names(&sources),
+            Some(Names::Fields(&["file-path"]))
+        );
+        assert_eq!(
+            Source::known_names(&path(&["mirror"])),
+            Some(Names::Fields(&["source", "type"]))
+        );
+    }
+
+    #[test]
+    fn tagged_enums_know_no_names() {
+        assert_eq!(Tagged::known_names(&[]), None);
+        assert_eq!(ClusterSettings::known_names(&path(&["tagged"])), None);
+    }
+
+    #[test]
+    fn suggests_known_names() {
+        let err = from_model_value::<ClusterSettings>(serde_json::json!({
+            "containers": {"my-container": {"sorce": "foo"}}
+        }))
+        .unwrap_err();
+        assert_eq!(err.path(), "containers.my-container.sorce");
+        assert_eq!(err.suggestions(), &["source"]);
+
+        let err = from_model_value::<ClusterSettings>(serde_json::json!({
+            "sources": ["registry", "mirorr"]
+        }))
+        .unwrap_err();
+        assert_eq!(err.path(), "sources[1]");
+        assert_eq!(err.suggestions(), &["mirror"]);
+    }
+}
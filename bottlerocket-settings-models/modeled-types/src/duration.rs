@@ -0,0 +1,193 @@
+//! Parsing and formatting for durations written in Go's style, such as `1h2.5m3s`, which is how
+//! both Kubernetes and ECS expect durations to be written.
+use std::time::Duration;
+
+const NANOS_PER_SECOND: u128 = 1_000_000_000;
+
+/// The longest duration Go can represent, since it counts nanoseconds in an `i64`.
+pub(crate) const MAX_NANOS: u128 = i64::MAX as u128;
+
+/// A unit that may appear in a duration, with the spellings it accepts and its length in
+/// nanoseconds. The first spelling is used when formatting.
+pub(crate) type DurationUnit = (&'static [&'static str], u128);
+
+pub(crate) const HOURS: DurationUnit = (&["h"], 3_600 * NANOS_PER_SECOND);
+pub(crate) const MINUTES: DurationUnit = (&["m"], 60 * NANOS_PER_SECOND);
+pub(crate) const SECONDS: DurationUnit = (&["s"], NANOS_PER_SECOND);
+pub(crate) const MILLISECONDS: DurationUnit = (&["ms"], 1_000_000);
+pub(crate) const MICROSECONDS: DurationUnit = (&["us", "µs"], 1_000);
+pub(crate) const NANOSECONDS: DurationUnit = (&["ns"], 1);
+
+/// Parses a duration made of numbers followed by units, where `units` lists the units allowed
+/// from longest to shortest. Each unit may be used once, in that order. As in Go, precision finer
+/// than a nanosecond is truncated.
+pub(crate) fn parse_duration(
+    input: &str,
+    units: &[DurationUnit],
+) -> Result<Duration, &'static str> {
+    const OVERFLOW: &str = "value is too large";
+
+    if input.is_empty() {
+        return Err("value is empty");
+    }
+
+    let mut nanos: u128 = 0;
+    // Units must come after the one used before them.
+    let mut remaining_units = units;
+    let mut rest = input;
+    while !rest.is_empty() {
+        let number_len = rest
+            .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+            .ok_or("missing unit")?;
+        let (number, unit_and_rest) = rest.split_at(number_len);
+        let unit_len = unit_and_rest
+            .find(|c: char| c.is_ascii_digit() || c == '.')
+            .unwrap_or(unit_and_rest.len());
+        let (unit, next) = unit_and_rest.split_at(unit_len);
+
+        let (whole, fraction) = number.split_once('.').unwrap_or((number, ""));
+        if whole.is_empty() || (number.contains('.') && fraction.is_empty()) {
+            return Err("invalid number");
+        }
+        if !fraction.chars().all(|c| c.is_ascii_digit()) {
+            return Err("invalid number");
+        }
+
+        let position = remaining_units
+            .iter()
+            .position(|(spellings, _)| spellings.contains(&unit))
+            .ok_or_else(|| {
+                if units.iter().any(|(spellings, _)| spellings.contains(&unit)) {
+                    "units must be used once each, from longest to shortest"
+                } else {
+                    "unknown unit"
+                }
+            })?;
+        let (_, unit_nanos) = remaining_units.get(position).ok_or("unknown unit")?;
+        remaining_units = remaining_units.get(position + 1..).unwrap_or_default();
+
+        let whole: u128 = whole.parse().map_err(|_| OVERFLOW)?;
+        // Digits past the 20th can't affect the result, since no unit is longer than 10^20ns.
+        let fraction = fraction.get(..fraction.len().min(20)).unwrap_or_default();
+        let fraction_nanos = if fraction.is_empty() {
+            0
+        } else {
+            let digits: u128 = fraction.parse().map_err(|_| "invalid number")?;
+            let scale = 10_u128.pow(u32::try_from(fraction.len()).map_err(|_| OVERFLOW)?);
+            digits * unit_nanos / scale
+        };
+
+        nanos = whole
+            .checked_mul(*unit_nanos)
+            .and_then(|value| value.checked_add(fraction_nanos))
+            .and_then(|value| value.checked_add(nanos))
+            .filter(|value| *value <= MAX_NANOS)
+            .ok_or(OVERFLOW)?;
+        rest = next;
+    }
+
+    Ok(from_nanos(nanos))
+}
+
+/// Writes a duration using whole numbers of each unit in `units`, e.g. `1h30m` or `1s500ms`, or
+/// `None` if the duration can't be written exactly with those units.
+pub(crate) fn format_duration(duration: Duration, units: &[DurationUnit]) -> Option<String> {
+    let mut remaining = duration.as_nanos();
+    let mut formatted = String::new();
+    for (spellings, unit_nanos) in units {
+        let count = remaining / unit_nanos;
+        if count > 0 {
+            formatted.push_str(&count.to_string());
+            formatted.push_str(spellings.first()?);
+        }
+        remaining %= unit_nanos;
+    }
+
+    if remaining != 0 {
+        return None;
+    }
+    if formatted.is_empty() {
+        formatted.push_str("0s");
+    }
+    Some(formatted)
+}
+
+/// Checks that a duration is short enough to be understood by Go programs.
+pub(crate) fn check_duration(duration: Duration) -> Result<(), &'static str> {
+    if duration.as_nanos() > MAX_NANOS {
+        return Err("value is too large");
+    }
+    Ok(())
+}
+
+fn from_nanos(nanos: u128) -> Duration {
+    let seconds = u64::try_from(nanos / NANOS_PER_SECOND).unwrap_or(u64::MAX);
+    let subsec_nanos = u32::try_from(nanos % NANOS_PER_SECOND).unwrap_or_default();
+    Duration::new(seconds, subsec_nanos)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const UNITS: &[DurationUnit] = &[HOURS, MINUTES, SECONDS, MILLISECONDS];
+
+    #[test]
+    fn parsed_durations() {
+        for (input, expected) in [
+            ("0s", Duration::ZERO),
+            ("90s", Duration::from_secs(90)),
+            ("1.5m", Duration::from_secs(90)),
+            ("1h2m3s10ms", Duration::from_millis(3_723_010)),
+            ("0.0001ms", Duration::from_nanos(100)),
+            ("1.00000000000000000000000001s", Duration::from_secs(1)),
+        ] {
+            assert_eq!(parse_duration(input, UNITS), Ok(expected), "{}", input);
+        }
+    }
+
+    #[test]
+    fn bad_durations() {
+        for (input, expected) in [
+            ("", "value is empty"),
+            ("10", "missing unit"),
+            (".5s", "invalid number"),
+            ("5.s", "invalid number"),
+            ("1..5s", "invalid number"),
+            ("9ns", "unknown unit"),
+            (
+                "1m2h",
+                "units must be used once each, from longest to shortest",
+            ),
+            (
+                "1s1s",
+                "units must be used once each, from longest to shortest",
+            ),
+            ("2562048h", "value is too large"),
+            (
+                "99999999999999999999999999999999999999999h",
+                "value is too large",
+            ),
+        ] {
+            assert_eq!(parse_duration(input, UNITS), Err(expected), "{}", input);
+        }
+    }
+
+    #[test]
+    fn formatted_durations() {
+        for (duration, expected) in [
+            (Duration::ZERO, Some("0s")),
+            (Duration::from_secs(90), Some("1m30s")),
+            (Duration::from_millis(3_723_010), Some("1h2m3s10ms")),
+            (Duration::from_secs(7_200), Some("2h")),
+            (Duration::from_micros(1), None),
+        ] {
+            assert_eq!(
+                format_duration(duration, UNITS).as_deref(),
+                expected,
+                "{:?}",
+                duration
+            );
+        }
+    }
+}
@@ -3,12 +3,17 @@ use regex::Regex;
 use serde::{Deserialize, Serialize};
 // Just need serde's Error in scope to get its trait methods
 use super::error::{self, big_pattern_error};
+use crate::duration::{
+    check_duration, format_duration, parse_duration, DurationUnit, HOURS, MICROSECONDS,
+    MILLISECONDS, MINUTES, NANOSECONDS, SECONDS,
+};
 use bottlerocket_scalar::traits::{Scalar, Validate};
 use bottlerocket_scalar::ValidationError;
 use bottlerocket_scalar_derive::Scalar;
 use bottlerocket_string_impls_for::string_impls_for;
 use snafu::ensure;
 use std::convert::TryFrom;
+use std::time::Duration;
 
 /// ECSAttributeKey represents a string that contains a valid ECS attribute key.  It stores
 /// the original string and makes it accessible through standard traits.
@@ -244,37 +249,88 @@ mod test_ecs_agent_image_pull_behavior {
     }
 }
 
-/// ECSDurationValue represents a string that contains a valid ECS duration value
+/// ECSDurationValue represents a string that contains a valid ECS duration value, such as
+/// `1h2m3s` or `1ms1us1ns`.
+///
+/// The duration is parsed so that values can be compared, while the original string is kept for
+/// serialization. Durations too long for the ECS agent to represent are rejected.
 #[derive(Debug, Clone, Eq, PartialEq, Hash)]
 pub struct ECSDurationValue {
     inner: String,
+    duration: Duration,
 }
 
-lazy_static! {
-    pub(crate) static ref ECS_DURATION_VALUE: Regex =
-        Regex::new(r"^(([0-9]+\.)?[0-9]+h)?(([0-9]+\.)?[0-9]+m)?(([0-9]+\.)?[0-9]+s)?(([0-9]+\.)?[0-9]+ms)?(([0-9]+\.)?[0-9]+(u|µ)s)?(([0-9]+\.)?[0-9]+ns)?$").unwrap();
+/// The units ECS durations may use, from longest to shortest.
+const ECS_DURATION_UNITS: &[DurationUnit] = &[
+    HOURS,
+    MINUTES,
+    SECONDS,
+    MILLISECONDS,
+    MICROSECONDS,
+    NANOSECONDS,
+];
+
+impl ECSDurationValue {
+    /// Returns the length of the duration.
+    pub fn duration(&self) -> Duration {
+        self.duration
+    }
+}
+
+impl PartialOrd for ECSDurationValue {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for ECSDurationValue {
+    /// Orders durations by their length; equal lengths written differently, such as `1m` and
+    /// `60s`, are ordered by how they are written.
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.duration
+            .cmp(&other.duration)
+            .then_with(|| self.inner.cmp(&other.inner))
+    }
 }
 
 impl TryFrom<&str> for ECSDurationValue {
     type Error = error::Error;
 
     fn try_from(input: &str) -> Result<Self, Self::Error> {
-        ensure!(
-            !input.is_empty() && ECS_DURATION_VALUE.is_match(input),
-            error::InvalidECSDurationValueSnafu { input }
-        );
+        let duration = parse_duration(input, ECS_DURATION_UNITS)
+            .map_err(|msg| error::InvalidECSDurationValueSnafu { input, msg }.build())?;
         Ok(ECSDurationValue {
             inner: input.to_string(),
+            duration,
         })
     }
 }
 
+/// Durations are written with whole numbers of each unit, e.g. `1m30s` for 90 seconds.
+impl TryFrom<Duration> for ECSDurationValue {
+    type Error = error::Error;
+
+    fn try_from(duration: Duration) -> Result<Self, Self::Error> {
+        let inner = check_duration(duration)
+            .and_then(|()| {
+                // Every duration can be written in nanoseconds.
+                format_duration(duration, ECS_DURATION_UNITS).ok_or("value is too precise")
+            })
+            .map_err(|msg| {
+                let input = format!("{:?}", duration);
+                error::InvalidECSDurationValueSnafu { input, msg }.build()
+            })?;
+        Ok(ECSDurationValue { inner, duration })
+    }
+}
+
 string_impls_for!(ECSDurationValue, "ECSDurationValue");
 
 #[cfg(test)]
 mod test_ecs_duration_value {
     use super::ECSDurationValue;
     use std::convert::TryFrom;
+    use std::time::Duration;
 
     #[test]
     fn valid_values() {
@@ -303,9 +359,41 @@ mod test_ecs_duration_value {
             "ten second",
             "1m2h",
             "1y2w",
+            "1us1µs",
+            "2562048h",
             &"a".repeat(23),
         ] {
             ECSDurationValue::try_from(*err).unwrap_err();
         }
     }
+
+    fn duration(input: &str) -> ECSDurationValue {
+        ECSDurationValue::try_from(input).unwrap()
+    }
+
+    #[test]
+    fn parsed_values() {
+        assert_eq!(duration("1.5h3.5m").duration(), Duration::from_secs(5_610));
+        assert_eq!(duration("1s1µs1ns").duration(), Duration::new(1, 1_001));
+        assert!(duration("90s") > duration("1m"));
+        assert!(duration("999ms") < duration("1s"));
+        assert_eq!(
+            duration("1m").duration().cmp(&duration("60s").duration()),
+            std::cmp::Ordering::Equal
+        );
+    }
+
+    #[test]
+    fn from_duration() {
+        for (value, expected) in [
+            (Duration::from_secs(90), "1m30s"),
+            (Duration::new(3_600, 1_001), "1h1us1ns"),
+            (Duration::ZERO, "0s"),
+        ] {
+            let converted = ECSDurationValue::try_from(value).unwrap();
+            assert_eq!(converted.duration(), value);
+            assert_eq!(serde_json::to_value(&converted).unwrap(), expected);
+        }
+        ECSDurationValue::try_from(Duration::MAX).unwrap_err();
+    }
 }
@@ -13,7 +13,12 @@ use std::collections::HashMap;
 use std::convert::TryFrom;
 use std::fmt::{self, Display, Formatter};
 use std::net::IpAddr;
+use std::time::Duration;
 
+use crate::duration::{
+    check_duration, format_duration, parse_duration, DurationUnit, HOURS, MILLISECONDS, MINUTES,
+    SECONDS,
+};
 use crate::SingleLineString;
 
 // Declare constant values usable by any type
@@ -654,14 +659,120 @@ mod test_reserved_resources_key {
     }
 }
 
-/// // =^..^=   =^..^=   =^..^=   =^..^=   =^..^=   =^..^=   =^..^=   =^..^=   =^..^=
+// =^..^=   =^..^=   =^..^=   =^..^=   =^..^=   =^..^=   =^..^=   =^..^=   =^..^=
 
 /// KubernetesQuantityValue represents a string that contains a valid kubernetes quantity value.
 /// https://kubernetes.io/docs/concepts/configuration/manage-resources-containers/
-
-#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+///
+/// The quantity is parsed into a whole number of nano-units, which allows quantities to be
+/// compared and added, while the original string is kept for serialization. As in Kubernetes,
+/// precision finer than a nano-unit is rounded up.  Quantities too large to represent, such as
+/// `1e99`, are still valid, and saturate at the largest number of nano-units.
+///
+/// Quantities are compared by value, so `1Ki` and `1024` are equal even though they're written
+/// differently.
+#[derive(Debug, Clone)]
 pub struct KubernetesQuantityValue {
     inner: String,
+    nanos: i128,
+    format: QuantityFormat,
+}
+
+/// How a quantity was written, which is kept when formatting the results of arithmetic.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+enum QuantityFormat {
+    /// Powers of two, e.g. `1Ki`.
+    BinarySI,
+    /// Powers of ten with SI suffixes, e.g. `1k` or `100m`.
+    DecimalSI,
+    /// Powers of ten with exponents, e.g. `1e3`.
+    DecimalExponent,
+}
+
+const NANOS_PER_UNIT: u128 = 1_000_000_000;
+
+/// Decimal suffixes and the power of ten they stand for.
+const DECIMAL_SUFFIXES: &[(&str, u32)] = &[
+    ("E", 18),
+    ("P", 15),
+    ("T", 12),
+    ("G", 9),
+    ("M", 6),
+    ("k", 3),
+    ("", 0),
+];
+
+/// Suffixes for fractions of a unit, and the negated power of ten they stand for.
+const FRACTIONAL_SUFFIXES: &[(&str, u32)] = &[("m", 3), ("u", 6), ("n", 9)];
+
+/// Binary suffixes and the power of two they stand for.
+const BINARY_SUFFIXES: &[(&str, u32)] = &[
+    ("Ei", 60),
+    ("Pi", 50),
+    ("Ti", 40),
+    ("Gi", 30),
+    ("Mi", 20),
+    ("Ki", 10),
+];
+
+impl KubernetesQuantityValue {
+    /// Returns the quantity as a whole number of nano-units, e.g. `1500000000` for `1.5`.
+    pub fn nanos(&self) -> i128 {
+        self.nanos
+    }
+
+    /// Creates a quantity from a whole number of nano-units, written with decimal suffixes.
+    pub fn from_nanos(nanos: i128) -> Self {
+        Self::formatted(nanos, QuantityFormat::DecimalSI)
+    }
+
+    /// Adds two quantities, returning `None` on overflow. The result is written in the same style
+    /// as `self`.
+    pub fn checked_add(&self, other: &Self) -> Option<Self> {
+        let nanos = self.nanos.checked_add(other.nanos)?;
+        Some(Self::formatted(nanos, self.format))
+    }
+
+    /// Subtracts `other` from this quantity, returning `None` on overflow. The result is written
+    /// in the same style as `self`.
+    pub fn checked_sub(&self, other: &Self) -> Option<Self> {
+        let nanos = self.nanos.checked_sub(other.nanos)?;
+        Some(Self::formatted(nanos, self.format))
+    }
+
+    fn formatted(nanos: i128, format: QuantityFormat) -> Self {
+        Self {
+            inner: format_quantity(nanos, format),
+            nanos,
+            format,
+        }
+    }
+}
+
+impl PartialEq for KubernetesQuantityValue {
+    fn eq(&self, other: &Self) -> bool {
+        self.nanos == other.nanos
+    }
+}
+
+impl Eq for KubernetesQuantityValue {}
+
+impl std::hash::Hash for KubernetesQuantityValue {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.nanos.hash(state);
+    }
+}
+
+impl PartialOrd for KubernetesQuantityValue {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for KubernetesQuantityValue {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.nanos.cmp(&other.nanos)
+    }
 }
 
 impl TryFrom<&str> for KubernetesQuantityValue {
@@ -676,14 +787,125 @@ impl TryFrom<&str> for KubernetesQuantityValue {
                 input
             }
         );
+        let (nanos, format) = parse_quantity(input)
+            .map_err(|msg| error::InvalidKubernetesQuantitySnafu { input, msg }.build())?;
 
         Ok(KubernetesQuantityValue {
             inner: input.to_string(),
+            nanos,
+            format,
         })
     }
 }
 string_impls_for!(KubernetesQuantityValue, "KubernetesQuantityValue");
 
+/// Parses a quantity that matches `KUBERNETES_QUANTITY` into nano-units, saturating values that
+/// are too large to represent.
+fn parse_quantity(input: &str) -> Result<(i128, QuantityFormat), &'static str> {
+    let number_len = input
+        .find(|c: char| !(c.is_ascii_digit() || matches!(c, '.' | '+' | '-')))
+        .unwrap_or(input.len());
+    let (number, suffix) = input.split_at(number_len);
+
+    // The number is read as a whole number of units of 10^-`scale`.
+    let (negative, number) = match number.strip_prefix('-') {
+        Some(number) => (true, number),
+        None => (false, number.strip_prefix('+').unwrap_or(number)),
+    };
+    let (whole, fraction) = number.split_once('.').unwrap_or((number, ""));
+    if (whole.is_empty() && fraction.is_empty()) || fraction.contains('.') {
+        return Err("invalid number");
+    }
+    let digits = format!("{}{}", whole, fraction);
+    let scale = i64::try_from(fraction.len()).map_err(|_| "invalid number")?;
+
+    // Find the power of ten and power of two the number is multiplied by.
+    let (exponent, binary_exponent, format) = if let Some(exponent) = suffix.strip_prefix('e') {
+        let exponent: i64 = exponent.parse().map_err(|_| "invalid exponent")?;
+        (exponent, 0, QuantityFormat::DecimalExponent)
+    } else if let Some((_, power)) = BINARY_SUFFIXES.iter().find(|(s, _)| *s == suffix) {
+        (0, *power, QuantityFormat::BinarySI)
+    } else if let Some((_, power)) = DECIMAL_SUFFIXES.iter().find(|(s, _)| *s == suffix) {
+        (i64::from(*power), 0, QuantityFormat::DecimalSI)
+    } else if let Some((_, power)) = FRACTIONAL_SUFFIXES.iter().find(|(s, _)| *s == suffix) {
+        (-i64::from(*power), 0, QuantityFormat::DecimalSI)
+    } else {
+        return Err("unknown suffix");
+    };
+
+    let nanos =
+        scale_nanos(&digits, binary_exponent, exponent.saturating_sub(scale)).unwrap_or(i128::MAX);
+    Ok((if negative { -nanos } else { nanos }, format))
+}
+
+/// Returns `digits * 2^binary_exponent * 10^(exponent + 9)` rounded up, or `None` if it's too large
+/// to represent.
+fn scale_nanos(digits: &str, binary_exponent: u32, exponent: i64) -> Option<i128> {
+    let ten_to = |power: i64| {
+        u32::try_from(power)
+            .ok()
+            .and_then(|power| 10_i128.checked_pow(power))
+    };
+    let power_of_ten = exponent.saturating_add(9);
+    let nanos = digits
+        .parse::<i128>()
+        .ok()?
+        .checked_mul(1_i128 << binary_exponent)?;
+    if power_of_ten >= 0 {
+        nanos.checked_mul(ten_to(power_of_ten)?)
+    } else {
+        // Precision beyond what can be represented is rounded up, so tiny values don't become 0.
+        let divisor = ten_to(power_of_ten.saturating_neg()).unwrap_or(i128::MAX);
+        Some(nanos / divisor + i128::from(nanos % divisor != 0))
+    }
+}
+
+/// Writes a number of nano-units in the given style, using the largest suffix that represents the
+/// value exactly.
+fn format_quantity(nanos: i128, format: QuantityFormat) -> String {
+    let sign = if nanos < 0 { "-" } else { "" };
+    let magnitude = nanos.unsigned_abs();
+    if magnitude == 0 {
+        return "0".to_string();
+    }
+
+    let units = magnitude / NANOS_PER_UNIT;
+    if format == QuantityFormat::BinarySI && magnitude.is_multiple_of(NANOS_PER_UNIT) {
+        if let Some((suffix, power)) = BINARY_SUFFIXES
+            .iter()
+            .find(|(_, power)| units.is_multiple_of(1_u128 << power))
+        {
+            return format!("{}{}{}", sign, units >> power, suffix);
+        }
+    }
+
+    // Try each power of ten from 10^27 (exa-units) down to 10^0 (nano-units), in steps of three.
+    for power in (0..=27_u32).rev().step_by(3) {
+        let divisor = 10_u128.pow(power);
+        if magnitude.is_multiple_of(divisor) {
+            let value = magnitude / divisor;
+            // Convert from a power of nano-units to a power of units.
+            let unit_power = i64::from(power) - 9;
+            let suffix = match format {
+                QuantityFormat::DecimalExponent if unit_power == 0 => String::new(),
+                QuantityFormat::DecimalExponent => format!("e{}", unit_power),
+                _ => DECIMAL_SUFFIXES
+                    .iter()
+                    .find(|(_, p)| i64::from(*p) == unit_power)
+                    .or_else(|| {
+                        FRACTIONAL_SUFFIXES
+                            .iter()
+                            .find(|(_, p)| -i64::from(*p) == unit_power)
+                    })
+                    .map(|(suffix, _)| suffix.to_string())
+                    .unwrap_or_default(),
+            };
+            return format!("{}{}{}", sign, value, suffix);
+        }
+    }
+    format!("{}{}n", sign, magnitude)
+}
+
 #[cfg(test)]
 mod test_kubernetes_quantity_value {
     use super::KubernetesQuantityValue;
@@ -709,11 +931,101 @@ mod test_kubernetes_quantity_value {
             "1100KTi",
             "100Kiii",
             "1000i",
+            "1..5",
             &"a".repeat(64),
         ] {
             KubernetesQuantityValue::try_from(*err).unwrap_err();
         }
     }
+
+    fn quantity(input: &str) -> KubernetesQuantityValue {
+        KubernetesQuantityValue::try_from(input).unwrap()
+    }
+
+    #[test]
+    fn parsed_kubernetes_quantity_value() {
+        for (input, nanos) in [
+            ("0", 0),
+            ("1", 1_000_000_000),
+            ("1.5", 1_500_000_000),
+            ("+2", 2_000_000_000),
+            ("-100m", -100_000_000),
+            ("100m", 100_000_000),
+            ("5u", 5_000),
+            ("7n", 7),
+            ("0.5n", 1),
+            ("1k", 1_000_000_000_000),
+            ("1.5M", 1_500_000_000_000_000),
+            ("1Ki", 1_024_000_000_000),
+            ("1.5Gi", 1_610_612_736_000_000_000),
+            ("1Ei", 1_152_921_504_606_846_976_000_000_000),
+            ("129e6", 129_000_000_000_000_000),
+            ("1e3", 1_000_000_000_000),
+            ("1e99", i128::MAX),
+            ("-1e99", -i128::MAX),
+            ("99999999999999999999999999999999999999999", i128::MAX),
+        ] {
+            assert_eq!(quantity(input).nanos(), nanos, "{}", input);
+        }
+    }
+
+    #[test]
+    fn kubernetes_quantity_value_round_trip() {
+        for input in ["1.50Gi", "0100m", "129e6", "+5"] {
+            let value = quantity(input);
+            assert_eq!(value.to_string(), input);
+            assert_eq!(serde_json::to_value(&value).unwrap(), input);
+        }
+    }
+
+    #[test]
+    fn kubernetes_quantity_value_ordering() {
+        let mut values = [
+            quantity("1Gi"),
+            quantity("1G"),
+            quantity("1024Mi"),
+            quantity("500Mi"),
+        ];
+        values.sort();
+        let sorted: Vec<_> = values.iter().map(|value| value.as_ref()).collect();
+        assert_eq!(sorted, ["500Mi", "1G", "1Gi", "1024Mi"]);
+        assert_eq!(quantity("1Ki"), quantity("1024"));
+        assert_eq!(
+            quantity("1024Mi").cmp(&quantity("1Gi")),
+            std::cmp::Ordering::Equal
+        );
+        assert_ne!(quantity("1Ki"), quantity("1k"));
+        assert!(quantity("100m") < quantity("1"));
+        assert!(quantity("-1") < quantity("1n"));
+    }
+
+    #[test]
+    fn kubernetes_quantity_value_arithmetic() {
+        for (lhs, rhs, sum, difference) in [
+            ("1Gi", "512Mi", "1536Mi", "512Mi"),
+            ("1Gi", "1G", "2073741824", "73741824"),
+            ("100m", "1", "1100m", "-900m"),
+            ("1k", "500", "1500", "500"),
+            ("2e3", "1e3", "3e3", "1e3"),
+            ("1Ki", "1Ki", "2Ki", "0"),
+        ] {
+            assert_eq!(
+                quantity(lhs).checked_add(&quantity(rhs)).unwrap(),
+                quantity(sum)
+            );
+            assert_eq!(
+                quantity(lhs).checked_sub(&quantity(rhs)).unwrap(),
+                quantity(difference)
+            );
+        }
+
+        let max = KubernetesQuantityValue::from_nanos(i128::MAX);
+        assert!(max.checked_add(&quantity("1n")).is_none());
+        assert_eq!(
+            KubernetesQuantityValue::from_nanos(1_500_000_000).as_ref(),
+            "1500m"
+        );
+    }
 }
 
 // =^..^=   =^..^=   =^..^=   =^..^=   =^..^=   =^..^=   =^..^=   =^..^=   =^..^=
@@ -759,9 +1071,7 @@ mod test_kubernetes_cloud_provider {
 
     #[test]
     fn disallowed_providers() {
-        for err in &["internal"] {
-            KubernetesCloudProvider::try_from(*err).unwrap_err();
-        }
+        KubernetesCloudProvider::try_from("internal").unwrap_err();
     }
 }
 
@@ -818,43 +1128,82 @@ mod test_cpu_manager_policy {
 
 // =^..^=   =^..^=   =^..^=   =^..^=   =^..^=   =^..^=   =^..^=   =^..^=   =^..^=
 
-/// KubernetesDurationValue represents a string that contains a valid Kubernetes duration value.
+/// KubernetesDurationValue represents a string that contains a valid Kubernetes duration value,
+/// such as `1h2m3s10ms`.
+///
+/// The duration is parsed so that values can be compared, while the original string is kept for
+/// serialization. Durations too long for the kubelet to represent are rejected.
 #[derive(Debug, Clone, Eq, PartialEq, Hash)]
 pub struct KubernetesDurationValue {
     inner: String,
+    duration: Duration,
 }
 
-lazy_static! {
-    pub(crate) static ref KUBERNETES_DURATION_VALUE: Regex = Regex::new(
-        r"^(([0-9]+\.)?[0-9]+h)?(([0-9]+\.)?[0-9]+m)?(([0-9]+\.)?[0-9]+s)?(([0-9]+\.)?[0-9]+ms)?$"
-    )
-    .unwrap();
+/// The units Kubernetes durations may use, from longest to shortest.
+const KUBERNETES_DURATION_UNITS: &[DurationUnit] = &[HOURS, MINUTES, SECONDS, MILLISECONDS];
+
+impl KubernetesDurationValue {
+    /// Returns the length of the duration.
+    pub fn duration(&self) -> Duration {
+        self.duration
+    }
+}
+
+impl PartialOrd for KubernetesDurationValue {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for KubernetesDurationValue {
+    /// Orders durations by their length; equal lengths written differently, such as `1m` and
+    /// `60s`, are ordered by how they are written.
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.duration
+            .cmp(&other.duration)
+            .then_with(|| self.inner.cmp(&other.inner))
+    }
 }
 
 impl TryFrom<&str> for KubernetesDurationValue {
     type Error = error::Error;
 
     fn try_from(input: &str) -> Result<Self, Self::Error> {
-        ensure!(
-            !input.is_empty(),
-            error::InvalidKubernetesDurationValueSnafu { input }
-        );
-        ensure!(
-            KUBERNETES_DURATION_VALUE.is_match(input),
-            error::InvalidKubernetesDurationValueSnafu { input }
-        );
+        let duration = parse_duration(input, KUBERNETES_DURATION_UNITS)
+            .map_err(|msg| error::InvalidKubernetesDurationValueSnafu { input, msg }.build())?;
         Ok(KubernetesDurationValue {
             inner: input.to_string(),
+            duration,
         })
     }
 }
 
+/// Durations are written with whole numbers of each unit, e.g. `1m30s` for 90 seconds. Durations
+/// with precision finer than a millisecond are rejected.
+impl TryFrom<Duration> for KubernetesDurationValue {
+    type Error = error::Error;
+
+    fn try_from(duration: Duration) -> Result<Self, Self::Error> {
+        let inner = check_duration(duration)
+            .and_then(|()| {
+                format_duration(duration, KUBERNETES_DURATION_UNITS)
+                    .ok_or("must be a whole number of milliseconds")
+            })
+            .map_err(|msg| {
+                let input = format!("{:?}", duration);
+                error::InvalidKubernetesDurationValueSnafu { input, msg }.build()
+            })?;
+        Ok(KubernetesDurationValue { inner, duration })
+    }
+}
+
 string_impls_for!(KubernetesDurationValue, "KubernetesDurationValue");
 
 #[cfg(test)]
 mod test_kubernetes_duration_value {
     use super::KubernetesDurationValue;
     use std::convert::TryFrom;
+    use std::time::Duration;
 
     #[test]
     fn good_tokens() {
@@ -882,11 +1231,52 @@ mod test_kubernetes_duration_value {
             "ten second",
             "1m2h",
             "9ns",
+            "1s1s",
+            "2562048h",
             &"a".repeat(23),
         ] {
             KubernetesDurationValue::try_from(*err).unwrap_err();
         }
     }
+
+    fn duration(input: &str) -> KubernetesDurationValue {
+        KubernetesDurationValue::try_from(input).unwrap()
+    }
+
+    #[test]
+    fn parsed_durations() {
+        assert_eq!(
+            duration("1h2m3s10ms").duration(),
+            Duration::from_millis(3_723_010)
+        );
+        assert_eq!(duration("1.5m").duration(), Duration::from_secs(90));
+
+        let mut values = [
+            duration("2m"),
+            duration("90s"),
+            duration("1.5m"),
+            duration("5s"),
+        ];
+        values.sort();
+        let sorted: Vec<_> = values.iter().map(|value| value.as_ref()).collect();
+        assert_eq!(sorted, ["5s", "1.5m", "90s", "2m"]);
+    }
+
+    #[test]
+    fn from_duration() {
+        for (value, expected) in [
+            (Duration::from_secs(90), "1m30s"),
+            (Duration::from_millis(3_723_010), "1h2m3s10ms"),
+            (Duration::ZERO, "0s"),
+        ] {
+            let converted = KubernetesDurationValue::try_from(value).unwrap();
+            assert_eq!(converted.duration(), value);
+            assert_eq!(converted, expected);
+            assert_eq!(serde_json::to_value(&converted).unwrap(), expected);
+        }
+        KubernetesDurationValue::try_from(Duration::from_micros(1)).unwrap_err();
+        KubernetesDurationValue::try_from(Duration::MAX).unwrap_err();
+    }
 }
 
 // =^..^=   =^..^=   =^..^=   =^..^=   =^..^=   =^..^=   =^..^=   =^..^=   =^..^=
@@ -993,6 +1383,56 @@ mod test_topology_manager_policy {
 
 // =^..^=   =^..^=   =^..^=   =^..^=   =^..^=   =^..^=   =^..^=   =^..^=   =^..^=
 
+/// KubernetesCgroupDriver represents a string that contains a valid cgroup driver for the kubelet.
+/// Default: systemd
+/// https://kubernetes.io/docs/setup/production-environment/container-runtimes/#cgroup-drivers
+
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct KubernetesCgroupDriver {
+    inner: String,
+}
+#[derive(Debug, Clone, Eq, PartialEq, Hash, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum ValidKubernetesCgroupDriver {
+    Systemd,
+    Cgroupfs,
+}
+
+impl TryFrom<&str> for KubernetesCgroupDriver {
+    type Error = error::Error;
+
+    fn try_from(input: &str) -> Result<Self, Self::Error> {
+        serde_plain::from_str::<ValidKubernetesCgroupDriver>(input)
+            .context(error::InvalidCgroupDriverSnafu { input })?;
+        Ok(KubernetesCgroupDriver {
+            inner: input.to_string(),
+        })
+    }
+}
+string_impls_for!(KubernetesCgroupDriver, "KubernetesCgroupDriver");
+
+#[cfg(test)]
+mod test_kubernetes_cgroup_driver {
+    use super::KubernetesCgroupDriver;
+    use std::convert::TryFrom;
+
+    #[test]
+    fn good_kubernetes_cgroup_driver() {
+        for ok in &["systemd", "cgroupfs"] {
+            KubernetesCgroupDriver::try_from(*ok).unwrap();
+        }
+    }
+
+    #[test]
+    fn bad_kubernetes_cgroup_driver() {
+        for err in &["", "Systemd", "cgroup", "none"] {
+            KubernetesCgroupDriver::try_from(*err).unwrap_err();
+        }
+    }
+}
+
+// =^..^=   =^..^=   =^..^=   =^..^=   =^..^=   =^..^=   =^..^=   =^..^=   =^..^=
+
 /// This enum is used by `IntegerPercent` to "remember" how the number was deserialized.
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd)]
 enum IntegerPercentMode {
@@ -1035,6 +1475,11 @@ impl IntegerPercent {
         );
         Ok(Self { value, mode })
     }
+
+    /// Returns the percentage as a number.
+    pub fn value(&self) -> i32 {
+        self.value
+    }
 }
 
 impl Display for IntegerPercent {
@@ -1184,8 +1629,8 @@ mod test_integer_percent {
 ///
 /// This model allows the value to be either a list of IPs, or a single IP string
 /// for backwards compatibility.
+#[model(untagged = true)]
 #[derive(Debug, Clone, Eq, PartialEq, Hash, Serialize, Deserialize)]
-#[serde(untagged)]
 pub enum KubernetesClusterDnsIp {
     Scalar(IpAddr),
     Vector(Vec<IpAddr>),
@@ -1314,6 +1759,28 @@ pub struct CredentialProvider {
     environment: Option<EnvVarMap>,
 }
 
+impl CredentialProvider {
+    /// Returns whether the kubelet should use this credential provider.
+    pub fn enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Returns the patterns of the images this credential provider supplies credentials for.
+    pub fn image_patterns(&self) -> &[SingleLineString] {
+        &self.image_patterns
+    }
+
+    /// Returns how long the kubelet should cache credentials from this provider, if set.
+    pub fn cache_duration(&self) -> Option<&KubernetesDurationValue> {
+        self.cache_duration.as_ref()
+    }
+
+    /// Returns the environment variables to run the credential provider with, if set.
+    pub fn environment(&self) -> Option<&HashMap<SingleLineString, SingleLineString>> {
+        self.environment.as_ref()
+    }
+}
+
 // =^..^=   =^..^=   =^..^=   =^..^=   =^..^=   =^..^=   =^..^=   =^..^=   =^..^=
 
 /// KubernetesCPUManagerPolicyOption values are the possible option names for the cpuManagerPolicyOptions.
@@ -1330,9 +1797,7 @@ mod test_kubernetes_cpu_manager_policy_option {
 
     #[test]
     fn good_cpu_manager_policy_option() {
-        for ok in &["full-pcpus-only"] {
-            KubernetesCPUManagerPolicyOption::try_from(*ok).unwrap();
-        }
+        KubernetesCPUManagerPolicyOption::try_from("full-pcpus-only").unwrap();
     }
 
     #[test]
@@ -1386,6 +1851,18 @@ pub struct KubernetesMemoryReservation {
     limits: HashMap<KubernetesMemoryReservationKey, KubernetesQuantityValue>,
 }
 
+impl KubernetesMemoryReservation {
+    /// Returns whether this memory reservation should be applied.
+    pub fn enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Returns the amount of each kind of memory to reserve.
+    pub fn limits(&self) -> &HashMap<KubernetesMemoryReservationKey, KubernetesQuantityValue> {
+        &self.limits
+    }
+}
+
 /// KubernetesMemoryManagerPolicy represents the valid options for the memory manager policy.
 #[derive(Debug, Clone, Eq, PartialEq, Hash, Serialize, Deserialize, Scalar)]
 pub enum KubernetesMemoryManagerPolicy {
@@ -1454,15 +1931,15 @@ pub struct NvidiaDevicePluginSettings {
     device_list_strategy: NvidiaDeviceListStrategy,
 }
 
+#[model]
 #[derive(Debug, Clone, Eq, PartialEq, Hash, Serialize, Deserialize)]
-#[serde(rename_all = "kebab-case")]
 pub enum NvidiaDeviceIdStrategy {
     Uuid,
     Index,
 }
 
+#[model]
 #[derive(Debug, Clone, Eq, PartialEq, Hash, Serialize, Deserialize)]
-#[serde(rename_all = "kebab-case")]
 pub enum NvidiaDeviceListStrategy {
     Envvar,
     VolumeMounts,
@@ -51,6 +51,15 @@ pub mod error {
         #[snafu(display("Given invalid URL '{}'", input))]
         InvalidUrl { input: String },
 
+        #[snafu(display("Invalid image reference '{}': {}", input, msg))]
+        InvalidImageReference { input: String, msg: &'static str },
+
+        #[snafu(display("Invalid CIDR '{}': {}", input, msg))]
+        InvalidCidr { input: String, msg: &'static str },
+
+        #[snafu(display("Invalid no-proxy entry '{}': {}", input, msg))]
+        InvalidNoProxyEntry { input: String, msg: &'static str },
+
         #[snafu(display("Invalid version string '{}'", input))]
         InvalidVersion { input: String },
 
@@ -80,6 +89,9 @@ pub mod error {
         #[snafu(display("Invalid domain name '{}': {}", input, msg))]
         InvalidDomainName { input: String, msg: String },
 
+        #[snafu(display("Invalid host '{}': {}", input, msg))]
+        InvalidNetworkHost { input: String, msg: String },
+
         #[snafu(display("Invalid hostname '{}': {}", input, msg))]
         InvalidLinuxHostname { input: String, msg: String },
 
@@ -113,8 +125,11 @@ pub mod error {
             source: serde_plain::Error,
         },
 
-        #[snafu(display("Invalid Kubernetes duration value '{}'", input))]
-        InvalidKubernetesDurationValue { input: String },
+        #[snafu(display("Invalid Kubernetes quantity '{}': {}", input, msg))]
+        InvalidKubernetesQuantity { input: String, msg: &'static str },
+
+        #[snafu(display("Invalid Kubernetes duration value '{}': {}", input, msg))]
+        InvalidKubernetesDurationValue { input: String, msg: &'static str },
 
         #[snafu(display("Invalid x509 certificate: {}", source))]
         InvalidX509Certificate { source: PEMToX509ParseError },
@@ -133,6 +148,12 @@ pub mod error {
             source: serde_plain::Error,
         },
 
+        #[snafu(display("Invalid cgroup driver '{}'", input))]
+        InvalidCgroupDriver {
+            input: String,
+            source: serde_plain::Error,
+        },
+
         #[snafu(display("Invalid topology manager policy '{}'", input))]
         InvalidTopologyManagerPolicy {
             input: String,
@@ -145,8 +166,8 @@ pub mod error {
         #[snafu(display("Invalid imageGCLowThresholdPercent '{}': {}", input, msg))]
         InvalidImageGCLowThresholdPercent { input: String, msg: String },
 
-        #[snafu(display("Invalid ECS duration value '{}'", input))]
-        InvalidECSDurationValue { input: String },
+        #[snafu(display("Invalid ECS duration value '{}': {}", input, msg))]
+        InvalidECSDurationValue { input: String, msg: &'static str },
 
         #[snafu(display("Could not parse '{}' as an integer", input))]
         ParseInt {
@@ -184,6 +205,7 @@ macro_rules! require {
 }
 
 // Must be after macro definition
+mod duration;
 mod ecs;
 mod kubernetes;
 mod oci_defaults;
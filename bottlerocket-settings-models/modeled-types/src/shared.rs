@@ -8,10 +8,13 @@ use lazy_static::lazy_static;
 use regex::Regex;
 use semver::Version;
 use serde::{Deserialize, Serialize};
-use snafu::{ensure, ResultExt};
+use sha2::{Digest, Sha256};
+use snafu::{ensure, OptionExt, ResultExt};
 use std::convert::TryFrom;
-use std::net::IpAddr;
+use std::fmt::{self, Display, Formatter};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
 use std::str::FromStr;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use url::Host;
 
 /// ValidBase64 can only be created by deserializing from valid base64 text.  It stores the
@@ -511,6 +514,746 @@ mod test_url {
 
 // =^..^=   =^..^=   =^..^=   =^..^=   =^..^=   =^..^=   =^..^=   =^..^=   =^..^=
 
+/// ImageReference represents a string that refers to a container image, following the grammar
+/// of the OCI distribution reference format, e.g. `public.ecr.aws/bottlerocket/admin:v1.0` or
+/// `ubuntu@sha256:<digest>`. It stores the original string and makes it accessible through
+/// standard traits, along with the parts of the reference.
+///
+/// As in Docker, the first part of the name is treated as the registry if it contains a `.` or
+/// `:`, is `localhost`, or contains an uppercase letter, which repository paths can't; otherwise
+/// the whole name is the repository.  So `Registry/app` names the registry `Registry`, while
+/// `registry/app` is a repository in the default registry.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct ImageReference {
+    inner: String,
+    registry: Option<String>,
+    repository: String,
+    tag: Option<String>,
+    digest: Option<String>,
+}
+
+/// The longest a registry and repository name may be together.
+const IMAGE_NAME_MAX_LEN: usize = 255;
+
+lazy_static! {
+    /// A registry host, which may be a domain name or a bracketed IPv6 address, with an optional
+    /// port.
+    pub(crate) static ref IMAGE_REGISTRY: Regex = Regex::new(
+        r"(?x)^
+          (?:
+            [a-zA-Z0-9](?:[a-zA-Z0-9-]*[a-zA-Z0-9])?
+            (?:\.[a-zA-Z0-9](?:[a-zA-Z0-9-]*[a-zA-Z0-9])?)*
+          |
+            \[[a-fA-F0-9:]+\]
+          )
+          (?::[0-9]+)?
+          $"
+    )
+    .unwrap();
+
+    /// One `/`-separated component of a repository path.
+    pub(crate) static ref IMAGE_PATH_COMPONENT: Regex =
+        Regex::new(r"^[a-z0-9]+(?:(?:[._]|__|-+)[a-z0-9]+)*$").unwrap();
+
+    pub(crate) static ref IMAGE_TAG: Regex = Regex::new(r"^[a-zA-Z0-9_][a-zA-Z0-9_.-]{0,127}$").unwrap();
+
+    pub(crate) static ref IMAGE_DIGEST: Regex =
+        Regex::new(r"^[a-z0-9]+(?:[.+_-][a-z0-9]+)*:[a-zA-Z0-9=_-]{32,}$").unwrap();
+}
+
+impl ImageReference {
+    /// Returns the registry host and port, if the reference names one.
+    pub fn registry(&self) -> Option<&str> {
+        self.registry.as_deref()
+    }
+
+    /// Returns the repository path within the registry, e.g. `bottlerocket/admin`.
+    pub fn repository(&self) -> &str {
+        &self.repository
+    }
+
+    /// Returns the tag, if the reference has one.
+    pub fn tag(&self) -> Option<&str> {
+        self.tag.as_deref()
+    }
+
+    /// Returns the digest, including its algorithm, if the reference has one.
+    pub fn digest(&self) -> Option<&str> {
+        self.digest.as_deref()
+    }
+}
+
+impl TryFrom<&str> for ImageReference {
+    type Error = error::Error;
+
+    fn try_from(input: &str) -> Result<Self, Self::Error> {
+        let (name_and_tag, digest) = match input.split_once('@') {
+            Some((name_and_tag, digest)) => (name_and_tag, Some(digest)),
+            None => (input, None),
+        };
+        // A tag follows the last `:` after the last `/`; earlier colons separate a registry port.
+        let tag_start = name_and_tag.rfind('/').map_or(0, |slash| slash + 1);
+        let (name, tag) = match name_and_tag
+            .get(tag_start..)
+            .and_then(|last| last.rfind(':'))
+        {
+            Some(colon) => (
+                name_and_tag.get(..tag_start + colon).unwrap_or_default(),
+                name_and_tag.get(tag_start + colon + 1..),
+            ),
+            None => (name_and_tag, None),
+        };
+        let (registry, repository) = match name.split_once('/') {
+            Some((first, rest))
+                if first.contains(['.', ':'])
+                    || first == "localhost"
+                    || first != first.to_lowercase() =>
+            {
+                (Some(first), rest)
+            }
+            _ => (None, name),
+        };
+
+        ensure!(
+            !repository.is_empty(),
+            error::InvalidImageReferenceSnafu {
+                input,
+                msg: "missing repository"
+            }
+        );
+        ensure!(
+            name.len() <= IMAGE_NAME_MAX_LEN,
+            error::InvalidImageReferenceSnafu {
+                input,
+                msg: "name is too long"
+            }
+        );
+        if let Some(registry) = registry {
+            ensure!(
+                IMAGE_REGISTRY.is_match(registry),
+                error::InvalidImageReferenceSnafu {
+                    input,
+                    msg: "invalid registry"
+                }
+            );
+        }
+        ensure!(
+            repository
+                .split('/')
+                .all(|component| IMAGE_PATH_COMPONENT.is_match(component)),
+            error::InvalidImageReferenceSnafu {
+                input,
+                msg: "invalid repository"
+            }
+        );
+        if let Some(tag) = tag {
+            ensure!(
+                IMAGE_TAG.is_match(tag),
+                error::InvalidImageReferenceSnafu {
+                    input,
+                    msg: "invalid tag"
+                }
+            );
+        }
+        if let Some(digest) = digest {
+            check_digest(digest)
+                .map_err(|msg| error::InvalidImageReferenceSnafu { input, msg }.build())?;
+        }
+
+        Ok(ImageReference {
+            inner: input.to_string(),
+            registry: registry.map(str::to_string),
+            repository: repository.to_string(),
+            tag: tag.map(str::to_string),
+            digest: digest.map(str::to_string),
+        })
+    }
+}
+
+string_impls_for!(ImageReference, "ImageReference");
+
+/// Checks a digest's format, and the length of the digests for algorithms the OCI image spec
+/// registers.
+fn check_digest(digest: &str) -> Result<(), &'static str> {
+    if !IMAGE_DIGEST.is_match(digest) {
+        return Err("invalid digest");
+    }
+    let (algorithm, encoded) = digest.split_once(':').ok_or("invalid digest")?;
+    let expected_len = match algorithm {
+        "sha256" => 64,
+        "sha512" => 128,
+        _ => return Ok(()),
+    };
+    if encoded.len() != expected_len
+        || !encoded
+            .chars()
+            .all(|c| c.is_ascii_digit() || ('a'..='f').contains(&c))
+    {
+        return Err("invalid digest");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test_image_reference {
+    use super::ImageReference;
+    use std::convert::TryFrom;
+
+    const DIGEST: &str = "sha256:0123456789abcdef0123456789abcdef0123456789abcdef0123456789abcdef";
+
+    #[test]
+    fn good_image_references() {
+        for (input, registry, repository, tag, digest) in [
+            ("ubuntu", None, "ubuntu", None, None),
+            (
+                "library/ubuntu:22.04",
+                None,
+                "library/ubuntu",
+                Some("22.04"),
+                None,
+            ),
+            (
+                "public.ecr.aws/bottlerocket/bottlerocket-admin:v0.11.4",
+                Some("public.ecr.aws"),
+                "bottlerocket/bottlerocket-admin",
+                Some("v0.11.4"),
+                None,
+            ),
+            (
+                "localhost:5000/my_app/web-server__v2",
+                Some("localhost:5000"),
+                "my_app/web-server__v2",
+                None,
+                None,
+            ),
+            ("localhost/app", Some("localhost"), "app", None, None),
+            ("Registry/app", Some("Registry"), "app", None, None),
+            ("registry/app", None, "registry/app", None, None),
+            (
+                "[fd00::1]:5000/app:latest",
+                Some("[fd00::1]:5000"),
+                "app",
+                Some("latest"),
+                None,
+            ),
+            (
+                &format!("ubuntu@{}", DIGEST),
+                None,
+                "ubuntu",
+                None,
+                Some(DIGEST),
+            ),
+            (
+                &format!("registry.example.com/app:1.0@{}", DIGEST),
+                Some("registry.example.com"),
+                "app",
+                Some("1.0"),
+                Some(DIGEST),
+            ),
+        ] {
+            let reference = ImageReference::try_from(input).unwrap();
+            assert_eq!(reference.registry(), registry, "{}", input);
+            assert_eq!(reference.repository(), repository, "{}", input);
+            assert_eq!(reference.tag(), tag, "{}", input);
+            assert_eq!(reference.digest(), digest, "{}", input);
+            assert_eq!(reference.to_string(), input);
+        }
+    }
+
+    #[test]
+    fn bad_image_references() {
+        for err in &[
+            "",
+            "https://example.com/app",
+            "Ubuntu",
+            "example.com/",
+            "example.com/App",
+            "example..com/app",
+            "-example.com/app",
+            "app:",
+            "app:-tag",
+            &format!("app:{}", "a".repeat(129)),
+            "app@sha256:abc",
+            "app@sha256:0123456789ABCDEF0123456789ABCDEF0123456789ABCDEF0123456789ABCDEF",
+            "app@@sha256:0123456789abcdef0123456789abcdef0123456789abcdef0123456789abcdef",
+            "app//web",
+            "app/web-",
+            &format!("example.com/{}", "a".repeat(250)),
+            "with space",
+        ] {
+            ImageReference::try_from(*err).unwrap_err();
+        }
+    }
+}
+
+// =^..^=   =^..^=   =^..^=   =^..^=   =^..^=   =^..^=   =^..^=   =^..^=   =^..^=
+
+/// IpNetwork is a parsed range of IPv4 or IPv6 addresses, written in CIDR notation as an address
+/// and a prefix length, e.g. `10.0.0.0/8` or `fd00::/64`.
+///
+/// Address bits beyond the prefix are ignored, so `10.1.2.3/8` is the same network as
+/// `10.0.0.0/8`.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub struct IpNetwork {
+    address: IpAddr,
+    prefix_len: u8,
+}
+
+impl IpNetwork {
+    /// Creates a network from an address and prefix length, returning `None` if the prefix is
+    /// longer than the address.
+    pub fn new(address: IpAddr, prefix_len: u8) -> Option<Self> {
+        let max_len = match address {
+            IpAddr::V4(_) => 32,
+            IpAddr::V6(_) => 128,
+        };
+        if prefix_len > max_len {
+            return None;
+        }
+        let address = match address {
+            IpAddr::V4(address) => {
+                let mask = u32::MAX
+                    .checked_shl(32 - u32::from(prefix_len))
+                    .unwrap_or(0);
+                IpAddr::V4(Ipv4Addr::from(u32::from(address) & mask))
+            }
+            IpAddr::V6(address) => {
+                let mask = u128::MAX
+                    .checked_shl(128 - u32::from(prefix_len))
+                    .unwrap_or(0);
+                IpAddr::V6(Ipv6Addr::from(u128::from(address) & mask))
+            }
+        };
+        Some(Self {
+            address,
+            prefix_len,
+        })
+    }
+
+    /// Returns the first address of the network.
+    pub fn network(&self) -> IpAddr {
+        self.address
+    }
+
+    /// Returns the number of leading bits shared by every address in the network.
+    pub fn prefix_len(&self) -> u8 {
+        self.prefix_len
+    }
+
+    pub fn is_ipv4(&self) -> bool {
+        self.address.is_ipv4()
+    }
+
+    pub fn is_ipv6(&self) -> bool {
+        self.address.is_ipv6()
+    }
+
+    /// Returns whether `address` is in the network. Addresses of the other IP version are never
+    /// contained.
+    pub fn contains(&self, address: &IpAddr) -> bool {
+        Self::new(*address, self.prefix_len).is_some_and(|network| network.address == self.address)
+    }
+
+    /// Returns whether every address in `other` is also in this network.
+    pub fn contains_network(&self, other: &IpNetwork) -> bool {
+        other.prefix_len >= self.prefix_len && self.contains(&other.address)
+    }
+
+    /// Returns whether any address is in both networks.
+    pub fn overlaps(&self, other: &IpNetwork) -> bool {
+        self.contains_network(other) || other.contains_network(self)
+    }
+}
+
+/// A single address is a network with the longest prefix.
+impl From<IpAddr> for IpNetwork {
+    fn from(address: IpAddr) -> Self {
+        let prefix_len = if address.is_ipv4() { 32 } else { 128 };
+        Self {
+            address,
+            prefix_len,
+        }
+    }
+}
+
+impl FromStr for IpNetwork {
+    type Err = error::Error;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let (address, prefix_len) = input.split_once('/').context(error::InvalidCidrSnafu {
+            input,
+            msg: "missing prefix length",
+        })?;
+        let address: IpAddr = address.parse().ok().context(error::InvalidCidrSnafu {
+            input,
+            msg: "invalid IP address",
+        })?;
+        // Leading signs and zeros are accepted by `u8::from_str`, but not in CIDR notation.
+        ensure!(
+            !prefix_len.is_empty()
+                && prefix_len.chars().all(|c| c.is_ascii_digit())
+                && (prefix_len == "0" || !prefix_len.starts_with('0')),
+            error::InvalidCidrSnafu {
+                input,
+                msg: "invalid prefix length"
+            }
+        );
+        prefix_len
+            .parse()
+            .ok()
+            .and_then(|prefix_len| Self::new(address, prefix_len))
+            .context(error::InvalidCidrSnafu {
+                input,
+                msg: "prefix length is too long for the address",
+            })
+    }
+}
+
+impl Display for IpNetwork {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.address, self.prefix_len)
+    }
+}
+
+/// Cidr represents a string that contains an IPv4 or IPv6 network in CIDR notation, e.g.
+/// `10.100.0.0/16`. It stores the original string and makes it accessible through standard
+/// traits, along with the parsed network.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct Cidr {
+    inner: String,
+    network: IpNetwork,
+}
+
+impl Cidr {
+    pub fn network(&self) -> IpNetwork {
+        self.network
+    }
+
+    /// Returns whether `address` is in the network.
+    pub fn contains(&self, address: &IpAddr) -> bool {
+        self.network.contains(address)
+    }
+}
+
+impl TryFrom<&str> for Cidr {
+    type Error = error::Error;
+
+    fn try_from(input: &str) -> Result<Self, Self::Error> {
+        Ok(Cidr {
+            inner: input.to_string(),
+            network: input.parse()?,
+        })
+    }
+}
+
+string_impls_for!(Cidr, "Cidr");
+
+#[cfg(test)]
+mod test_cidr {
+    use super::{Cidr, IpNetwork};
+    use std::convert::TryFrom;
+    use std::net::IpAddr;
+
+    fn ip(input: &str) -> IpAddr {
+        input.parse().unwrap()
+    }
+
+    fn network(input: &str) -> IpNetwork {
+        input.parse().unwrap()
+    }
+
+    #[test]
+    fn good_cidrs() {
+        for (input, address, prefix_len) in [
+            ("10.0.0.0/8", "10.0.0.0", 8),
+            ("10.1.2.3/8", "10.0.0.0", 8),
+            ("192.168.1.5/32", "192.168.1.5", 32),
+            ("0.0.0.0/0", "0.0.0.0", 0),
+            ("fd00:1234::/64", "fd00:1234::", 64),
+            ("2600::1/128", "2600::1", 128),
+            ("::/0", "::", 0),
+        ] {
+            let cidr = Cidr::try_from(input).unwrap();
+            assert_eq!(cidr.network().network(), ip(address), "{}", input);
+            assert_eq!(cidr.network().prefix_len(), prefix_len, "{}", input);
+            assert_eq!(cidr.to_string(), input);
+        }
+    }
+
+    #[test]
+    fn bad_cidrs() {
+        for err in &[
+            "",
+            "10.0.0.0",
+            "10.0.0.0/",
+            "10.0.0.0/33",
+            "10.0.0.0/+8",
+            "10.0.0.0/08",
+            "10.0.0/8",
+            "fd00::/129",
+            "example.com/8",
+            "10.0.0.0/8/8",
+        ] {
+            Cidr::try_from(*err).unwrap_err();
+        }
+    }
+
+    #[test]
+    fn containment() {
+        let cidr = Cidr::try_from("10.100.0.0/16").unwrap();
+        assert!(cidr.contains(&ip("10.100.0.10")));
+        assert!(cidr.contains(&ip("10.100.255.255")));
+        assert!(!cidr.contains(&ip("10.101.0.0")));
+        assert!(!cidr.contains(&ip("::ffff:10.100.0.10")));
+
+        let v6 = network("fd00::/8");
+        assert!(v6.contains(&ip("fdff::1")));
+        assert!(!v6.contains(&ip("fe00::1")));
+        assert!(!v6.contains(&ip("10.0.0.1")));
+
+        assert!(network("0.0.0.0/0").contains(&ip("255.255.255.255")));
+        assert!(IpNetwork::from(ip("10.0.0.1")).contains(&ip("10.0.0.1")));
+        assert!(!IpNetwork::from(ip("10.0.0.1")).contains(&ip("10.0.0.2")));
+    }
+
+    #[test]
+    fn network_relationships() {
+        let large = network("10.0.0.0/8");
+        let small = network("10.100.0.0/16");
+        let other = network("172.20.0.0/16");
+        assert!(large.contains_network(&small));
+        assert!(!small.contains_network(&large));
+        assert!(large.overlaps(&small) && small.overlaps(&large));
+        assert!(!large.overlaps(&other));
+        assert!(!large.overlaps(&network("::/0")));
+        assert_eq!(network("10.1.2.3/8").to_string(), "10.0.0.0/8");
+        assert_eq!(IpNetwork::new(ip("10.0.0.0"), 33), None);
+    }
+}
+
+// =^..^=   =^..^=   =^..^=   =^..^=   =^..^=   =^..^=   =^..^=   =^..^=   =^..^=
+
+/// NoProxyEntry represents a string that contains an entry for a proxy exclusion list such as
+/// `NO_PROXY`. Entries may be `*` to match every host, an IP address, a network in CIDR notation,
+/// or a hostname with an optional port. IPv6 addresses are written without brackets and can't be
+/// given a port, so entries like `[::1]:8080` are rejected. Hostnames starting with `.` or `*.`
+/// match only subdomains; others match the domain and its subdomains.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct NoProxyEntry {
+    inner: String,
+    target: NoProxyTarget,
+}
+
+/// The hosts that a `NoProxyEntry` matches.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub enum NoProxyTarget {
+    /// Every host, written `*`.
+    Any,
+    /// A single IP address.
+    Address(IpAddr),
+    /// Every address in a network.
+    Network(IpNetwork),
+    /// A domain, and possibly its subdomains, on any port unless one is given.
+    Domain {
+        name: String,
+        subdomains_only: bool,
+        port: Option<u16>,
+    },
+}
+
+lazy_static! {
+    /// Dot-separated labels of letters, digits, `-` and `_`, which may not start or end with `-`.
+    pub(crate) static ref NO_PROXY_DOMAIN: Regex = Regex::new(
+        r"^[a-zA-Z0-9_](?:[a-zA-Z0-9_-]{0,61}[a-zA-Z0-9_])?(?:\.[a-zA-Z0-9_](?:[a-zA-Z0-9_-]{0,61}[a-zA-Z0-9_])?)*$"
+    )
+    .unwrap();
+}
+
+impl NoProxyEntry {
+    pub fn target(&self) -> &NoProxyTarget {
+        &self.target
+    }
+
+    /// Returns whether a request to `host` would skip the proxy because of this entry, where
+    /// `host` is a hostname or IP address without a port.
+    pub fn matches(&self, host: &str) -> bool {
+        match &self.target {
+            NoProxyTarget::Any => true,
+            NoProxyTarget::Address(address) => host.parse::<IpAddr>().ok() == Some(*address),
+            NoProxyTarget::Network(network) => host
+                .parse::<IpAddr>()
+                .is_ok_and(|address| network.contains(&address)),
+            NoProxyTarget::Domain {
+                name,
+                subdomains_only,
+                ..
+            } => {
+                let host = host.to_ascii_lowercase();
+                let name = name.to_ascii_lowercase();
+                let is_subdomain = host
+                    .strip_suffix(name.as_str())
+                    .is_some_and(|prefix| prefix.ends_with('.'));
+                is_subdomain || (!subdomains_only && host == name)
+            }
+        }
+    }
+}
+
+impl TryFrom<&str> for NoProxyEntry {
+    type Error = error::Error;
+
+    fn try_from(input: &str) -> Result<Self, Self::Error> {
+        let target = if input == "*" {
+            NoProxyTarget::Any
+        } else if let Ok(address) = input.parse() {
+            NoProxyTarget::Address(address)
+        } else if input.contains('/') {
+            NoProxyTarget::Network(input.parse().map_err(|_| {
+                error::InvalidNoProxyEntrySnafu {
+                    input,
+                    msg: "invalid CIDR",
+                }
+                .build()
+            })?)
+        } else {
+            let (name, subdomains_only) =
+                match input.strip_prefix("*.").or_else(|| input.strip_prefix('.')) {
+                    Some(name) => (name, true),
+                    None => (input, false),
+                };
+            let (name, port) = match name.rsplit_once(':') {
+                Some((name, port)) => (
+                    name,
+                    Some(port.parse().ok().filter(|port| *port != 0).context(
+                        error::InvalidNoProxyEntrySnafu {
+                            input,
+                            msg: "invalid port",
+                        },
+                    )?),
+                ),
+                None => (name, None),
+            };
+            ensure!(
+                name.len() <= 253 && NO_PROXY_DOMAIN.is_match(name),
+                error::InvalidNoProxyEntrySnafu {
+                    input,
+                    msg: "must be '*', an IP address, a CIDR, or a hostname"
+                }
+            );
+            NoProxyTarget::Domain {
+                name: name.to_string(),
+                subdomains_only,
+                port,
+            }
+        };
+
+        Ok(NoProxyEntry {
+            inner: input.to_string(),
+            target,
+        })
+    }
+}
+
+string_impls_for!(NoProxyEntry, "NoProxyEntry");
+
+#[cfg(test)]
+mod test_no_proxy_entry {
+    use super::{NoProxyEntry, NoProxyTarget};
+    use std::convert::TryFrom;
+
+    fn entry(input: &str) -> NoProxyEntry {
+        NoProxyEntry::try_from(input).unwrap()
+    }
+
+    #[test]
+    fn good_no_proxy_entries() {
+        for ok in &[
+            "*",
+            "localhost",
+            "example.com",
+            ".example.com",
+            "*.example.com",
+            "example.com:8080",
+            "my_host.internal",
+            "169.254.169.254",
+            "fd00::1",
+            "10.0.0.0/8",
+            "fd00::/8",
+        ] {
+            assert_eq!(entry(ok).to_string(), *ok);
+        }
+    }
+
+    #[test]
+    fn bad_no_proxy_entries() {
+        for err in &[
+            "",
+            ".",
+            "*.",
+            "**",
+            "example..com",
+            "-example.com",
+            "example.com:",
+            "example.com:0",
+            "example.com:99999",
+            "10.0.0.0/33",
+            "example.com/path",
+            "https://example.com",
+            "[::1]",
+            "[::1]:8080",
+            "with space",
+            &"a".repeat(64),
+        ] {
+            NoProxyEntry::try_from(*err).unwrap_err();
+        }
+    }
+
+    #[test]
+    fn no_proxy_targets() {
+        assert_eq!(entry("*").target(), &NoProxyTarget::Any);
+        assert_eq!(
+            entry("10.0.0.0/8").target(),
+            &NoProxyTarget::Network("10.0.0.0/8".parse().unwrap())
+        );
+        assert_eq!(
+            entry(".example.com:443").target(),
+            &NoProxyTarget::Domain {
+                name: "example.com".to_string(),
+                subdomains_only: true,
+                port: Some(443),
+            }
+        );
+    }
+
+    #[test]
+    fn no_proxy_matches() {
+        for (entry_input, host, matches) in [
+            ("*", "anything.example.com", true),
+            ("example.com", "example.com", true),
+            ("example.com", "api.Example.com", true),
+            ("example.com", "badexample.com", false),
+            (".example.com", "example.com", false),
+            (".example.com", "api.example.com", true),
+            ("*.example.com", "api.example.com", true),
+            ("10.0.0.0/8", "10.1.2.3", true),
+            ("10.0.0.0/8", "11.1.2.3", false),
+            ("10.0.0.0/8", "ten.example.com", false),
+            ("169.254.169.254", "169.254.169.254", true),
+            ("fd00::1", "fd00:0::1", true),
+        ] {
+            assert_eq!(
+                entry(entry_input).matches(host),
+                matches,
+                "{} {}",
+                entry_input,
+                host
+            );
+        }
+    }
+}
+
+// =^..^=   =^..^=   =^..^=   =^..^=   =^..^=   =^..^=   =^..^=   =^..^=   =^..^=
+
 /// FriendlyVersion represents a version string that can optionally be prefixed with 'v'.
 /// It can also be set to 'latest' to represent the latest version. It stores the original string
 /// and makes it accessible through standard traits.
@@ -686,6 +1429,103 @@ mod test_dns_domain {
 
 // =^..^=   =^..^=   =^..^=   =^..^=   =^..^=   =^..^=   =^..^=   =^..^=   =^..^=
 
+/// NetworkHost represents a string that names a host on the network, as either a DNS name or an
+/// IP address, without a scheme, port, or path. IPv6 addresses are written without brackets. It
+/// stores the original string and makes it accessible through standard traits.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct NetworkHost {
+    inner: String,
+    ip: Option<IpAddr>,
+}
+
+impl NetworkHost {
+    /// Returns the host's IP address, if it was given as one rather than a DNS name.
+    pub fn ip(&self) -> Option<IpAddr> {
+        self.ip
+    }
+}
+
+impl TryFrom<&str> for NetworkHost {
+    type Error = error::Error;
+
+    fn try_from(input: &str) -> Result<Self, error::Error> {
+        if let Ok(ip) = input.parse::<IpAddr>() {
+            return Ok(Self {
+                inner: input.to_string(),
+                ip: Some(ip),
+            });
+        }
+
+        ensure!(
+            !input.starts_with('.'),
+            error::InvalidNetworkHostSnafu {
+                input,
+                msg: "must not start with '.'",
+            }
+        );
+        match Host::parse(input) {
+            Ok(Host::Domain(_)) => Ok(Self {
+                inner: input.to_string(),
+                ip: None,
+            }),
+            // Bracketed IPv6 addresses are the only other kind of host that parses.
+            Ok(Host::Ipv4(_) | Host::Ipv6(_)) => error::InvalidNetworkHostSnafu {
+                input,
+                msg: "IPv6 addresses must not be enclosed in brackets",
+            }
+            .fail(),
+            Err(e) => error::InvalidNetworkHostSnafu {
+                input,
+                msg: e.to_string(),
+            }
+            .fail(),
+        }
+    }
+}
+
+string_impls_for!(NetworkHost, "NetworkHost");
+
+#[cfg(test)]
+mod test_network_host {
+    use super::NetworkHost;
+    use std::convert::TryFrom;
+    use std::net::{IpAddr, Ipv4Addr};
+
+    #[test]
+    fn valid_network_host() {
+        for ok in &[
+            "time.aws.com",
+            "localhost",
+            "169.254.169.123",
+            "fd00:ec2::123",
+        ] {
+            assert!(NetworkHost::try_from(*ok).is_ok(), "{}", ok);
+        }
+        assert_eq!(
+            NetworkHost::try_from("169.254.169.123").unwrap().ip(),
+            Some(IpAddr::V4(Ipv4Addr::new(169, 254, 169, 123)))
+        );
+        assert_eq!(NetworkHost::try_from("time.aws.com").unwrap().ip(), None);
+    }
+
+    #[test]
+    fn invalid_network_host() {
+        for err in &[
+            "",
+            ".time.aws.com",
+            "ntp://time.aws.com",
+            "time.aws.com:123",
+            "time.aws.com/path",
+            "[fd00:ec2::123]",
+            "time aws com",
+        ] {
+            assert!(NetworkHost::try_from(*err).is_err(), "{}", err);
+        }
+    }
+}
+
+// =^..^=   =^..^=   =^..^=   =^..^=   =^..^=   =^..^=   =^..^=   =^..^=   =^..^=
+
 /// SysctlKey represents a string that is a valid Linux sysctl key; keys must be representable as
 /// filesystem paths, and are generally kept to lowercase_underscored_names separated with '.' or
 /// '/'.  SysctlKey stores the original string and makes it accessible through standard traits.
@@ -1068,9 +1908,26 @@ mod test_valid_container_mode {
 }
 
 // =^..^=   =^..^=   =^..^=   =^..^=   =^..^=   =^..^=   =^..^=   =^..^=   =^..^=
+
+/// PemCertificateString represents a base64-encoded bundle of PEM certificates. It stores the
+/// original string and makes it accessible through standard traits, along with metadata about
+/// each certificate in the bundle. An empty string is accepted so that bundles can be deleted.
 #[derive(Debug, Clone, Eq, PartialEq, Hash)]
 pub struct PemCertificateString {
     inner: String,
+    certificates: Vec<CertificateMetadata>,
+}
+
+impl PemCertificateString {
+    /// Returns the metadata of each certificate in the bundle, in the order they appear.
+    pub fn certificates(&self) -> &[CertificateMetadata] {
+        &self.certificates
+    }
+
+    /// Returns the number of certificates in the bundle.
+    pub fn certificate_count(&self) -> usize {
+        self.certificates.len()
+    }
 }
 
 impl TryFrom<&str> for PemCertificateString {
@@ -1081,29 +1938,31 @@ impl TryFrom<&str> for PemCertificateString {
         if input.trim().is_empty() {
             return Ok(PemCertificateString {
                 inner: input.to_string(),
+                certificates: Vec::new(),
             });
         }
         let decoded_bytes = base64::engine::general_purpose::STANDARD
             .decode(input)
             .context(error::InvalidBase64Snafu)?;
-        // Flag to check if the bundle doesn't contain any valid certificate
-        let mut certs_found = false;
         // Validate each certificate in the bundle
+        let mut certificates = Vec::new();
         for pem in x509_parser::pem::Pem::iter_from_buffer(&decoded_bytes) {
             // Parse buffer into a PEM object, then to a x509 certificate
             let pem = pem.context(error::InvalidPEMSnafu)?;
-            pem.parse_x509()
+            let certificate = pem
+                .parse_x509()
                 .context(error::InvalidX509CertificateSnafu)?;
-            certs_found = true;
+            certificates.push(CertificateMetadata::new(&pem.contents, &certificate));
         }
 
         // No valid certificate found
-        if !certs_found {
+        if certificates.is_empty() {
             return error::NoCertificatesFoundSnafu {}.fail();
         }
 
         Ok(PemCertificateString {
             inner: input.to_string(),
+            certificates,
         })
     }
 }
@@ -1112,16 +1971,128 @@ impl Default for PemCertificateString {
     fn default() -> Self {
         PemCertificateString {
             inner: "".to_string(),
+            certificates: Vec::new(),
         }
     }
 }
 
+/// Metadata about a certificate in a `PemCertificateString`, along with the certificate itself.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct CertificateMetadata {
+    der: Vec<u8>,
+    subject: String,
+    issuer: String,
+    // Seconds since the Unix epoch, which may be negative.
+    not_before: i64,
+    not_after: i64,
+    fingerprint: String,
+    is_ca: bool,
+}
+
+/// Whether a certificate is valid at a point in time.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum CertificateValidity {
+    NotYetValid,
+    Valid,
+    Expired,
+}
+
+impl CertificateMetadata {
+    fn new(der: &[u8], certificate: &x509_parser::certificate::X509Certificate<'_>) -> Self {
+        let fingerprint = Sha256::digest(der)
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect();
+        Self {
+            der: der.to_vec(),
+            subject: certificate.subject().to_string(),
+            issuer: certificate.issuer().to_string(),
+            not_before: certificate.validity().not_before.timestamp(),
+            not_after: certificate.validity().not_after.timestamp(),
+            fingerprint,
+            is_ca: certificate.is_ca(),
+        }
+    }
+
+    /// Returns the certificate's subject, e.g. `CN=example.com, O=Example`.
+    pub fn subject(&self) -> &str {
+        &self.subject
+    }
+
+    /// Returns the subject of the certificate's issuer.
+    pub fn issuer(&self) -> &str {
+        &self.issuer
+    }
+
+    /// Returns the time the certificate becomes valid.
+    pub fn not_before(&self) -> SystemTime {
+        unix_time(self.not_before)
+    }
+
+    /// Returns the time the certificate expires.
+    pub fn not_after(&self) -> SystemTime {
+        unix_time(self.not_after)
+    }
+
+    /// Returns the SHA-256 digest of the certificate's DER encoding, as lowercase hex.
+    pub fn fingerprint(&self) -> &str {
+        &self.fingerprint
+    }
+
+    /// Returns whether the certificate's basic constraints allow it to act as a CA.
+    pub fn is_ca(&self) -> bool {
+        self.is_ca
+    }
+
+    /// Returns the certificate's DER encoding.
+    pub fn der(&self) -> &[u8] {
+        &self.der
+    }
+
+    /// Returns the certificate as a PEM block, with base64 lines of 64 characters and a trailing
+    /// newline, regardless of how it was written in the original bundle.
+    pub fn to_pem(&self) -> String {
+        let encoded = base64::engine::general_purpose::STANDARD.encode(&self.der);
+        let mut pem = String::from("-----BEGIN CERTIFICATE-----\n");
+        // Base64 output is ASCII, so every chunk is valid UTF-8.
+        for line in encoded.as_bytes().chunks(64) {
+            pem.push_str(std::str::from_utf8(line).unwrap_or_default());
+            pem.push('\n');
+        }
+        pem.push_str("-----END CERTIFICATE-----\n");
+        pem
+    }
+
+    /// Returns whether the certificate is valid at `time`, according to its validity window.
+    pub fn validity_at(&self, time: SystemTime) -> CertificateValidity {
+        if time < self.not_before() {
+            CertificateValidity::NotYetValid
+        } else if time > self.not_after() {
+            CertificateValidity::Expired
+        } else {
+            CertificateValidity::Valid
+        }
+    }
+}
+
+/// Converts seconds since the Unix epoch to a `SystemTime`.
+fn unix_time(seconds: i64) -> SystemTime {
+    let offset = Duration::from_secs(seconds.unsigned_abs());
+    if seconds >= 0 {
+        UNIX_EPOCH + offset
+    } else {
+        UNIX_EPOCH - offset
+    }
+}
+
 string_impls_for!(PemCertificateString, "PemCertificateString");
 
 #[cfg(test)]
 mod test_valid_pem_certificate_string {
-    use super::PemCertificateString;
+    use super::{CertificateValidity, PemCertificateString};
+    use base64::Engine;
     use std::convert::TryFrom;
+    use std::time::{Duration, UNIX_EPOCH};
 
     static TEST_PEM: &str = include_str!("../../tests/data/test-pem");
     static TEST_INCOMPLETE_PEM: &str = include_str!("../../tests/data/test-incomplete-pem");
@@ -1132,6 +2103,49 @@ mod test_valid_pem_certificate_string {
         assert!(PemCertificateString::try_from("").is_ok());
     }
 
+    #[test]
+    fn pem_certificate_metadata() {
+        let bundle = PemCertificateString::try_from(TEST_PEM).unwrap();
+        // The bundle has the same certificate twice.
+        assert_eq!(bundle.certificate_count(), 2);
+        let certificate = bundle.certificates().first().unwrap();
+        assert_eq!(bundle.certificates().last(), Some(certificate));
+        assert_eq!(certificate.subject(), "CN=bottlerocket, C=US, L=WASHINGTON");
+        assert_eq!(certificate.issuer(), certificate.subject());
+        assert!(certificate.is_ca());
+        assert_eq!(certificate.fingerprint().len(), 64);
+
+        // Text between certificates isn't kept when they're written back out.
+        let reencoded = base64::engine::general_purpose::STANDARD.encode(certificate.to_pem());
+        let reparsed = PemCertificateString::try_from(reencoded.as_str()).unwrap();
+        assert_eq!(reparsed.certificates(), std::slice::from_ref(certificate));
+
+        // The certificate is valid from 2021-07-07 21:01:30 to 2026-07-06 21:01:30 UTC.
+        let not_before = UNIX_EPOCH + Duration::from_secs(1_625_691_690);
+        let not_after = UNIX_EPOCH + Duration::from_secs(1_783_371_690);
+        assert_eq!(certificate.not_before(), not_before);
+        assert_eq!(certificate.not_after(), not_after);
+        assert_eq!(
+            certificate.validity_at(not_before - Duration::from_secs(1)),
+            CertificateValidity::NotYetValid
+        );
+        assert_eq!(
+            certificate.validity_at(not_after),
+            CertificateValidity::Valid
+        );
+        assert_eq!(
+            certificate.validity_at(not_after + Duration::from_secs(1)),
+            CertificateValidity::Expired
+        );
+
+        assert_eq!(
+            PemCertificateString::try_from("")
+                .unwrap()
+                .certificate_count(),
+            0
+        );
+    }
+
     #[test]
     fn invalid_pem_certificate() {
         // PEM with valid markers but with invalid content
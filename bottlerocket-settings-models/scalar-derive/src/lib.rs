@@ -28,7 +28,7 @@ enum.
 The macro can take the following input parameters when used with wrapper structs (in most cases you
 will not need to use these; the defaults will "just work"):
 - `as_ref_str: bool`: Set to `true` if need the macro to treat your inner type as a `String`.
-   This will happen automatically if your inner type is named `String`.
+  This will happen automatically if your inner type is named `String`.
 - `inner`: The name of the field that holds your `inner` type. Defaults to `inner`.
 
 # Examples
@@ -539,7 +539,7 @@ fn find_inner_field(data_struct: DataStruct, field_name: Option<&str>) -> (Strin
         }
         Fields::Unnamed(unnamed_field) => {
             let field_name = field_name.unwrap_or("0");
-            return (
+            (
                 field_name.to_string(),
                 typename(
                     &unnamed_field
@@ -551,7 +551,7 @@ fn find_inner_field(data_struct: DataStruct, field_name: Option<&str>) -> (Strin
                         )
                         .ty,
                 ),
-            );
+            )
         }
         Fields::Unit => {
             panic!(
@@ -1,3 +1,6 @@
+// These tests intentionally exercise comparisons against owned values and references.
+#![allow(clippy::cmp_owned, clippy::op_ref)]
+
 use bottlerocket_scalar::traits::{Scalar, Validate};
 use bottlerocket_scalar::ValidationError;
 use bottlerocket_scalar_derive::Scalar;
@@ -43,7 +46,7 @@ impl Validate for UnnamedFields {
         let input = input.into();
         // Contrived weirdness
         if input == 0 {
-            return Err(ValidationError::new("never zero"));
+            Err(ValidationError::new("never zero"))
         } else if input == 1 {
             Ok(Self(2, "it was 1 but I changed it to 2".to_string()))
         } else {
@@ -1,7 +1,7 @@
 //! Settings related to bootstrap commands.
 use bottlerocket_model_derive::model;
 use bottlerocket_modeled_types::{ApiclientCommand, BootstrapMode, Identifier};
-use bottlerocket_settings_sdk::{GenerateResult, SettingsModel};
+use bottlerocket_settings_sdk::{GenerateResult, KnownNames, Names, PathSegment, SettingsModel};
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use std::{collections::BTreeMap, convert::Infallible};
 
@@ -31,6 +31,12 @@ impl<'de> Deserialize<'de> for BootstrapCommandsSettingsV1 {
     }
 }
 
+impl KnownNames for BootstrapCommandsSettingsV1 {
+    fn known_names(path: &[PathSegment]) -> Option<Names> {
+        BTreeMap::<Identifier, BootstrapCommand>::known_names(path)
+    }
+}
+
 #[model(impl_default = true)]
 struct BootstrapCommand {
     commands: Vec<ApiclientCommand>,
@@ -66,6 +72,8 @@ impl SettingsModel for BootstrapCommandsSettingsV1 {
     }
 }
 
+type Result<T> = std::result::Result<T, Infallible>;
+
 #[cfg(test)]
 mod test_bootstrap_command {
     use super::*;
@@ -144,5 +152,3 @@ mod test_bootstrap_command {
         assert!(bootstrap_commands_err.is_err());
     }
 }
-
-type Result<T> = std::result::Result<T, Infallible>;
@@ -0,0 +1,19 @@
+//! The error type returned by the bootstrap-containers settings extension.
+use snafu::Snafu;
+
+/// The error type returned when migrating bootstrap-containers settings between versions.
+#[derive(Debug, Snafu)]
+#[snafu(visibility(pub(crate)))]
+pub enum Error {
+    #[snafu(display(
+        "Cannot migrate bootstrap container '{}' to {}: {}",
+        name,
+        version,
+        source
+    ))]
+    Migration {
+        name: String,
+        version: &'static str,
+        source: bottlerocket_modeled_types::error::Error,
+    },
+}
@@ -1,9 +1,14 @@
 //! Settings related to bootstrap containers.
 use bottlerocket_model_derive::model;
-use bottlerocket_modeled_types::{BootstrapMode, Identifier, Url, ValidBase64};
-use bottlerocket_settings_sdk::{GenerateResult, SettingsModel};
+use bottlerocket_modeled_types::{BootstrapMode, Identifier, ImageReference, Url, ValidBase64};
+use bottlerocket_settings_sdk::{
+    migrate_entries, GenerateResult, KnownNames, LinearlyMigrateable, Names, NoMigration,
+    PathSegment, SettingsModel,
+};
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
-use std::{collections::HashMap, convert::Infallible};
+use std::collections::HashMap;
+
+pub mod error;
 
 #[derive(Clone, Debug, Default, PartialEq)]
 pub struct BootstrapContainersSettingsV1 {
@@ -33,6 +38,12 @@ impl<'de> Deserialize<'de> for BootstrapContainersSettingsV1 {
     }
 }
 
+impl KnownNames for BootstrapContainersSettingsV1 {
+    fn known_names(path: &[PathSegment]) -> Option<Names> {
+        HashMap::<Identifier, BootstrapContainer>::known_names(path)
+    }
+}
+
 #[model(impl_default = true)]
 struct BootstrapContainer {
     source: Url,
@@ -41,11 +52,11 @@ struct BootstrapContainer {
     essential: bool,
 }
 
-type Result<T> = std::result::Result<T, Infallible>;
+type Result<T> = std::result::Result<T, error::Error>;
 
 impl SettingsModel for BootstrapContainersSettingsV1 {
     type PartialKind = Self;
-    type ErrorKind = Infallible;
+    type ErrorKind = error::Error;
 
     fn get_version() -> &'static str {
         "v1"
@@ -71,6 +82,145 @@ impl SettingsModel for BootstrapContainersSettingsV1 {
     }
 }
 
+impl LinearlyMigrateable for BootstrapContainersSettingsV1 {
+    type ForwardMigrationTarget = BootstrapContainersSettingsV2;
+    type BackwardMigrationTarget = NoMigration;
+
+    /// Sources must be valid image references to migrate forward.
+    fn migrate_forward(&self) -> Result<Self::ForwardMigrationTarget> {
+        let bootstrap_containers = migrate_entries(
+            &self.bootstrap_containers,
+            |container| {
+                Ok(BootstrapContainerV2 {
+                    source: container
+                        .source
+                        .as_ref()
+                        .map(|source| ImageReference::try_from(source.as_ref()))
+                        .transpose()?,
+                    mode: container.mode.clone(),
+                    user_data: container.user_data.clone(),
+                    essential: container.essential,
+                })
+            },
+            |name| error::MigrationSnafu {
+                name: name.to_string(),
+                version: "v2",
+            },
+        )?;
+        Ok(BootstrapContainersSettingsV2 {
+            bootstrap_containers,
+        })
+    }
+
+    fn migrate_backward(&self) -> Result<Self::BackwardMigrationTarget> {
+        NoMigration::no_defined_migration()
+    }
+}
+
+/// The second version of the bootstrap-containers settings, in which each container's source
+/// must be a valid image reference rather than any URL.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct BootstrapContainersSettingsV2 {
+    pub bootstrap_containers: HashMap<Identifier, BootstrapContainerV2>,
+}
+
+impl Serialize for BootstrapContainersSettingsV2 {
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        self.bootstrap_containers.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for BootstrapContainersSettingsV2 {
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let bootstrap_containers = HashMap::deserialize(deserializer)?;
+        Ok(Self {
+            bootstrap_containers,
+        })
+    }
+}
+
+impl KnownNames for BootstrapContainersSettingsV2 {
+    fn known_names(path: &[PathSegment]) -> Option<Names> {
+        HashMap::<Identifier, BootstrapContainerV2>::known_names(path)
+    }
+}
+
+#[model(impl_default = true)]
+struct BootstrapContainerV2 {
+    source: ImageReference,
+    mode: BootstrapMode,
+    user_data: ValidBase64,
+    essential: bool,
+}
+
+impl SettingsModel for BootstrapContainersSettingsV2 {
+    type PartialKind = Self;
+    type ErrorKind = error::Error;
+
+    fn get_version() -> &'static str {
+        "v2"
+    }
+
+    fn set(_current_value: Option<Self>, _target: Self) -> Result<()> {
+        // Set anything that parses as BootstrapContainersSettingsV2.
+        Ok(())
+    }
+
+    fn generate(
+        existing_partial: Option<Self::PartialKind>,
+        _dependent_settings: Option<serde_json::Value>,
+    ) -> Result<GenerateResult<Self::PartialKind, Self>> {
+        Ok(GenerateResult::Complete(
+            existing_partial.unwrap_or_default(),
+        ))
+    }
+
+    fn validate(_value: Self, _validated_settings: Option<serde_json::Value>) -> Result<()> {
+        // Validate anything that parses as BootstrapContainersSettingsV2.
+        Ok(())
+    }
+}
+
+impl LinearlyMigrateable for BootstrapContainersSettingsV2 {
+    type ForwardMigrationTarget = NoMigration;
+    type BackwardMigrationTarget = BootstrapContainersSettingsV1;
+
+    fn migrate_forward(&self) -> Result<Self::ForwardMigrationTarget> {
+        NoMigration::no_defined_migration()
+    }
+
+    fn migrate_backward(&self) -> Result<Self::BackwardMigrationTarget> {
+        let bootstrap_containers = migrate_entries(
+            &self.bootstrap_containers,
+            |container| {
+                Ok(BootstrapContainer {
+                    source: container
+                        .source
+                        .as_ref()
+                        .map(|source| Url::try_from(source.as_ref()))
+                        .transpose()?,
+                    mode: container.mode.clone(),
+                    user_data: container.user_data.clone(),
+                    essential: container.essential,
+                })
+            },
+            |name| error::MigrationSnafu {
+                name: name.to_string(),
+                version: "v1",
+            },
+        )?;
+        Ok(BootstrapContainersSettingsV1 {
+            bootstrap_containers,
+        })
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -134,4 +284,38 @@ mod test {
 
         assert_eq!(serialized_json, test_json);
     }
+
+    #[test]
+    fn test_migrate_bootstrap_containers() {
+        let v1: BootstrapContainersSettingsV1 = serde_json::from_value(json!({
+            "setup": {"source": "localhost/setup:1.0", "mode": "once", "essential": true}
+        }))
+        .unwrap();
+
+        let v2 = v1.migrate_forward().unwrap();
+        let setup = v2
+            .bootstrap_containers
+            .get(&Identifier::try_from("setup").unwrap())
+            .unwrap();
+        assert_eq!(setup.source.as_ref().unwrap().tag(), Some("1.0"));
+        assert_eq!(setup.essential, Some(true));
+
+        assert_eq!(v2.migrate_backward().unwrap(), v1);
+    }
+
+    #[test]
+    fn test_migrate_bootstrap_containers_failure() {
+        let v1: BootstrapContainersSettingsV1 = serde_json::from_value(json!({
+            "setup": {"source": "https://example.com/setup"}
+        }))
+        .unwrap();
+        let error = v1.migrate_forward().unwrap_err();
+        assert!(
+            error
+                .to_string()
+                .starts_with("Cannot migrate bootstrap container 'setup' to v2"),
+            "{}",
+            error
+        );
+    }
 }
@@ -1,13 +1,16 @@
-use bottlerocket_settings_sdk::{BottlerocketSetting, NullMigratorExtensionBuilder};
-use settings_extension_bootstrap_containers::BootstrapContainersSettingsV1;
+use bottlerocket_settings_sdk::{BottlerocketSetting, LinearMigratorExtensionBuilder};
+use settings_extension_bootstrap_containers::{
+    BootstrapContainersSettingsV1, BootstrapContainersSettingsV2,
+};
 use std::process::ExitCode;
 
 fn main() -> ExitCode {
     env_logger::init();
 
-    match NullMigratorExtensionBuilder::with_name("bootstrap-containers")
+    match LinearMigratorExtensionBuilder::with_name("bootstrap-containers")
         .with_models(vec![
             BottlerocketSetting::<BootstrapContainersSettingsV1>::model(),
+            BottlerocketSetting::<BootstrapContainersSettingsV2>::model(),
         ])
         .build()
     {
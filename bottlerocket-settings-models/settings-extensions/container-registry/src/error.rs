@@ -0,0 +1,20 @@
+//! The error type returned by the container-registry settings extension.
+use bottlerocket_settings_sdk::FieldErrors;
+use snafu::Snafu;
+
+/// The error type returned when validating, migrating, or rendering container-registry settings.
+#[derive(Debug, Snafu)]
+#[snafu(visibility(pub(crate)))]
+pub enum Error {
+    #[snafu(display("Invalid container-registry settings: {}", source))]
+    InvalidSettings { source: FieldErrors },
+
+    #[snafu(display("Cannot migrate {}.{} to v2: 'registry' is not set", field, index))]
+    MissingRegistry { field: &'static str, index: usize },
+
+    #[snafu(display("Failed to render {}: {}", document, source))]
+    Render {
+        document: &'static str,
+        source: serde_json::Error,
+    },
+}
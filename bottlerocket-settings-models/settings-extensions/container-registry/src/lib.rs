@@ -2,21 +2,30 @@
 //! registries, including credentials for logging into a registry, or mirrors to use when
 //! pulling from a registry.
 mod de;
+pub mod error;
+pub mod render;
+mod validate;
 
 use crate::de::deserialize_mirrors;
 use bottlerocket_model_derive::model;
 use bottlerocket_modeled_types::{SingleLineString, Url, ValidBase64};
-use bottlerocket_settings_sdk::{GenerateResult, SettingsModel};
-use std::convert::Infallible;
+use bottlerocket_settings_sdk::{
+    provide_template_helpers, FieldChecks, FieldErrors, GenerateResult, HelperDef,
+    LinearlyMigrateable, NoMigration, SettingsModel,
+};
+use snafu::{OptionExt, ResultExt};
+use std::collections::HashMap;
 
-#[model(impl_default = true)]
+#[model(impl_default = true, builder = true)]
 struct RegistryMirrorV1 {
+    #[builder(required)]
     registry: SingleLineString,
     endpoint: Vec<Url>,
 }
 
-#[model(impl_default = true)]
+#[model(impl_default = true, builder = true)]
 struct RegistryCredentialV1 {
+    #[builder(required)]
     registry: SingleLineString,
     username: SingleLineString,
     password: SingleLineString,
@@ -32,16 +41,19 @@ struct RegistrySettingsV1 {
         skip_serializing_if = "Option::is_none",
         deserialize_with = "deserialize_mirrors"
     )]
+    #[validate(nested)]
     mirrors: Vec<RegistryMirrorV1>,
-    #[serde(alias = "creds", default, skip_serializing_if = "Option::is_none")]
+    #[deprecated_alias = "creds"]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[validate(nested)]
     credentials: Vec<RegistryCredentialV1>,
 }
 
-type Result<T> = std::result::Result<T, Infallible>;
+type Result<T> = std::result::Result<T, error::Error>;
 
 impl SettingsModel for RegistrySettingsV1 {
     type PartialKind = Self;
-    type ErrorKind = Infallible;
+    type ErrorKind = error::Error;
 
     fn get_version() -> &'static str {
         "v1"
@@ -64,26 +76,262 @@ impl SettingsModel for RegistrySettingsV1 {
         ))
     }
 
+    fn validate(
+        value: Self,
+        _validated_settings: Option<serde_json::Value>,
+    ) -> std::result::Result<(), Self::ErrorKind> {
+        let errors = validate::duplicate_registry_errors(&value);
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(FieldErrors::from(errors)).context(error::InvalidSettingsSnafu)
+        }
+    }
+
+    fn field_checks() -> FieldChecks<Self> {
+        FieldChecks::declared()
+    }
+
+    fn template_helpers() -> Result<HashMap<String, Box<dyn HelperDef>>> {
+        use render::{docker_config_helper, hosts_toml_helper};
+
+        Ok(provide_template_helpers! {
+            "hosts_toml" => hosts_toml_helper,
+            "docker_config" => docker_config_helper,
+        })
+    }
+}
+
+impl LinearlyMigrateable for RegistrySettingsV1 {
+    type ForwardMigrationTarget = RegistrySettingsV2;
+    type BackwardMigrationTarget = NoMigration;
+
+    /// Every mirror and credential must name its registry to migrate forward. If a registry is
+    /// listed more than once, its last entry is kept, as it's the one the runtimes would use.
+    fn migrate_forward(&self) -> Result<Self::ForwardMigrationTarget> {
+        let mirrors = self
+            .mirrors
+            .as_ref()
+            .map(|mirrors| {
+                keyed_by_registry(mirrors, "mirrors", |mirror| {
+                    (
+                        mirror.registry.clone(),
+                        RegistryMirrorV2 {
+                            endpoint: mirror.endpoint.clone(),
+                        },
+                    )
+                })
+            })
+            .transpose()?;
+        let credentials = self
+            .credentials
+            .as_ref()
+            .map(|credentials| {
+                keyed_by_registry(credentials, "credentials", |credential| {
+                    (
+                        credential.registry.clone(),
+                        RegistryCredentialV2 {
+                            username: credential.username.clone(),
+                            password: credential.password.clone(),
+                            auth: credential.auth.clone(),
+                            identitytoken: credential.identitytoken.clone(),
+                        },
+                    )
+                })
+            })
+            .transpose()?;
+        Ok(RegistrySettingsV2 {
+            mirrors,
+            credentials,
+        })
+    }
+
+    fn migrate_backward(&self) -> Result<Self::BackwardMigrationTarget> {
+        NoMigration::no_defined_migration()
+    }
+}
+
+#[model(impl_default = true)]
+struct RegistryMirrorV2 {
+    endpoint: Vec<Url>,
+}
+
+#[model(impl_default = true)]
+struct RegistryCredentialV2 {
+    username: SingleLineString,
+    password: SingleLineString,
+    // This is the base64 encoding of "username:password"
+    auth: ValidBase64,
+    identitytoken: SingleLineString,
+}
+
+/// The second version of the container-registry settings, in which mirrors and credentials are
+/// keyed by the registry they apply to, so that each registry can only be listed once.
+#[model(impl_default = true)]
+struct RegistrySettingsV2 {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[validate(nested)]
+    mirrors: HashMap<SingleLineString, RegistryMirrorV2>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[validate(nested)]
+    credentials: HashMap<SingleLineString, RegistryCredentialV2>,
+}
+
+impl SettingsModel for RegistrySettingsV2 {
+    type PartialKind = Self;
+    type ErrorKind = error::Error;
+
+    fn get_version() -> &'static str {
+        "v2"
+    }
+
+    fn set(
+        _current_value: Option<Self>,
+        _target: Self,
+    ) -> std::result::Result<(), Self::ErrorKind> {
+        // Anything that correctly deserializes to RegistrySettingsV2 is ok
+        Ok(())
+    }
+
+    fn generate(
+        existing_partial: Option<Self::PartialKind>,
+        _dependent_settings: Option<serde_json::Value>,
+    ) -> Result<GenerateResult<Self::PartialKind, Self>> {
+        Ok(GenerateResult::Complete(
+            existing_partial.unwrap_or_default(),
+        ))
+    }
+
     fn validate(
         _value: Self,
         _validated_settings: Option<serde_json::Value>,
     ) -> std::result::Result<(), Self::ErrorKind> {
+        // Registries can't be listed twice, so only the fields need to be checked.
         Ok(())
     }
+
+    fn field_checks() -> FieldChecks<Self> {
+        FieldChecks::declared()
+    }
+
+    fn template_helpers() -> Result<HashMap<String, Box<dyn HelperDef>>> {
+        use render::{docker_config_v2_helper, hosts_toml_v2_helper};
+
+        Ok(provide_template_helpers! {
+            "hosts_toml" => hosts_toml_v2_helper,
+            "docker_config" => docker_config_v2_helper,
+        })
+    }
+}
+
+impl LinearlyMigrateable for RegistrySettingsV2 {
+    type ForwardMigrationTarget = NoMigration;
+    type BackwardMigrationTarget = RegistrySettingsV1;
+
+    fn migrate_forward(&self) -> Result<Self::ForwardMigrationTarget> {
+        NoMigration::no_defined_migration()
+    }
+
+    /// Mirrors and credentials are listed in order of their registries.
+    fn migrate_backward(&self) -> Result<Self::BackwardMigrationTarget> {
+        let mirrors = self.mirrors.as_ref().map(|mirrors| {
+            listed_by_registry(mirrors, |registry, mirror| RegistryMirrorV1 {
+                registry: Some(registry.clone()),
+                endpoint: mirror.endpoint.clone(),
+            })
+        });
+        let credentials = self.credentials.as_ref().map(|credentials| {
+            listed_by_registry(credentials, |registry, credential| RegistryCredentialV1 {
+                registry: Some(registry.clone()),
+                username: credential.username.clone(),
+                password: credential.password.clone(),
+                auth: credential.auth.clone(),
+                identitytoken: credential.identitytoken.clone(),
+            })
+        });
+        Ok(RegistrySettingsV1 {
+            mirrors,
+            credentials,
+        })
+    }
+}
+
+/// The fields shared by each version of a registry credential.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Credential<'a> {
+    pub(crate) auth: Option<&'a str>,
+    pub(crate) username: Option<&'a str>,
+    pub(crate) password: Option<&'a str>,
+    pub(crate) identitytoken: Option<&'a str>,
+}
+
+impl<'a> From<&'a RegistryCredentialV1> for Credential<'a> {
+    fn from(credential: &'a RegistryCredentialV1) -> Self {
+        Self {
+            auth: credential.auth.as_deref(),
+            username: credential.username.as_deref(),
+            password: credential.password.as_deref(),
+            identitytoken: credential.identitytoken.as_deref(),
+        }
+    }
+}
+
+impl<'a> From<&'a RegistryCredentialV2> for Credential<'a> {
+    fn from(credential: &'a RegistryCredentialV2) -> Self {
+        Self {
+            auth: credential.auth.as_deref(),
+            username: credential.username.as_deref(),
+            password: credential.password.as_deref(),
+            identitytoken: credential.identitytoken.as_deref(),
+        }
+    }
+}
+
+/// Converts a list of registry settings into a map keyed by registry, naming the entry in any
+/// error.
+fn keyed_by_registry<T, U, F>(
+    entries: &[T],
+    field: &'static str,
+    split: F,
+) -> Result<HashMap<SingleLineString, U>>
+where
+    F: Fn(&T) -> (Option<SingleLineString>, U),
+{
+    let mut keyed = HashMap::new();
+    for (index, entry) in entries.iter().enumerate() {
+        let (registry, value) = split(entry);
+        let registry = registry.context(error::MissingRegistrySnafu { field, index })?;
+        keyed.insert(registry, value);
+    }
+    Ok(keyed)
+}
+
+/// Converts a map of registry settings into a list sorted by registry.
+fn listed_by_registry<T, U, F>(entries: &HashMap<SingleLineString, T>, join: F) -> Vec<U>
+where
+    F: Fn(&SingleLineString, &T) -> U,
+{
+    let mut entries: Vec<_> = entries.iter().collect();
+    entries.sort_by_key(|(registry, _)| registry.to_string());
+    entries
+        .into_iter()
+        .map(|(registry, value)| join(registry, value))
+        .collect()
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
+    use bottlerocket_settings_sdk::{DeprecationWarning, ValidateFields};
 
     #[test]
     fn test_generate_container_registry_settings() {
         assert_eq!(
-            RegistrySettingsV1::generate(None, None),
-            Ok(GenerateResult::Complete(RegistrySettingsV1 {
+            RegistrySettingsV1::generate(None, None).unwrap(),
+            GenerateResult::Complete(RegistrySettingsV1 {
                 mirrors: None,
                 credentials: None,
-            }))
+            })
         )
     }
 
@@ -96,12 +344,13 @@ mod test {
         let mirrors = container_registry.mirrors.unwrap();
 
         assert_eq!(mirrors.len(), 1);
+        let mirror = mirrors.first().unwrap();
         assert_eq!(
-            mirrors[0].registry.clone().unwrap(),
+            mirror.registry.clone().unwrap(),
             SingleLineString::try_from("foo").unwrap(),
         );
         assert_eq!(
-            mirrors[0].endpoint.clone().unwrap(),
+            mirror.endpoint.clone().unwrap(),
             vec!(Url::try_from("https://example.net").unwrap()),
         );
     }
@@ -114,16 +363,185 @@ mod test {
         let credentials = container_registry.credentials.unwrap();
 
         assert_eq!(credentials.len(), 1);
+        let credential = credentials.first().unwrap();
         assert_eq!(
-            credentials[0].registry.clone().unwrap(),
+            credential.registry.clone().unwrap(),
             SingleLineString::try_from("foo").unwrap(),
         );
         assert_eq!(
-            credentials[0].auth.clone().unwrap(),
+            credential.auth.clone().unwrap(),
             ValidBase64::try_from("Ym90dGxlcm9ja2V0").unwrap(),
         );
-        assert!(credentials[0].username.is_none());
-        assert!(credentials[0].password.is_none());
-        assert!(credentials[0].identitytoken.is_none());
+        assert!(credential.username.is_none());
+        assert!(credential.password.is_none());
+        assert!(credential.identitytoken.is_none());
+    }
+
+    #[test]
+    fn test_builders_require_registry() {
+        let registry = SingleLineString::try_from("docker.io").unwrap();
+        let mirror = RegistryMirrorV1::builder()
+            .registry(registry.clone())
+            .endpoint(vec![Url::try_from("https://mirror.example.com").unwrap()])
+            .build()
+            .unwrap();
+        assert_eq!(mirror.registry, Some(registry));
+
+        let err = RegistryCredentialV1::builder()
+            .identitytoken(SingleLineString::try_from("token").unwrap())
+            .build()
+            .unwrap_err();
+        assert_eq!(err.missing_fields(), &["registry"]);
+    }
+
+    #[test]
+    fn test_deprecated_creds_alias() {
+        let test_json = serde_json::json!({"creds": [{"registry": "foo"}]});
+
+        let container_registry: RegistrySettingsV1 =
+            serde_json::from_value(test_json.clone()).unwrap();
+        assert_eq!(container_registry.credentials.unwrap().len(), 1);
+        assert_eq!(
+            RegistrySettingsV1::find_deprecated_aliases(&test_json),
+            vec![DeprecationWarning::new("creds", "credentials")]
+        );
+    }
+
+    #[test]
+    fn test_serde_container_registry_v2() {
+        let test_json = serde_json::json!({
+            "mirrors": {"docker.io": {"endpoint": ["https://mirror.example.com"]}},
+            "credentials": {"docker.io": {"auth": "dXNlcjpwYXNz"}},
+        });
+
+        let settings: RegistrySettingsV2 = serde_json::from_value(test_json.clone()).unwrap();
+        let docker_io = SingleLineString::try_from("docker.io").unwrap();
+        assert_eq!(
+            settings.mirrors.as_ref().unwrap().get(&docker_io).unwrap(),
+            &RegistryMirrorV2 {
+                endpoint: Some(vec![Url::try_from("https://mirror.example.com").unwrap()]),
+            }
+        );
+        assert_eq!(serde_json::to_value(&settings).unwrap(), test_json);
+    }
+
+    #[test]
+    fn test_v2_field_errors_name_the_registry() {
+        let settings: RegistrySettingsV2 = serde_json::from_value(serde_json::json!({
+            "mirrors": {"docker.io": {"endpoint": ["mirror.example.com"]}},
+            "credentials": {"docker.io": {"username": "user"}},
+        }))
+        .unwrap();
+
+        assert_eq!(
+            RegistrySettingsV2::field_checks()
+                .check_fields(&settings)
+                .unwrap_err()
+                .to_string(),
+            "'mirrors.docker.io.endpoint.0' must start with 'http://' or 'https://'; \
+             'credentials.docker.io.password' is required when username is set"
+        );
+    }
+
+    #[test]
+    fn test_migrate_forward() {
+        let v1: RegistrySettingsV1 = serde_json::from_value(serde_json::json!({
+            "mirrors": [
+                {"registry": "docker.io", "endpoint": ["https://old.example.com"]},
+                {"registry": "*", "endpoint": ["https://fallback.example.com"]},
+                {"registry": "docker.io", "endpoint": ["https://mirror.example.com"]},
+            ],
+            "creds": [{"registry": "docker.io", "username": "user", "password": "pass"}],
+        }))
+        .unwrap();
+
+        assert_eq!(
+            serde_json::to_value(v1.migrate_forward().unwrap()).unwrap(),
+            serde_json::json!({
+                "mirrors": {
+                    "*": {"endpoint": ["https://fallback.example.com"]},
+                    "docker.io": {"endpoint": ["https://mirror.example.com"]},
+                },
+                "credentials": {"docker.io": {"username": "user", "password": "pass"}},
+            })
+        );
+        assert_eq!(
+            RegistrySettingsV1::default().migrate_forward().unwrap(),
+            RegistrySettingsV2::default()
+        );
+    }
+
+    #[test]
+    fn test_migrate_forward_requires_registries() {
+        let v1: RegistrySettingsV1 = serde_json::from_value(serde_json::json!({
+            "credentials": [
+                {"registry": "docker.io", "auth": "dXNlcjpwYXNz"},
+                {"auth": "dXNlcjpwYXNz"},
+            ],
+        }))
+        .unwrap();
+
+        assert_eq!(
+            v1.migrate_forward().unwrap_err().to_string(),
+            "Cannot migrate credentials.1 to v2: 'registry' is not set"
+        );
+    }
+
+    #[test]
+    fn test_migrate_backward() {
+        let v2: RegistrySettingsV2 = serde_json::from_value(serde_json::json!({
+            "mirrors": {
+                "docker.io": {"endpoint": ["https://mirror.example.com"]},
+                "*": {"endpoint": ["https://fallback.example.com"]},
+            },
+            "credentials": {"docker.io": {"identitytoken": "token"}},
+        }))
+        .unwrap();
+
+        let v1 = v2.migrate_backward().unwrap();
+        assert_eq!(
+            serde_json::to_value(&v1).unwrap(),
+            serde_json::json!({
+                "mirrors": [
+                    {"registry": "*", "endpoint": ["https://fallback.example.com"]},
+                    {"registry": "docker.io", "endpoint": ["https://mirror.example.com"]},
+                ],
+                "credentials": [{"registry": "docker.io", "identitytoken": "token"}],
+            })
+        );
+        assert_eq!(v1.migrate_forward().unwrap(), v2);
+    }
+
+    #[test]
+    fn test_template_helpers_listed() {
+        let config: toml::Value =
+            toml::from_str(include_str!("../container-registry.toml")).unwrap();
+        let listed = |version: &str| {
+            let mut helpers: Vec<_> = config
+                .get(version)
+                .and_then(|settings| settings.get("templating"))
+                .and_then(|templating| templating.get("helpers"))
+                .and_then(toml::Value::as_array)
+                .unwrap()
+                .iter()
+                .map(|helper| helper.as_str().unwrap().to_string())
+                .collect();
+            helpers.sort();
+            helpers
+        };
+
+        let mut registered: Vec<_> = RegistrySettingsV1::template_helpers()
+            .unwrap()
+            .into_keys()
+            .collect();
+        registered.sort();
+        assert_eq!(registered, listed("v1"));
+
+        let mut registered: Vec<_> = RegistrySettingsV2::template_helpers()
+            .unwrap()
+            .into_keys()
+            .collect();
+        registered.sort();
+        assert_eq!(registered, listed("v2"));
     }
 }
@@ -1,12 +1,15 @@
-use bottlerocket_settings_sdk::{BottlerocketSetting, NullMigratorExtensionBuilder};
-use settings_extension_container_registry::RegistrySettingsV1;
+use bottlerocket_settings_sdk::{BottlerocketSetting, LinearMigratorExtensionBuilder};
+use settings_extension_container_registry::{RegistrySettingsV1, RegistrySettingsV2};
 use std::process::ExitCode;
 
 fn main() -> ExitCode {
     env_logger::init();
 
-    match NullMigratorExtensionBuilder::with_name("container-registry")
-        .with_models(vec![BottlerocketSetting::<RegistrySettingsV1>::model()])
+    match LinearMigratorExtensionBuilder::with_name("container-registry")
+        .with_models(vec![
+            BottlerocketSetting::<RegistrySettingsV1>::model(),
+            BottlerocketSetting::<RegistrySettingsV2>::model(),
+        ])
         .build()
     {
         Ok(extension) => extension.run(),
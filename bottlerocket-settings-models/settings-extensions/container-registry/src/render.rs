@@ -0,0 +1,485 @@
+//! Renders container runtime configuration from [`RegistrySettingsV1`] or [`RegistrySettingsV2`].
+//!
+//! Mirrors are rendered as containerd `hosts.toml` files, one per registry, and credentials as the
+//! `auths` of a docker `config.json`. Registries are sorted so that the rendered documents only
+//! change when the settings do. If a registry is listed more than once, its last entry is used.
+use crate::error::{self, Error};
+use crate::{Credential, RegistrySettingsV1, RegistrySettingsV2};
+use base64::Engine;
+use bottlerocket_modeled_types::Url;
+use bottlerocket_settings_sdk::helper::template_helper;
+use serde::Serialize;
+use snafu::ResultExt;
+use std::collections::BTreeMap;
+use std::convert::Infallible;
+
+type Result<T> = std::result::Result<T, Error>;
+
+/// The registry name whose mirrors are used for every registry without mirrors of its own.
+const DEFAULT_REGISTRY: &str = "*";
+/// The directory containerd reads the `hosts.toml` for other registries from.
+const DEFAULT_HOSTS_DIRECTORY: &str = "_default";
+
+const DOCKER_HUB: &str = "docker.io";
+/// The host containerd pulls Docker Hub images from.
+const DOCKER_HUB_SERVER: &str = "https://registry-1.docker.io";
+/// The key the docker CLI stores Docker Hub credentials under.
+const DOCKER_HUB_AUTH_KEY: &str = "https://index.docker.io/v1/";
+
+/// What containerd may use a mirror for; mirrors aren't trusted to accept pushes.
+const MIRROR_CAPABILITIES: &str = r#"["pull", "resolve"]"#;
+
+/// The mirrors and credentials of each registry, which either version of the settings can be
+/// read as.
+#[derive(Debug)]
+pub struct Registries<'a> {
+    mirrors: BTreeMap<&'a str, &'a [Url]>,
+    credentials: BTreeMap<&'a str, Credential<'a>>,
+}
+
+impl<'a> From<&'a RegistrySettingsV1> for Registries<'a> {
+    /// Entries that don't name their registry are skipped.
+    fn from(settings: &'a RegistrySettingsV1) -> Self {
+        let mirrors = settings.mirrors.iter().flatten().filter_map(|mirror| {
+            let registry = mirror.registry.as_deref()?;
+            Some((registry, mirror.endpoint.as_deref().unwrap_or_default()))
+        });
+        let credentials = settings
+            .credentials
+            .iter()
+            .flatten()
+            .filter_map(|credential| Some((credential.registry.as_deref()?, credential.into())));
+        Self::new(mirrors, credentials)
+    }
+}
+
+impl<'a> From<&'a RegistrySettingsV2> for Registries<'a> {
+    fn from(settings: &'a RegistrySettingsV2) -> Self {
+        let mirrors = settings.mirrors.iter().flatten().map(|(registry, mirror)| {
+            (
+                registry.as_ref(),
+                mirror.endpoint.as_deref().unwrap_or_default(),
+            )
+        });
+        let credentials = settings
+            .credentials
+            .iter()
+            .flatten()
+            .map(|(registry, credential)| (registry.as_ref(), credential.into()));
+        Self::new(mirrors, credentials)
+    }
+}
+
+impl<'a> Registries<'a> {
+    /// Collects the registries, keeping the last entry for each, and dropping mirrors without
+    /// endpoints.
+    fn new(
+        mirrors: impl Iterator<Item = (&'a str, &'a [Url])>,
+        credentials: impl Iterator<Item = (&'a str, Credential<'a>)>,
+    ) -> Self {
+        let mut mirrors: BTreeMap<_, _> = mirrors.collect();
+        mirrors.retain(|_, endpoints| !endpoints.is_empty());
+        Self {
+            mirrors,
+            credentials: credentials.collect(),
+        }
+    }
+
+    /// Renders the containerd `hosts.toml` for `registry`, or `None` if it has no mirrors.
+    ///
+    /// Mirrors are listed in the order they're set, which is the order containerd tries them in
+    /// before falling back to the registry itself.
+    pub fn hosts_toml(&self, registry: &str) -> Option<String> {
+        let endpoints = self.mirrors.get(registry)?;
+
+        let mut sections = Vec::new();
+        if registry != DEFAULT_REGISTRY {
+            let server = if registry == DOCKER_HUB {
+                DOCKER_HUB_SERVER.to_string()
+            } else {
+                format!("https://{}", registry)
+            };
+            sections.push(format!("server = {}\n", toml_string(&server)));
+        }
+        for endpoint in endpoints.iter() {
+            sections.push(format!(
+                "[host.{}]\ncapabilities = {}\n",
+                toml_string(endpoint.as_ref()),
+                MIRROR_CAPABILITIES
+            ));
+        }
+        Some(sections.join("\n"))
+    }
+
+    /// Renders the containerd `hosts.toml` for every registry with mirrors, keyed by the
+    /// directory it's read from.
+    pub fn hosts_tomls(&self) -> BTreeMap<String, String> {
+        self.mirrors
+            .keys()
+            .filter_map(|registry| {
+                self.hosts_toml(registry)
+                    .map(|hosts| (hosts_directory(registry).to_string(), hosts))
+            })
+            .collect()
+    }
+
+    /// Renders a docker `config.json` holding the credentials for each registry.
+    ///
+    /// A registry's `auth` is used if it's set, and otherwise its `username` and `password` are
+    /// encoded the same way. An `identitytoken` is passed along as is. Registries without any
+    /// usable credentials are left out.
+    pub fn docker_config(&self) -> Result<String> {
+        let mut auths = BTreeMap::new();
+        for (registry, credential) in &self.credentials {
+            let auth = docker_auth(credential);
+            if auth.auth.is_none() && auth.identitytoken.is_none() {
+                continue;
+            }
+            let key = if *registry == DOCKER_HUB {
+                DOCKER_HUB_AUTH_KEY
+            } else {
+                registry
+            };
+            auths.insert(key, auth);
+        }
+
+        serde_json::to_string_pretty(&DockerConfig { auths }).context(error::RenderSnafu {
+            document: "docker config.json",
+        })
+    }
+}
+
+/// The docker `config.json` document, of which only credentials are rendered.
+#[derive(Debug, Serialize)]
+struct DockerConfig<'a> {
+    auths: BTreeMap<&'a str, DockerAuth<'a>>,
+}
+
+#[derive(Debug, Serialize)]
+struct DockerAuth<'a> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    auth: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    identitytoken: Option<&'a str>,
+}
+
+/// Returns the directory, under containerd's registry `config_path`, that the `hosts.toml` for
+/// `registry` is read from.
+pub fn hosts_directory(registry: &str) -> &str {
+    if registry == DEFAULT_REGISTRY {
+        DEFAULT_HOSTS_DIRECTORY
+    } else {
+        registry
+    }
+}
+
+/// Renders the containerd `hosts.toml` for `registry` from either version of the settings; see
+/// [`Registries::hosts_toml`].
+pub fn render_hosts_toml<'a>(
+    settings: impl Into<Registries<'a>>,
+    registry: &str,
+) -> Option<String> {
+    settings.into().hosts_toml(registry)
+}
+
+/// Renders the containerd `hosts.toml` for every registry with mirrors; see
+/// [`Registries::hosts_tomls`].
+pub fn render_hosts_tomls<'a>(settings: impl Into<Registries<'a>>) -> BTreeMap<String, String> {
+    settings.into().hosts_tomls()
+}
+
+/// Renders a docker `config.json` from either version of the settings; see
+/// [`Registries::docker_config`].
+pub fn render_docker_config<'a>(settings: impl Into<Registries<'a>>) -> Result<String> {
+    settings.into().docker_config()
+}
+
+#[template_helper(ident = hosts_toml_helper, vis = "pub(crate)")]
+fn hosts_toml(
+    settings: RegistrySettingsV1,
+    registry: String,
+) -> std::result::Result<String, Infallible> {
+    Ok(render_hosts_toml(&settings, &registry).unwrap_or_default())
+}
+
+#[template_helper(ident = docker_config_helper, vis = "pub(crate)")]
+fn docker_config(settings: RegistrySettingsV1) -> Result<String> {
+    render_docker_config(&settings)
+}
+
+#[template_helper(ident = hosts_toml_v2_helper, vis = "pub(crate)")]
+fn hosts_toml_v2(
+    settings: RegistrySettingsV2,
+    registry: String,
+) -> std::result::Result<String, Infallible> {
+    Ok(render_hosts_toml(&settings, &registry).unwrap_or_default())
+}
+
+#[template_helper(ident = docker_config_v2_helper, vis = "pub(crate)")]
+fn docker_config_v2(settings: RegistrySettingsV2) -> Result<String> {
+    render_docker_config(&settings)
+}
+
+fn docker_auth<'a>(credential: &Credential<'a>) -> DockerAuth<'a> {
+    let auth = match (credential.auth, credential.username, credential.password) {
+        (Some(auth), _, _) => Some(auth.to_string()),
+        (None, Some(username), Some(password)) => Some(
+            base64::engine::general_purpose::STANDARD.encode(format!("{}:{}", username, password)),
+        ),
+        _ => None,
+    };
+    DockerAuth {
+        auth,
+        identitytoken: credential.identitytoken,
+    }
+}
+
+/// Quotes and escapes a string for use in a TOML document.
+fn toml_string(value: &str) -> String {
+    toml::Value::String(value.to_string()).to_string()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use serde_json::json;
+
+    fn settings(value: serde_json::Value) -> RegistrySettingsV1 {
+        serde_json::from_value(value).unwrap()
+    }
+
+    /// Returns the `auths` entry for `key` in a rendered docker `config.json`.
+    fn docker_auth_for(config: &str, key: &str) -> Option<serde_json::Value> {
+        let config: serde_json::Value = serde_json::from_str(config).unwrap();
+        config.get("auths").unwrap().get(key).cloned()
+    }
+
+    #[test]
+    fn test_hosts_toml_docker_hub() {
+        // Docker Hub images are pulled from a different host than the registry's name, and
+        // mirrors are listed in the order they're set.
+        let settings = settings(json!({"mirrors": [{
+            "registry": "docker.io",
+            "endpoint": ["https://mirror.example.com", "http://10.0.0.5:5000/v2"],
+        }]}));
+        assert_eq!(
+            render_hosts_toml(&settings, "docker.io").unwrap(),
+            "server = \"https://registry-1.docker.io\"\n\
+             \n\
+             [host.\"https://mirror.example.com\"]\n\
+             capabilities = [\"pull\", \"resolve\"]\n\
+             \n\
+             [host.\"http://10.0.0.5:5000/v2\"]\n\
+             capabilities = [\"pull\", \"resolve\"]\n"
+        );
+    }
+
+    #[test]
+    fn test_hosts_toml_is_valid_toml() {
+        let settings = settings(json!({"mirrors": [{
+            "registry": "registry.example.com:5000",
+            "endpoint": ["https://mirror.example.com:8443/path"],
+        }]}));
+        let hosts: toml::Value =
+            toml::from_str(&render_hosts_toml(&settings, "registry.example.com:5000").unwrap())
+                .unwrap();
+        assert_eq!(
+            hosts.get("server").and_then(toml::Value::as_str),
+            Some("https://registry.example.com:5000")
+        );
+        let host = hosts
+            .get("host")
+            .and_then(|host| host.get("https://mirror.example.com:8443/path"))
+            .unwrap();
+        assert_eq!(
+            host.get("capabilities"),
+            Some(&toml::Value::Array(vec![
+                toml::Value::from("pull"),
+                toml::Value::from("resolve")
+            ]))
+        );
+    }
+
+    #[test]
+    fn test_hosts_toml_default_registry() {
+        // The default mirrors don't name a server, as they apply to every registry.
+        let settings = settings(json!({"mirrors": [
+            {"registry": "*", "endpoint": ["https://fallback.example.com"]},
+        ]}));
+        let hosts = render_hosts_tomls(&settings);
+        assert_eq!(hosts.keys().collect::<Vec<_>>(), vec!["_default"]);
+        assert_eq!(
+            hosts.get("_default").unwrap(),
+            "[host.\"https://fallback.example.com\"]\ncapabilities = [\"pull\", \"resolve\"]\n"
+        );
+        assert_eq!(hosts_directory("docker.io"), "docker.io");
+    }
+
+    #[test]
+    fn test_hosts_toml_last_entry_wins() {
+        let settings = settings(json!({"mirrors": [
+            {"registry": "docker.io", "endpoint": ["https://old.example.com"]},
+            {"registry": "docker.io", "endpoint": ["https://new.example.com"]},
+        ]}));
+        let hosts = render_hosts_toml(&settings, "docker.io").unwrap();
+        assert!(hosts.contains("https://new.example.com"));
+        assert!(!hosts.contains("https://old.example.com"));
+    }
+
+    #[test]
+    fn test_hosts_toml_without_mirrors() {
+        // A registry without endpoints, or without a name, doesn't get a hosts.toml.
+        let settings = settings(json!({"mirrors": [
+            {"registry": "empty.example.com", "endpoint": []},
+            {"registry": "unset.example.com"},
+            {"endpoint": ["https://mirror.example.com"]},
+        ]}));
+        assert_eq!(render_hosts_toml(&settings, "empty.example.com"), None);
+        assert_eq!(render_hosts_toml(&settings, "unset.example.com"), None);
+        assert_eq!(render_hosts_toml(&settings, "unknown.example.com"), None);
+        assert!(render_hosts_tomls(&settings).is_empty());
+        assert!(render_hosts_tomls(&RegistrySettingsV1::default()).is_empty());
+    }
+
+    #[test]
+    fn test_docker_config_auths() {
+        let config = render_docker_config(&settings(json!({"credentials": [
+            {"registry": "docker.io", "username": "user", "password": "pass"},
+            {"registry": "auth.example.com", "auth": "cm9ib3Q6czNjcmV0"},
+            {"registry": "token.example.com", "identitytoken": "token"},
+        ]})))
+        .unwrap();
+
+        // Docker Hub credentials are stored under the key the docker CLI uses.
+        assert_eq!(
+            docker_auth_for(&config, "https://index.docker.io/v1/"),
+            Some(json!({"auth": "dXNlcjpwYXNz"}))
+        );
+        assert_eq!(docker_auth_for(&config, "docker.io"), None);
+        assert_eq!(
+            docker_auth_for(&config, "auth.example.com"),
+            Some(json!({"auth": "cm9ib3Q6czNjcmV0"}))
+        );
+        assert_eq!(
+            docker_auth_for(&config, "token.example.com"),
+            Some(json!({"identitytoken": "token"}))
+        );
+    }
+
+    #[test]
+    fn test_docker_config_auth_precedence() {
+        // `auth` is used over `username` and `password`, and an identity token is kept with it.
+        let config = render_docker_config(&settings(json!({"credentials": [{
+            "registry": "registry.example.com",
+            "auth": "cm9ib3Q6czNjcmV0",
+            "username": "ignored",
+            "password": "ignored",
+            "identitytoken": "token",
+        }]})))
+        .unwrap();
+        assert_eq!(
+            docker_auth_for(&config, "registry.example.com"),
+            Some(json!({"auth": "cm9ib3Q6czNjcmV0", "identitytoken": "token"}))
+        );
+    }
+
+    #[test]
+    fn test_docker_config_skips_unusable_credentials() {
+        let config = render_docker_config(&settings(json!({"credentials": [
+            {"registry": "nopass.example.com", "username": "user"},
+            {"registry": "nouser.example.com", "password": "pass"},
+            {"username": "user", "password": "pass"},
+        ]})))
+        .unwrap();
+        assert_eq!(config, "{\n  \"auths\": {}\n}");
+        assert_eq!(
+            render_docker_config(&RegistrySettingsV1::default()).unwrap(),
+            config
+        );
+    }
+
+    #[test]
+    fn test_helpers() {
+        let settings = json!({"mirrors": [
+            {"registry": "docker.io", "endpoint": ["https://mirror.example.com"]},
+        ]});
+        let hosts = hosts_toml_helper(vec![settings.clone(), json!("docker.io")]).unwrap();
+        assert!(hosts
+            .as_str()
+            .unwrap()
+            .starts_with("server = \"https://registry-1.docker.io\"\n"));
+
+        // Registries without mirrors render an empty document rather than failing the template.
+        assert_eq!(
+            hosts_toml_helper(vec![settings.clone(), json!("unknown.example.com")]).unwrap(),
+            json!("")
+        );
+        assert!(hosts_toml_helper(vec![settings.clone()]).is_err());
+        assert_eq!(
+            docker_config_helper(vec![settings]).unwrap(),
+            json!("{\n  \"auths\": {}\n}")
+        );
+    }
+
+    #[test]
+    fn test_v2_settings() {
+        // Registries are keyed in v2, but render the same way as in v1.
+        let settings: RegistrySettingsV2 = serde_json::from_value(json!({
+            "mirrors": {
+                "*": {"endpoint": ["https://fallback.example.com"]},
+                "docker.io": {"endpoint": ["https://mirror.example.com"]},
+                "empty.example.com": {"endpoint": []},
+            },
+            "credentials": {
+                "docker.io": {"username": "user", "password": "pass"},
+                "nopass.example.com": {"username": "user"},
+            },
+        }))
+        .unwrap();
+
+        let hosts = render_hosts_tomls(&settings);
+        assert_eq!(
+            hosts.keys().collect::<Vec<_>>(),
+            vec!["_default", "docker.io"]
+        );
+        assert_eq!(
+            hosts.get("docker.io").unwrap(),
+            "server = \"https://registry-1.docker.io\"\n\
+             \n\
+             [host.\"https://mirror.example.com\"]\n\
+             capabilities = [\"pull\", \"resolve\"]\n"
+        );
+
+        let config = render_docker_config(&settings).unwrap();
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(&config).unwrap(),
+            json!({"auths": {"https://index.docker.io/v1/": {"auth": "dXNlcjpwYXNz"}}})
+        );
+    }
+
+    #[test]
+    fn test_v2_helpers() {
+        let settings = json!({
+            "mirrors": {"docker.io": {"endpoint": ["https://mirror.example.com"]}},
+            "credentials": {"token.example.com": {"identitytoken": "token"}},
+        });
+        let hosts = hosts_toml_v2_helper(vec![settings.clone(), json!("docker.io")]).unwrap();
+        assert!(hosts
+            .as_str()
+            .unwrap()
+            .contains("[host.\"https://mirror.example.com\"]\n"));
+        assert_eq!(
+            hosts_toml_v2_helper(vec![settings.clone(), json!("unknown.example.com")]).unwrap(),
+            json!("")
+        );
+
+        let config = docker_config_v2_helper(vec![settings]).unwrap();
+        assert_eq!(
+            docker_auth_for(config.as_str().unwrap(), "token.example.com"),
+            Some(json!({"identitytoken": "token"}))
+        );
+
+        // The v1 list of mirrors isn't accepted by the v2 helpers.
+        assert!(docker_config_v2_helper(vec![json!({"mirrors": []})]).is_err());
+    }
+}
@@ -0,0 +1,250 @@
+//! Checks that registry mirrors and credentials can be used by the container runtimes.
+use crate::{
+    Credential, RegistryCredentialV1, RegistryCredentialV2, RegistryMirrorV1, RegistryMirrorV2,
+    RegistrySettingsV1,
+};
+use base64::Engine;
+use bottlerocket_modeled_types::Url;
+use bottlerocket_settings_sdk::{FieldError, FieldPath, ValidateFields};
+use std::collections::HashMap;
+
+impl ValidateFields for RegistryMirrorV1 {
+    fn collect_field_errors(&self, path: &FieldPath, errors: &mut Vec<FieldError>) {
+        if self.registry.is_none() {
+            errors.push(FieldError::new(path.join("registry"), "is required"));
+        }
+        endpoint_errors(path, self.endpoint.as_deref(), errors);
+    }
+}
+
+impl ValidateFields for RegistryMirrorV2 {
+    fn collect_field_errors(&self, path: &FieldPath, errors: &mut Vec<FieldError>) {
+        endpoint_errors(path, self.endpoint.as_deref(), errors);
+    }
+}
+
+impl ValidateFields for RegistryCredentialV1 {
+    fn collect_field_errors(&self, path: &FieldPath, errors: &mut Vec<FieldError>) {
+        if self.registry.is_none() {
+            errors.push(FieldError::new(path.join("registry"), "is required"));
+        }
+        credential_errors(path, self.into(), errors);
+    }
+}
+
+impl ValidateFields for RegistryCredentialV2 {
+    fn collect_field_errors(&self, path: &FieldPath, errors: &mut Vec<FieldError>) {
+        credential_errors(path, self.into(), errors);
+    }
+}
+
+/// Checks that container runtimes can pull from each mirror endpoint.
+fn endpoint_errors(path: &FieldPath, endpoints: Option<&[Url]>, errors: &mut Vec<FieldError>) {
+    // Container runtimes only pull from mirrors over HTTP(S), and need to be told which.
+    for (index, endpoint) in endpoints.into_iter().flatten().enumerate() {
+        let scheme = endpoint
+            .split_once("://")
+            .map(|(scheme, _)| scheme.to_ascii_lowercase());
+        if !matches!(scheme.as_deref(), Some("http" | "https")) {
+            errors.push(FieldError::new(
+                path.join("endpoint").join(index),
+                "must start with 'http://' or 'https://'",
+            ));
+        }
+    }
+}
+
+/// Checks that a credential is complete, and doesn't set more than one kind of password.
+fn credential_errors(path: &FieldPath, credential: Credential<'_>, errors: &mut Vec<FieldError>) {
+    let field = |name| path.join(name);
+    match (credential.auth, credential.username, credential.password) {
+        (Some(_), Some(_), _) | (Some(_), _, Some(_)) => errors.push(FieldError::new(
+            field("auth"),
+            "must not be set along with username or password",
+        )),
+        (Some(auth), None, None) if !is_encoded_user_pass(auth) => errors.push(FieldError::new(
+            field("auth"),
+            "must be the base64 encoding of 'username:password'",
+        )),
+        // Identity tokens are sent along with the username they were issued for.
+        (None, Some(_), None) if credential.identitytoken.is_none() => errors.push(
+            FieldError::new(field("password"), "is required when username is set"),
+        ),
+        (None, None, Some(_)) => errors.push(FieldError::new(
+            field("username"),
+            "is required when password is set",
+        )),
+        (None, None, None) if credential.identitytoken.is_none() => errors.push(FieldError::new(
+            path,
+            "must set auth, username and password, or identitytoken",
+        )),
+        _ => {}
+    }
+}
+
+/// Checks that `auth` decodes to a username and password separated by a colon.
+fn is_encoded_user_pass(auth: &str) -> bool {
+    base64::engine::general_purpose::STANDARD
+        .decode(auth)
+        .ok()
+        .and_then(|decoded| String::from_utf8(decoded).ok())
+        .and_then(|decoded| {
+            decoded
+                .split_once(':')
+                .map(|(username, _)| !username.is_empty())
+        })
+        .unwrap_or(false)
+}
+
+/// Returns a [`FieldError`] for each mirror or credential whose registry is already listed earlier
+/// in the same list, since only one of them would be used.
+pub(crate) fn duplicate_registry_errors(settings: &RegistrySettingsV1) -> Vec<FieldError> {
+    let mirrors = settings
+        .mirrors
+        .iter()
+        .flatten()
+        .map(|mirror| mirror.registry.as_ref());
+    let credentials = settings
+        .credentials
+        .iter()
+        .flatten()
+        .map(|credential| credential.registry.as_ref());
+
+    let mut errors = duplicates("mirrors", mirrors);
+    errors.extend(duplicates("credentials", credentials));
+    errors
+}
+
+fn duplicates<'a, I, R>(field: &str, registries: I) -> Vec<FieldError>
+where
+    I: Iterator<Item = Option<&'a R>>,
+    R: AsRef<str> + 'a,
+{
+    let mut first_seen = HashMap::new();
+    let mut errors = Vec::new();
+    for (index, registry) in registries.enumerate() {
+        let Some(registry) = registry.map(AsRef::as_ref) else {
+            continue;
+        };
+        match first_seen.get(registry) {
+            Some(first) => errors.push(FieldError::new(
+                FieldPath::from(field).join(index).join("registry"),
+                format!(
+                    "duplicates the registry at {}",
+                    FieldPath::from(field).join(*first)
+                ),
+            )),
+            None => {
+                first_seen.insert(registry, index);
+            }
+        }
+    }
+    errors
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use serde_json::json;
+
+    fn field_errors(value: serde_json::Value) -> Vec<String> {
+        let settings: RegistrySettingsV1 = serde_json::from_value(value).unwrap();
+        let mut errors = Vec::new();
+        settings.collect_field_errors(&FieldPath::default(), &mut errors);
+        errors.iter().map(ToString::to_string).collect()
+    }
+
+    #[test]
+    fn test_valid_credentials() {
+        let errors = field_errors(json!({
+            "credentials": [
+                {"registry": "a.example.com", "auth": "dXNlcjpwYXNz"},
+                {"registry": "b.example.com", "username": "user", "password": "pass"},
+                {"registry": "c.example.com", "identitytoken": "token"},
+                {"registry": "d.example.com", "username": "user", "identitytoken": "token"},
+            ],
+        }));
+        assert!(errors.is_empty(), "{:?}", errors);
+    }
+
+    #[test]
+    fn test_invalid_credentials() {
+        assert_eq!(
+            field_errors(json!({
+                "credentials": [
+                    {"registry": "a.example.com", "auth": "dXNlcjpwYXNz", "username": "user"},
+                    {"registry": "b.example.com", "username": "user"},
+                    {"registry": "c.example.com", "password": "pass"},
+                    {"registry": "d.example.com"},
+                    // "userpass", without a colon
+                    {"registry": "e.example.com", "auth": "dXNlcnBhc3M="},
+                    // ":pass", without a username
+                    {"registry": "f.example.com", "auth": "OnBhc3M="},
+                    {"auth": "dXNlcjpwYXNz"},
+                ],
+            })),
+            vec![
+                "'credentials.0.auth' must not be set along with username or password",
+                "'credentials.1.password' is required when username is set",
+                "'credentials.2.username' is required when password is set",
+                "'credentials.3' must set auth, username and password, or identitytoken",
+                "'credentials.4.auth' must be the base64 encoding of 'username:password'",
+                "'credentials.5.auth' must be the base64 encoding of 'username:password'",
+                "'credentials.6.registry' is required",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_mirror_endpoints() {
+        assert_eq!(
+            field_errors(json!({
+                "mirrors": [
+                    {
+                        "registry": "docker.io",
+                        "endpoint": [
+                            "https://mirror.example.com",
+                            "HTTP://10.0.0.5:5000",
+                            "mirror.example.com",
+                            "ftp://mirror.example.com",
+                        ],
+                    },
+                    {"endpoint": ["https://mirror.example.com"]},
+                ],
+            })),
+            vec![
+                "'mirrors.0.endpoint.2' must start with 'http://' or 'https://'",
+                "'mirrors.0.endpoint.3' must start with 'http://' or 'https://'",
+                "'mirrors.1.registry' is required",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_duplicate_registries() {
+        let settings: RegistrySettingsV1 = serde_json::from_value(json!({
+            "mirrors": [
+                {"registry": "docker.io", "endpoint": ["https://a.example.com"]},
+                {"registry": "*", "endpoint": ["https://b.example.com"]},
+                {"registry": "docker.io", "endpoint": ["https://c.example.com"]},
+            ],
+            "credentials": [
+                {"registry": "docker.io", "auth": "dXNlcjpwYXNz"},
+                {"registry": "docker.io", "identitytoken": "token"},
+            ],
+        }))
+        .unwrap();
+
+        let errors: Vec<_> = duplicate_registry_errors(&settings)
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        assert_eq!(
+            errors,
+            vec![
+                "'mirrors.2.registry' duplicates the registry at mirrors.0",
+                "'credentials.1.registry' duplicates the registry at credentials.0",
+            ]
+        );
+    }
+}
@@ -0,0 +1,14 @@
+//! The error type returned by the DNS settings extension.
+use bottlerocket_settings_sdk::{CrossValidationError, FieldErrors};
+use snafu::Snafu;
+
+/// The error type returned when validating DNS settings.
+#[derive(Debug, Snafu)]
+#[snafu(visibility(pub(crate)))]
+pub enum Error {
+    #[snafu(display("Invalid DNS settings: {}", source))]
+    InvalidSettings { source: FieldErrors },
+
+    #[snafu(display("Cannot read the settings DNS is validated against: {}", source))]
+    CrossValidation { source: CrossValidationError },
+}
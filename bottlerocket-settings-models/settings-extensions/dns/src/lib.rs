@@ -1,21 +1,51 @@
 //! Settings related to custom DNS settings
 use bottlerocket_model_derive::model;
 use bottlerocket_modeled_types::ValidLinuxHostname;
-use bottlerocket_settings_sdk::{GenerateResult, SettingsModel};
-use std::convert::Infallible;
+use bottlerocket_settings_sdk::{
+    provide_template_helpers, FieldChecks, FieldErrors, GenerateResult, HelperDef, SettingsModel,
+    ValidatedSettings,
+};
+use snafu::ResultExt;
+use std::collections::HashMap;
 use std::net::IpAddr;
 
+pub mod error;
+pub mod resolv;
+mod validate;
+
+pub use validate::DnsInputs;
+
 #[model(impl_default = true)]
 pub struct DnsSettingsV1 {
+    // The resolver only queries the first three name servers (MAXNS).
+    #[validate(len(max = 3))]
     name_servers: Vec<IpAddr>,
+    #[validate(custom = validate::search_list)]
     search_list: Vec<ValidLinuxHostname>,
+    #[validate(nested)]
+    options: ResolverOptionsV1,
+}
+
+/// The options line of `resolv.conf`. The resolver caps each number at the given maximum rather
+/// than rejecting it, so larger values are rejected here instead of silently being ignored.
+#[model(impl_default = true)]
+struct ResolverOptionsV1 {
+    #[validate(range(max = 15))]
+    ndots: u8,
+    #[validate(range(min = 1, max = 30))]
+    timeout: u8,
+    #[validate(range(min = 1, max = 5))]
+    attempts: u8,
+    rotate: bool,
+    edns0: bool,
+    single_request: bool,
 }
 
-type Result<T> = std::result::Result<T, Infallible>;
+type Result<T> = std::result::Result<T, error::Error>;
 
 impl SettingsModel for DnsSettingsV1 {
     type PartialKind = Self;
-    type ErrorKind = Infallible;
+    type ErrorKind = error::Error;
 
     fn get_version() -> &'static str {
         "v1"
@@ -35,9 +65,28 @@ impl SettingsModel for DnsSettingsV1 {
         ))
     }
 
-    fn validate(_value: Self, _validated_settings: Option<serde_json::Value>) -> Result<()> {
-        // DnsSettingsV1 is validated during deserialization.
-        Ok(())
+    fn validate(value: Self, validated_settings: Option<serde_json::Value>) -> Result<()> {
+        let inputs: DnsInputs = ValidatedSettings::new(validated_settings)
+            .get()
+            .context(error::CrossValidationSnafu)?;
+        let errors = validate::hostname_errors(&value, &inputs);
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(FieldErrors::from(errors)).context(error::InvalidSettingsSnafu)
+        }
+    }
+
+    fn field_checks() -> FieldChecks<Self> {
+        FieldChecks::declared()
+    }
+
+    fn template_helpers() -> Result<HashMap<String, Box<dyn HelperDef>>> {
+        use resolv::resolv_conf_helper;
+
+        Ok(provide_template_helpers! {
+            "resolv_conf" => resolv_conf_helper,
+        })
     }
 }
 
@@ -49,11 +98,12 @@ mod test {
     #[test]
     fn test_generate_dns_settings() {
         assert_eq!(
-            DnsSettingsV1::generate(None, None),
-            Ok(GenerateResult::Complete(DnsSettingsV1 {
+            DnsSettingsV1::generate(None, None).unwrap(),
+            GenerateResult::Complete(DnsSettingsV1 {
                 name_servers: None,
                 search_list: None,
-            }))
+                options: None,
+            })
         )
     }
 
@@ -81,4 +131,26 @@ mod test {
         let results = serde_json::to_string(&dns).unwrap();
         assert_eq!(results, test_json);
     }
+
+    #[test]
+    fn test_template_helpers_listed() {
+        let config: toml::Value = toml::from_str(include_str!("../dns.toml")).unwrap();
+        let mut listed: Vec<_> = config
+            .get("v1")
+            .and_then(|v1| v1.get("templating"))
+            .and_then(|templating| templating.get("helpers"))
+            .and_then(toml::Value::as_array)
+            .unwrap()
+            .iter()
+            .map(|helper| helper.as_str().unwrap().to_string())
+            .collect();
+        listed.sort();
+
+        let mut registered: Vec<_> = DnsSettingsV1::template_helpers()
+            .unwrap()
+            .into_keys()
+            .collect();
+        registered.sort();
+        assert_eq!(registered, listed);
+    }
 }
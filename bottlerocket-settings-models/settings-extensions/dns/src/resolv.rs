@@ -0,0 +1,128 @@
+//! Renders `resolv.conf` from [`DnsSettingsV1`].
+use crate::DnsSettingsV1;
+use bottlerocket_settings_sdk::helper::template_helper;
+use std::convert::Infallible;
+
+/// Renders a `resolv.conf` with a `nameserver` line for each name server, followed by the search
+/// list and resolver options, if they're set.
+pub fn render_resolv_conf(settings: &DnsSettingsV1) -> String {
+    let mut lines: Vec<String> = settings
+        .name_servers
+        .iter()
+        .flatten()
+        .map(|name_server| format!("nameserver {}", name_server))
+        .collect();
+
+    let search_list: Vec<&str> = settings
+        .search_list
+        .iter()
+        .flatten()
+        .map(AsRef::as_ref)
+        .collect();
+    if !search_list.is_empty() {
+        lines.push(format!("search {}", search_list.join(" ")));
+    }
+
+    let mut options = Vec::new();
+    if let Some(resolver) = &settings.options {
+        for (name, value) in [
+            ("ndots", resolver.ndots),
+            ("timeout", resolver.timeout),
+            ("attempts", resolver.attempts),
+        ] {
+            if let Some(value) = value {
+                options.push(format!("{}:{}", name, value));
+            }
+        }
+        for (name, enabled) in [
+            ("rotate", resolver.rotate),
+            ("edns0", resolver.edns0),
+            ("single-request", resolver.single_request),
+        ] {
+            if enabled == Some(true) {
+                options.push(name.to_string());
+            }
+        }
+    }
+    if !options.is_empty() {
+        lines.push(format!("options {}", options.join(" ")));
+    }
+
+    lines.into_iter().map(|line| line + "\n").collect()
+}
+
+#[template_helper(ident = resolv_conf_helper, vis = "pub(crate)")]
+fn resolv_conf(settings: DnsSettingsV1) -> Result<String, Infallible> {
+    Ok(render_resolv_conf(&settings))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use serde_json::json;
+
+    fn render(value: serde_json::Value) -> String {
+        render_resolv_conf(&serde_json::from_value(value).unwrap())
+    }
+
+    #[test]
+    fn test_name_servers() {
+        // Name servers are listed in the order they're set, and IPv6 addresses aren't bracketed.
+        assert_eq!(
+            render(json!({"name-servers": ["fd00:ec2::253", "169.254.169.253"]})),
+            "nameserver fd00:ec2::253\nnameserver 169.254.169.253\n"
+        );
+    }
+
+    #[test]
+    fn test_search_list() {
+        assert_eq!(
+            render(json!({
+                "search-list": ["us-west-2.compute.internal", "example.com"],
+                "name-servers": ["169.254.169.253"],
+            })),
+            "nameserver 169.254.169.253\nsearch us-west-2.compute.internal example.com\n"
+        );
+        // An empty search list doesn't clear the resolver's default search domain.
+        assert_eq!(render(json!({"search-list": []})), "");
+    }
+
+    #[test]
+    fn test_options() {
+        // Options are written in a fixed order, whatever order they're set in.
+        assert_eq!(
+            render(json!({"options": {
+                "single-request": true,
+                "edns0": true,
+                "rotate": true,
+                "attempts": 3,
+                "timeout": 2,
+                "ndots": 0,
+            }})),
+            "options ndots:0 timeout:2 attempts:3 rotate edns0 single-request\n"
+        );
+        // Flags that are disabled aren't written, and neither is an options line without any.
+        assert_eq!(
+            render(json!({
+                "name-servers": ["1.1.1.1"],
+                "options": {"rotate": false, "edns0": false},
+            })),
+            "nameserver 1.1.1.1\n"
+        );
+        assert_eq!(render_resolv_conf(&DnsSettingsV1::default()), "");
+    }
+
+    #[test]
+    fn test_helper() {
+        assert_eq!(
+            resolv_conf_helper(vec![json!({
+                "name-servers": ["1.1.1.1"],
+                "options": {"edns0": true},
+            })])
+            .unwrap(),
+            json!("nameserver 1.1.1.1\noptions edns0\n")
+        );
+        assert!(resolv_conf_helper(vec![json!({"name-servers": ["not an address"]})]).is_err());
+        assert!(resolv_conf_helper(vec![]).is_err());
+    }
+}
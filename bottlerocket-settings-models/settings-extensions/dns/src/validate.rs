@@ -0,0 +1,163 @@
+//! Checks the limits of the glibc resolver, and the relationship between the DNS settings and the
+//! host's name.
+use crate::DnsSettingsV1;
+use bottlerocket_modeled_types::ValidLinuxHostname;
+use bottlerocket_settings_sdk::{CrossValidationInputs, FieldError};
+use serde::Deserialize;
+use std::net::IpAddr;
+
+/// The settings that [`DnsSettingsV1`] cross-validates against.
+#[derive(Debug, Deserialize)]
+pub struct DnsInputs {
+    network: NetworkInputs,
+}
+
+/// The parts of the network settings that affect name resolution.
+#[derive(Debug, Deserialize)]
+struct NetworkInputs {
+    hostname: Option<ValidLinuxHostname>,
+}
+
+impl CrossValidationInputs for DnsInputs {
+    fn required_settings() -> &'static [&'static str] {
+        &["network"]
+    }
+}
+
+/// Checks that the search list only lists domains.
+pub(crate) fn search_list(domains: &[ValidLinuxHostname]) -> Result<(), String> {
+    if let Some(address) = domains
+        .iter()
+        .find(|domain| domain.parse::<IpAddr>().is_ok())
+    {
+        return Err(format!("must only list domains, not '{}'", address));
+    }
+    Ok(())
+}
+
+/// Checks that the host's own short name still resolves when a search list is set.
+///
+/// Without a search list, the resolver searches the domain of the host's name. A search list
+/// replaces that domain rather than adding to it, so the domain of a fully qualified
+/// `network.hostname` must be listed for the host's short name to resolve under it.
+pub(crate) fn hostname_errors(settings: &DnsSettingsV1, inputs: &DnsInputs) -> Vec<FieldError> {
+    let Some(hostname) = inputs.network.hostname.as_ref() else {
+        return Vec::new();
+    };
+    let Some((_, domain)) = hostname.split_once('.') else {
+        return Vec::new();
+    };
+    let Some(search_list) = settings
+        .search_list
+        .as_ref()
+        .filter(|list| !list.is_empty())
+    else {
+        return Vec::new();
+    };
+
+    if search_list.iter().any(|listed| listed.as_ref() == domain) {
+        return Vec::new();
+    }
+    vec![FieldError::new(
+        "search-list",
+        format!(
+            "must include '{}' for network.hostname ('{}') to resolve by its short name",
+            domain, hostname
+        ),
+    )]
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use bottlerocket_settings_sdk::SettingsModel;
+    use serde_json::json;
+
+    fn field_errors(value: serde_json::Value) -> String {
+        let settings: DnsSettingsV1 = serde_json::from_value(value).unwrap();
+        DnsSettingsV1::field_checks()
+            .check_fields(&settings)
+            .unwrap_err()
+            .to_string()
+    }
+
+    #[test]
+    fn test_resolver_limits() {
+        assert_eq!(
+            field_errors(json!({
+                "name-servers": ["1.1.1.1", "8.8.8.8", "9.9.9.9", "2606:4700:4700::1111"],
+                "search-list": ["a.example", "10.0.0.1"],
+            })),
+            "'name-servers' must have a length of at most 3; \
+             'search-list' must only list domains, not '10.0.0.1'"
+        );
+    }
+
+    #[test]
+    fn test_long_search_list() {
+        // glibc 2.26 and later read any number of search domains, of any total length.
+        let long_domain = |name: char| format!("{}.example", name.to_string().repeat(63));
+        let settings: DnsSettingsV1 = serde_json::from_value(json!({
+            "search-list": ('a'..='h').map(long_domain).collect::<Vec<_>>(),
+        }))
+        .unwrap();
+        assert!(DnsSettingsV1::field_checks()
+            .check_fields(&settings)
+            .is_ok());
+    }
+
+    #[test]
+    fn test_options_limits() {
+        assert_eq!(
+            field_errors(json!({
+                "options": {"ndots": 16, "timeout": 0, "attempts": 6},
+            })),
+            "'options.ndots' must be at most 15; \
+             'options.timeout' must be at least 1; \
+             'options.attempts' must be at most 5"
+        );
+    }
+
+    #[test]
+    fn test_valid_settings() {
+        let settings: DnsSettingsV1 = serde_json::from_value(json!({
+            "name-servers": ["1.1.1.1", "8.8.8.8", "2606:4700:4700::1111"],
+            "search-list": ["us-west-2.compute.internal", "example.com"],
+            "options": {"ndots": 2, "timeout": 30, "attempts": 5, "rotate": true},
+        }))
+        .unwrap();
+        assert!(DnsSettingsV1::field_checks()
+            .check_fields(&settings)
+            .is_ok());
+    }
+
+    #[test]
+    fn test_search_list_hostname() {
+        let validate = |search_list: serde_json::Value, network: serde_json::Value| {
+            let settings: DnsSettingsV1 =
+                serde_json::from_value(json!({"search-list": search_list})).unwrap();
+            DnsSettingsV1::validate(settings, Some(json!({"network": network})))
+                .map_err(|e| e.to_string())
+        };
+        let hostname = json!({"hostname": "node.us-west-2.compute.internal"});
+
+        assert_eq!(
+            validate(json!(["example.com"]), hostname.clone()).unwrap_err(),
+            "Invalid DNS settings: 'search-list' must include 'us-west-2.compute.internal' for \
+             network.hostname ('node.us-west-2.compute.internal') to resolve by its short name"
+        );
+        // The host's domain may be listed anywhere.
+        assert!(validate(
+            json!(["example.com", "us-west-2.compute.internal"]),
+            hostname.clone()
+        )
+        .is_ok());
+        // A parent of the host's domain doesn't resolve the short name.
+        assert!(validate(json!(["compute.internal"]), hostname.clone()).is_err());
+
+        // Without a search list, or a domain in the hostname, the resolver has nothing to lose.
+        assert!(validate(json!([]), hostname).is_ok());
+        assert!(validate(json!(["example.com"]), json!({"hostname": "node"})).is_ok());
+        assert!(validate(json!(["example.com"]), json!({})).is_ok());
+    }
+}
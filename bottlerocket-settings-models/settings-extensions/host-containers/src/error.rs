@@ -0,0 +1,14 @@
+//! The error type returned by the host-containers settings extension.
+use snafu::Snafu;
+
+/// The error type returned when migrating host-containers settings between versions.
+#[derive(Debug, Snafu)]
+#[snafu(visibility(pub(crate)))]
+pub enum Error {
+    #[snafu(display("Cannot migrate host container '{}' to {}: {}", name, version, source))]
+    Migration {
+        name: String,
+        version: &'static str,
+        source: bottlerocket_modeled_types::error::Error,
+    },
+}
@@ -1,10 +1,14 @@
 //! host-containers settings allow users to configure multiple host containers
 use bottlerocket_model_derive::model;
-use bottlerocket_modeled_types::{Identifier, Url, ValidBase64};
-use bottlerocket_settings_sdk::{GenerateResult, SettingsModel};
+use bottlerocket_modeled_types::{Identifier, ImageReference, Url, ValidBase64};
+use bottlerocket_settings_sdk::{
+    migrate_entries, GenerateResult, KnownNames, LinearlyMigrateable, Names, NoMigration,
+    PathSegment, SettingsModel,
+};
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use std::collections::HashMap;
-use std::convert::Infallible;
+
+pub mod error;
 
 #[derive(Clone, Debug, Default, PartialEq)]
 pub struct HostContainersSettingsV1 {
@@ -32,6 +36,12 @@ impl<'de> Deserialize<'de> for HostContainersSettingsV1 {
     }
 }
 
+impl KnownNames for HostContainersSettingsV1 {
+    fn known_names(path: &[PathSegment]) -> Option<Names> {
+        HashMap::<Identifier, HostContainer>::known_names(path)
+    }
+}
+
 #[model(impl_default = true)]
 struct HostContainer {
     source: Url,
@@ -40,11 +50,11 @@ struct HostContainer {
     user_data: ValidBase64,
 }
 
-type Result<T> = std::result::Result<T, Infallible>;
+type Result<T> = std::result::Result<T, error::Error>;
 
 impl SettingsModel for HostContainersSettingsV1 {
     type PartialKind = Self;
-    type ErrorKind = Infallible;
+    type ErrorKind = error::Error;
 
     fn get_version() -> &'static str {
         "v1"
@@ -70,6 +80,139 @@ impl SettingsModel for HostContainersSettingsV1 {
     }
 }
 
+impl LinearlyMigrateable for HostContainersSettingsV1 {
+    type ForwardMigrationTarget = HostContainersSettingsV2;
+    type BackwardMigrationTarget = NoMigration;
+
+    /// Sources must be valid image references to migrate forward.
+    fn migrate_forward(&self) -> Result<Self::ForwardMigrationTarget> {
+        let host_containers = migrate_entries(
+            &self.host_containers,
+            |container| {
+                Ok(HostContainerV2 {
+                    source: container
+                        .source
+                        .as_ref()
+                        .map(|source| ImageReference::try_from(source.as_ref()))
+                        .transpose()?,
+                    enabled: container.enabled,
+                    superpowered: container.superpowered,
+                    user_data: container.user_data.clone(),
+                })
+            },
+            |name| error::MigrationSnafu {
+                name: name.to_string(),
+                version: "v2",
+            },
+        )?;
+        Ok(HostContainersSettingsV2 { host_containers })
+    }
+
+    fn migrate_backward(&self) -> Result<Self::BackwardMigrationTarget> {
+        NoMigration::no_defined_migration()
+    }
+}
+
+/// The second version of the host-containers settings, in which each container's source must be
+/// a valid image reference rather than any URL.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct HostContainersSettingsV2 {
+    pub host_containers: HashMap<Identifier, HostContainerV2>,
+}
+
+impl Serialize for HostContainersSettingsV2 {
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        self.host_containers.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for HostContainersSettingsV2 {
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let host_containers = HashMap::deserialize(deserializer)?;
+        Ok(Self { host_containers })
+    }
+}
+
+impl KnownNames for HostContainersSettingsV2 {
+    fn known_names(path: &[PathSegment]) -> Option<Names> {
+        HashMap::<Identifier, HostContainerV2>::known_names(path)
+    }
+}
+
+#[model(impl_default = true)]
+struct HostContainerV2 {
+    source: ImageReference,
+    enabled: bool,
+    superpowered: bool,
+    user_data: ValidBase64,
+}
+
+impl SettingsModel for HostContainersSettingsV2 {
+    type PartialKind = Self;
+    type ErrorKind = error::Error;
+
+    fn get_version() -> &'static str {
+        "v2"
+    }
+
+    fn set(_current_value: Option<Self>, _target: Self) -> Result<()> {
+        // Set anything that can be parsed as HostContainersSettingsV2.
+        Ok(())
+    }
+
+    fn generate(
+        existing_partial: Option<Self::PartialKind>,
+        _dependent_settings: Option<serde_json::Value>,
+    ) -> Result<GenerateResult<Self::PartialKind, Self>> {
+        Ok(GenerateResult::Complete(
+            existing_partial.unwrap_or_default(),
+        ))
+    }
+
+    fn validate(_value: Self, _validated_settings: Option<serde_json::Value>) -> Result<()> {
+        // HostContainersSettingsV2 is validated during deserialization.
+        Ok(())
+    }
+}
+
+impl LinearlyMigrateable for HostContainersSettingsV2 {
+    type ForwardMigrationTarget = NoMigration;
+    type BackwardMigrationTarget = HostContainersSettingsV1;
+
+    fn migrate_forward(&self) -> Result<Self::ForwardMigrationTarget> {
+        NoMigration::no_defined_migration()
+    }
+
+    fn migrate_backward(&self) -> Result<Self::BackwardMigrationTarget> {
+        let host_containers = migrate_entries(
+            &self.host_containers,
+            |container| {
+                Ok(HostContainer {
+                    source: container
+                        .source
+                        .as_ref()
+                        .map(|source| Url::try_from(source.as_ref()))
+                        .transpose()?,
+                    enabled: container.enabled,
+                    superpowered: container.superpowered,
+                    user_data: container.user_data.clone(),
+                })
+            },
+            |name| error::MigrationSnafu {
+                name: name.to_string(),
+                version: "v1",
+            },
+        )?;
+        Ok(HostContainersSettingsV1 { host_containers })
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -117,4 +260,61 @@ mod test {
             }
         );
     }
+
+    fn host_containers_v1(source: &str) -> HostContainersSettingsV1 {
+        serde_json::from_value(serde_json::json!({
+            "admin": {"source": source, "enabled": true, "superpowered": true}
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_migrate_host_containers() {
+        let source = "public.ecr.aws/bottlerocket/bottlerocket-admin:v0.11.4";
+        let v1 = host_containers_v1(source);
+
+        let v2 = v1.migrate_forward().unwrap();
+        let admin = v2
+            .host_containers
+            .get(&Identifier::try_from("admin").unwrap())
+            .unwrap();
+        assert_eq!(
+            admin.source.as_ref().unwrap().registry(),
+            Some("public.ecr.aws")
+        );
+        assert_eq!(admin.enabled, Some(true));
+
+        assert_eq!(v2.migrate_backward().unwrap(), v1);
+    }
+
+    #[test]
+    fn test_migrate_host_containers_failure() {
+        let error = host_containers_v1("https://example.com/admin")
+            .migrate_forward()
+            .unwrap_err();
+        assert!(
+            error
+                .to_string()
+                .starts_with("Cannot migrate host container 'admin' to v2"),
+            "{}",
+            error
+        );
+    }
+
+    #[test]
+    fn test_serde_host_containers_v2() {
+        let input = serde_json::json!({
+            "foo": {"source": "localhost:5000/example:latest", "enabled": false}
+        });
+        let host_containers: HostContainersSettingsV2 =
+            serde_json::from_value(input.clone()).unwrap();
+        assert_eq!(serde_json::to_value(&host_containers).unwrap(), input);
+
+        assert!(
+            serde_json::from_value::<HostContainersSettingsV2>(serde_json::json!({
+                "foo": {"source": "https://example.com/example"}
+            }))
+            .is_err()
+        );
+    }
 }
@@ -1,13 +1,14 @@
-use bottlerocket_settings_sdk::{BottlerocketSetting, NullMigratorExtensionBuilder};
-use settings_extension_host_containers::HostContainersSettingsV1;
+use bottlerocket_settings_sdk::{BottlerocketSetting, LinearMigratorExtensionBuilder};
+use settings_extension_host_containers::{HostContainersSettingsV1, HostContainersSettingsV2};
 use std::process::ExitCode;
 
 fn main() -> ExitCode {
     env_logger::init();
 
-    match NullMigratorExtensionBuilder::with_name("host-containers")
+    match LinearMigratorExtensionBuilder::with_name("host-containers")
         .with_models(vec![
             BottlerocketSetting::<HostContainersSettingsV1>::model(),
+            BottlerocketSetting::<HostContainersSettingsV2>::model(),
         ])
         .build()
     {
@@ -6,7 +6,7 @@ use bottlerocket_settings_sdk::{GenerateResult, SettingsModel};
 use std::collections::HashMap;
 use std::convert::Infallible;
 
-#[model(impl_default = true)]
+#[model(impl_default = true, accessors = true)]
 struct KernelSettingsV1 {
     lockdown: Lockdown,
     modules: HashMap<KmodKey, KmodSetting>,
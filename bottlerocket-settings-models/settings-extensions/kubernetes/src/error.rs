@@ -0,0 +1,64 @@
+//! The error type returned by the kubernetes settings extension.
+use bottlerocket_settings_sdk::environment::EnvironmentError;
+use bottlerocket_settings_sdk::{FieldErrors, GenerationDependencyError};
+use snafu::Snafu;
+
+/// The error type returned when generating, validating, or rendering kubernetes settings.
+#[derive(Debug, Snafu)]
+#[snafu(visibility(pub(crate)))]
+pub enum Error {
+    #[snafu(display("Generated invalid value for '{}': {}", setting, source))]
+    GeneratedValue {
+        setting: &'static str,
+        source: bottlerocket_modeled_types::error::Error,
+    },
+
+    #[snafu(display("Cannot read the settings kubernetes is generated from: {}", source))]
+    GenerationInputs { source: GenerationDependencyError },
+
+    #[snafu(display("Invalid IP address '{}' in instance metadata '{}'", value, path))]
+    InvalidMetadataIp { path: String, value: String },
+
+    #[snafu(display(
+        "Memory reservation key '{}' must be the number of a NUMA node",
+        numa_node
+    ))]
+    InvalidNumaNode { numa_node: String },
+
+    #[snafu(display(
+        "Taint '{}' for key '{}' must have the form '[value]:effect' with effect NoSchedule, PreferNoSchedule, or NoExecute",
+        value,
+        key
+    ))]
+    InvalidTaint { key: String, value: String },
+
+    #[snafu(display("Invalid kubernetes settings: {}", source))]
+    InvalidSettings { source: FieldErrors },
+
+    #[snafu(display("Failed to read instance metadata '{}': {}", path, source))]
+    Metadata {
+        path: String,
+        source: EnvironmentError,
+    },
+
+    #[snafu(display("Cannot migrate kubernetes settings to {}: {}", version, source))]
+    Migration {
+        version: &'static str,
+        source: bottlerocket_modeled_types::error::Error,
+    },
+
+    #[snafu(display("Instance metadata '{}' is missing", path))]
+    MissingMetadata { path: String },
+
+    #[snafu(display("Failed to render {}: {}", document, source))]
+    Render {
+        document: &'static str,
+        source: serde_json::Error,
+    },
+
+    #[snafu(display(
+        "Instance type '{}' is not in the ENI table; set max-pods for it",
+        instance_type
+    ))]
+    UnknownInstanceType { instance_type: String },
+}
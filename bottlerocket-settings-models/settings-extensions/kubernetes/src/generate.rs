@@ -0,0 +1,201 @@
+//! Generators for kubernetes settings that depend on the environment the node runs in.
+use crate::error::{self, Error};
+use bottlerocket_modeled_types::IpNetwork;
+use bottlerocket_settings_sdk::environment::MetadataProvider;
+use snafu::{OptionExt, ResultExt};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+/// The cluster domain used by Kubernetes unless configured otherwise.
+pub const DEFAULT_CLUSTER_DOMAIN: &str = "cluster.local";
+
+/// The number of ENIs and IPv4 addresses per ENI for each known instance type.
+const ENI_LIMITS: &str = include_str!("eni-limits.txt");
+
+/// The account hosting EKS images in most regions.
+const DEFAULT_ECR_ACCOUNT: &str = "602401143452";
+
+/// The accounts hosting EKS images in regions which don't use the default account.
+const ECR_ACCOUNTS: &[(&str, &str)] = &[
+    ("af-south-1", "877085696533"),
+    ("ap-east-1", "800184023465"),
+    ("ap-south-2", "900889452093"),
+    ("ap-southeast-3", "296578399912"),
+    ("ap-southeast-4", "491585149902"),
+    ("ca-west-1", "761377655185"),
+    ("cn-north-1", "918309763551"),
+    ("cn-northwest-1", "961992271922"),
+    ("eu-central-2", "900612956339"),
+    ("eu-south-1", "590381155156"),
+    ("eu-south-2", "455263428931"),
+    ("il-central-1", "066635153087"),
+    ("me-central-1", "759879836304"),
+    ("me-south-1", "558608220178"),
+    ("us-gov-east-1", "151742754352"),
+    ("us-gov-west-1", "013241004608"),
+];
+
+/// Returns the maximum number of pods for an instance type, based on the number of IP addresses
+/// its network interfaces can hold, or `None` if the instance type isn't in the ENI table.
+///
+/// Each ENI's primary address is reserved for the node, and two more pods are allowed for
+/// host-networked pods like kube-proxy and the VPC CNI plugin.
+pub fn max_pods(instance_type: &str) -> Option<u32> {
+    eni_limits(instance_type).map(|(enis, addresses_per_eni)| {
+        enis.saturating_mul(addresses_per_eni.saturating_sub(1))
+            .saturating_add(2)
+    })
+}
+
+/// Returns the number of ENIs and IPv4 addresses per ENI for an instance type, if it is known.
+fn eni_limits(instance_type: &str) -> Option<(u32, u32)> {
+    ENI_LIMITS
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .find_map(|line| {
+            let mut fields = line.split_whitespace();
+            match (fields.next(), fields.next(), fields.next()) {
+                (Some(name), Some(enis), Some(addresses)) if name == instance_type => {
+                    Some((enis.parse().ok()?, addresses.parse().ok()?))
+                }
+                _ => None,
+            }
+        })
+}
+
+/// Returns the address of the cluster's DNS service, which by convention is the tenth address in
+/// the service CIDR, e.g. `10.100.0.10` for `10.100.0.0/16`.
+pub fn cluster_dns_ip(network: &IpNetwork) -> Option<IpAddr> {
+    /// The offset of the DNS service's address within the service CIDR.
+    const DNS_OFFSET: u8 = 10;
+
+    // The CIDR must have room for the offset.
+    match network.network() {
+        IpAddr::V4(address) if network.prefix_len() <= 28 => Some(IpAddr::V4(Ipv4Addr::from(
+            u32::from(address) + u32::from(DNS_OFFSET),
+        ))),
+        IpAddr::V6(address) if network.prefix_len() <= 124 => Some(IpAddr::V6(Ipv6Addr::from(
+            u128::from(address) + u128::from(DNS_OFFSET),
+        ))),
+        _ => None,
+    }
+}
+
+/// Returns the pause image with the given tag, used for pod sandboxes, hosted in the EKS registry
+/// for `region`.
+pub fn pod_infra_container_image(region: &str, tag: &str) -> String {
+    let account = ECR_ACCOUNTS
+        .iter()
+        .find(|(known, _)| *known == region)
+        .map_or(DEFAULT_ECR_ACCOUNT, |(_, account)| account);
+    let domain = if region.starts_with("cn-") {
+        "amazonaws.com.cn"
+    } else {
+        "amazonaws.com"
+    };
+    format!(
+        "{}.dkr.ecr.{}.{}/eks/pause:{}",
+        account, region, domain, tag
+    )
+}
+
+/// Returns the provider ID the AWS cloud provider uses to identify an instance.
+pub fn provider_id(availability_zone: &str, instance_id: &str) -> String {
+    format!("aws:///{}/{}", availability_zone, instance_id)
+}
+
+/// Reads instance metadata, treating metadata that every instance has as required.
+pub(crate) struct Imds<'a> {
+    provider: &'a dyn MetadataProvider,
+}
+
+impl<'a> Imds<'a> {
+    pub(crate) fn new(provider: &'a dyn MetadataProvider) -> Self {
+        Self { provider }
+    }
+
+    /// Returns the metadata at `path`, if there is any.
+    pub(crate) fn optional(&self, path: &str) -> Result<Option<String>, Error> {
+        self.provider
+            .metadata(path)
+            .context(error::MetadataSnafu { path })
+            .map(|value| value.map(|value| value.trim().to_string()))
+    }
+
+    /// Returns the metadata at `path`, failing if there is none.
+    pub(crate) fn required(&self, path: &str) -> Result<String, Error> {
+        self.optional(path)?
+            .context(error::MissingMetadataSnafu { path })
+    }
+
+    /// Returns the instance's primary private address, preferring IPv4.
+    pub(crate) fn node_ip(&self) -> Result<IpAddr, Error> {
+        // IPv6-only instances don't have a private IPv4 address.
+        let (path, value) = match self.optional("meta-data/local-ipv4")? {
+            Some(value) => ("meta-data/local-ipv4", value),
+            None => ("meta-data/ipv6", self.required("meta-data/ipv6")?),
+        };
+        value
+            .parse()
+            .ok()
+            .context(error::InvalidMetadataIpSnafu { path, value })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_max_pods() {
+        assert_eq!(max_pods("t3.nano"), Some(4));
+        assert_eq!(max_pods("t3.medium"), Some(17));
+        assert_eq!(max_pods("m5.large"), Some(29));
+        assert_eq!(max_pods("m5.4xlarge"), Some(234));
+        assert_eq!(max_pods("m5.24xlarge"), Some(737));
+        assert_eq!(max_pods("m6a.large"), Some(29));
+        assert_eq!(max_pods("c7i.metal-48xl"), Some(737));
+        assert_eq!(max_pods("x9.unknown"), None);
+    }
+
+    #[test]
+    fn test_eni_table_is_well_formed() {
+        for line in ENI_LIMITS
+            .lines()
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        {
+            let instance_type = line.split_whitespace().next().unwrap();
+            assert!(eni_limits(instance_type).is_some(), "bad line: {}", line);
+        }
+    }
+
+    #[test]
+    fn test_cluster_dns_ip() {
+        let dns_ip = |cidr: &str| cluster_dns_ip(&cidr.parse().unwrap());
+        assert_eq!(
+            dns_ip("10.100.0.0/16"),
+            Some("10.100.0.10".parse().unwrap())
+        );
+        assert_eq!(
+            dns_ip("172.20.5.0/16"),
+            Some("172.20.0.10".parse().unwrap())
+        );
+        assert_eq!(
+            dns_ip("fd00:1234::/108"),
+            Some("fd00:1234::a".parse().unwrap())
+        );
+        assert_eq!(dns_ip("10.100.0.0/30"), None);
+    }
+
+    #[test]
+    fn test_pod_infra_container_image() {
+        assert_eq!(
+            pod_infra_container_image("us-west-2", "3.1-eksbuild.1"),
+            "602401143452.dkr.ecr.us-west-2.amazonaws.com/eks/pause:3.1-eksbuild.1"
+        );
+        assert_eq!(
+            pod_infra_container_image("cn-north-1", "3.1-eksbuild.1"),
+            "918309763551.dkr.ecr.cn-north-1.amazonaws.com.cn/eks/pause:3.1-eksbuild.1"
+        );
+    }
+}
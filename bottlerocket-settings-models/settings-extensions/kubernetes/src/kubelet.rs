@@ -0,0 +1,602 @@
+//! Renders the kubelet's configuration files from [`KubernetesSettingsV2`].
+//!
+//! The kubelet reads YAML, of which JSON is a subset, so the documents are rendered as JSON. Map
+//! keys are sorted so that the rendered documents only change when the settings do.
+use crate::error::{self, Error};
+use crate::{KubernetesSettingsV1, KubernetesSettingsV2};
+use bottlerocket_modeled_types::{
+    CpuManagerPolicy, KubernetesDurationValue, KubernetesMemoryManagerPolicy,
+    KubernetesQuantityValue, NonNegativeInteger, SingleLineString, TopologyManagerPolicy,
+    TopologyManagerScope,
+};
+use bottlerocket_settings_sdk::helper::template_helper;
+use serde::Serialize;
+use snafu::{OptionExt, ResultExt};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Display;
+use std::net::IpAddr;
+
+type Result<T> = std::result::Result<T, Error>;
+
+const KUBELET_CONFIG_API_VERSION: &str = "kubelet.config.k8s.io/v1beta1";
+const CREDENTIAL_PROVIDER_CONFIG_API_VERSION: &str = "kubelet.config.k8s.io/v1";
+const CREDENTIAL_PROVIDER_API_VERSION: &str = "credentialprovider.kubelet.k8s.io/v1";
+
+// Defaults for the settings which override the kubelet's authentication, cgroup driver, and
+// certificate paths.
+const CLIENT_CA_FILE: &str = "/etc/kubernetes/pki/ca.crt";
+const SERVER_CERTIFICATE_FILE: &str = "/etc/kubernetes/pki/kubelet-server.crt";
+const SERVER_KEY_FILE: &str = "/etc/kubernetes/pki/kubelet-server.key";
+const AUTHENTICATION_WEBHOOK_CACHE_TTL: &str = "2m0s";
+const AUTHORIZATION_WEBHOOK_CACHE_AUTHORIZED_TTL: &str = "5m0s";
+const AUTHORIZATION_WEBHOOK_CACHE_UNAUTHORIZED_TTL: &str = "30s";
+const CGROUP_DRIVER: &str = "systemd";
+
+/// The effects the kubelet accepts for the taints it registers with.
+const TAINT_EFFECTS: &[&str] = &["NoSchedule", "PreferNoSchedule", "NoExecute"];
+
+/// How long the kubelet caches credentials when a provider doesn't set `cache-duration`.
+const DEFAULT_CREDENTIAL_CACHE_DURATION: &str = "12h";
+
+/// The kubelet's `KubeletConfiguration` document.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KubeletConfiguration<'a> {
+    api_version: &'static str,
+    kind: &'static str,
+    address: &'static str,
+    authentication: Authentication<'a>,
+    authorization: Authorization<'a>,
+    cgroup_driver: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    cluster_domain: Option<&'a str>,
+    #[serde(rename = "clusterDNS", skip_serializing_if = "Vec::is_empty")]
+    cluster_dns: Vec<IpAddr>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    max_pods: Option<u32>,
+    #[serde(rename = "providerID", skip_serializing_if = "Option::is_none")]
+    provider_id: Option<&'a str>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    eviction_hard: BTreeMap<String, String>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    eviction_soft: BTreeMap<String, String>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    eviction_soft_grace_period: BTreeMap<String, String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    eviction_max_pod_grace_period: Option<&'a NonNegativeInteger>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    kube_reserved: BTreeMap<String, &'a KubernetesQuantityValue>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    system_reserved: BTreeMap<String, &'a KubernetesQuantityValue>,
+    #[serde(rename = "cpuCFSQuota", skip_serializing_if = "Option::is_none")]
+    cpu_cfs_quota: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    cpu_manager_policy: Option<&'a CpuManagerPolicy>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    cpu_manager_reconcile_period: Option<&'a KubernetesDurationValue>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    cpu_manager_policy_options: BTreeMap<String, &'static str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    topology_manager_scope: Option<&'a TopologyManagerScope>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    topology_manager_policy: Option<&'a TopologyManagerPolicy>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    memory_manager_policy: Option<&'a KubernetesMemoryManagerPolicy>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    reserved_memory: Vec<ReservedMemory<'a>>,
+    #[serde(rename = "reservedSystemCPUs", skip_serializing_if = "Option::is_none")]
+    reserved_system_cpus: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pod_pids_limit: Option<i64>,
+    #[serde(
+        rename = "imageGCHighThresholdPercent",
+        skip_serializing_if = "Option::is_none"
+    )]
+    image_gc_high_threshold_percent: Option<i32>,
+    #[serde(
+        rename = "imageGCLowThresholdPercent",
+        skip_serializing_if = "Option::is_none"
+    )]
+    image_gc_low_threshold_percent: Option<i32>,
+    #[serde(rename = "registryPullQPS", skip_serializing_if = "Option::is_none")]
+    registry_pull_qps: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    registry_burst: Option<i32>,
+    #[serde(rename = "eventRecordQPS", skip_serializing_if = "Option::is_none")]
+    event_record_qps: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    event_burst: Option<i32>,
+    #[serde(rename = "kubeAPIQPS", skip_serializing_if = "Option::is_none")]
+    kube_api_qps: Option<i32>,
+    #[serde(rename = "kubeAPIBurst", skip_serializing_if = "Option::is_none")]
+    kube_api_burst: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    container_log_max_size: Option<&'a KubernetesQuantityValue>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    container_log_max_files: Option<i32>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    allowed_unsafe_sysctls: Vec<&'a SingleLineString>,
+    #[serde(rename = "serverTLSBootstrap", skip_serializing_if = "Option::is_none")]
+    server_tls_bootstrap: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tls_cert_file: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tls_private_key_file: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    seccomp_default: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    shutdown_grace_period: Option<&'a KubernetesDurationValue>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    shutdown_grace_period_critical_pods: Option<&'a KubernetesDurationValue>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    register_with_taints: Vec<Taint<'a>>,
+}
+
+#[derive(Debug, Serialize)]
+struct Authentication<'a> {
+    anonymous: Enabled,
+    webhook: AuthenticationWebhook<'a>,
+    x509: X509<'a>,
+}
+
+#[derive(Debug, Serialize)]
+struct Enabled {
+    enabled: bool,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct AuthenticationWebhook<'a> {
+    enabled: bool,
+    #[serde(rename = "cacheTTL")]
+    cache_ttl: &'a str,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct X509<'a> {
+    #[serde(rename = "clientCAFile")]
+    client_ca_file: &'a str,
+}
+
+#[derive(Debug, Serialize)]
+struct Authorization<'a> {
+    mode: &'static str,
+    webhook: AuthorizationWebhook<'a>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct AuthorizationWebhook<'a> {
+    #[serde(rename = "cacheAuthorizedTTL")]
+    cache_authorized_ttl: &'a str,
+    #[serde(rename = "cacheUnauthorizedTTL")]
+    cache_unauthorized_ttl: &'a str,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ReservedMemory<'a> {
+    numa_node: u32,
+    limits: BTreeMap<String, &'a KubernetesQuantityValue>,
+}
+
+#[derive(Debug, Serialize)]
+struct Taint<'a> {
+    key: &'a str,
+    #[serde(skip_serializing_if = "str::is_empty")]
+    value: &'a str,
+    effect: &'a str,
+}
+
+/// The kubelet's `CredentialProviderConfig` document.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CredentialProviderConfig<'a> {
+    api_version: &'static str,
+    kind: &'static str,
+    providers: Vec<CredentialProviderEntry<'a>>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct CredentialProviderEntry<'a> {
+    name: &'a str,
+    match_images: &'a [SingleLineString],
+    default_cache_duration: &'a str,
+    api_version: &'static str,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    env: Vec<EnvVar<'a>>,
+}
+
+#[derive(Debug, Serialize)]
+struct EnvVar<'a> {
+    name: &'a str,
+    value: &'a str,
+}
+
+/// Builds the kubelet's `KubeletConfiguration` from the kubernetes settings.
+pub fn kubelet_config(settings: &KubernetesSettingsV2) -> Result<KubeletConfiguration<'_>> {
+    let address = match settings.node_ip {
+        Some(IpAddr::V6(_)) => "::",
+        _ => "0.0.0.0",
+    };
+
+    // The server certificate and key are either given as paths, or written to the default paths
+    // from their contents.
+    let (tls_cert_file, tls_private_key_file) =
+        match (&settings.server_certificate_file, &settings.server_key_file) {
+            (Some(certificate_file), Some(key_file)) => {
+                (Some(certificate_file.as_ref()), Some(key_file.as_ref()))
+            }
+            _ if settings.server_certificate.is_some() && settings.server_key.is_some() => {
+                (Some(SERVER_CERTIFICATE_FILE), Some(SERVER_KEY_FILE))
+            }
+            _ => (None, None),
+        };
+
+    Ok(KubeletConfiguration {
+        api_version: KUBELET_CONFIG_API_VERSION,
+        kind: "KubeletConfiguration",
+        address,
+        authentication: Authentication {
+            anonymous: Enabled {
+                enabled: settings.anonymous_auth.unwrap_or(false),
+            },
+            webhook: AuthenticationWebhook {
+                enabled: true,
+                cache_ttl: or_default(
+                    &settings.authentication_webhook_cache_ttl,
+                    AUTHENTICATION_WEBHOOK_CACHE_TTL,
+                ),
+            },
+            x509: X509 {
+                client_ca_file: or_default(&settings.client_ca_file, CLIENT_CA_FILE),
+            },
+        },
+        authorization: Authorization {
+            mode: "Webhook",
+            webhook: AuthorizationWebhook {
+                cache_authorized_ttl: or_default(
+                    &settings.authorization_webhook_cache_authorized_ttl,
+                    AUTHORIZATION_WEBHOOK_CACHE_AUTHORIZED_TTL,
+                ),
+                cache_unauthorized_ttl: or_default(
+                    &settings.authorization_webhook_cache_unauthorized_ttl,
+                    AUTHORIZATION_WEBHOOK_CACHE_UNAUTHORIZED_TTL,
+                ),
+            },
+        },
+        cgroup_driver: or_default(&settings.cgroup_driver, CGROUP_DRIVER),
+        cluster_domain: settings.cluster_domain.as_deref(),
+        cluster_dns: settings
+            .cluster_dns_ip
+            .iter()
+            .flat_map(|ips| ips.iter().copied())
+            .collect(),
+        max_pods: settings.max_pods,
+        provider_id: settings.provider_id.as_deref(),
+        eviction_hard: sorted(&settings.eviction_hard, ToString::to_string),
+        eviction_soft: sorted(&settings.eviction_soft, ToString::to_string),
+        eviction_soft_grace_period: sorted(
+            &settings.eviction_soft_grace_period,
+            ToString::to_string,
+        ),
+        eviction_max_pod_grace_period: settings.eviction_max_pod_grace_period.as_ref(),
+        kube_reserved: sorted(&settings.kube_reserved, |quantity| quantity),
+        system_reserved: sorted(&settings.system_reserved, |quantity| quantity),
+        cpu_cfs_quota: settings.cpu_cfs_quota_enforced,
+        cpu_manager_policy: settings.cpu_manager_policy.as_ref(),
+        cpu_manager_reconcile_period: settings.cpu_manager_reconcile_period.as_ref(),
+        cpu_manager_policy_options: settings
+            .cpu_manager_policy_options
+            .iter()
+            .flatten()
+            .map(|option| (option.to_string(), "true"))
+            .collect(),
+        topology_manager_scope: settings.topology_manager_scope.as_ref(),
+        topology_manager_policy: settings.topology_manager_policy.as_ref(),
+        memory_manager_policy: settings.memory_manager_policy.as_ref(),
+        reserved_memory: reserved_memory(settings)?,
+        reserved_system_cpus: settings.reserved_cpus.as_deref(),
+        pod_pids_limit: settings.pod_pids_limit,
+        image_gc_high_threshold_percent: settings
+            .image_gc_high_threshold_percent
+            .as_ref()
+            .map(|percent| percent.value()),
+        image_gc_low_threshold_percent: settings
+            .image_gc_low_threshold_percent
+            .as_ref()
+            .map(|percent| percent.value()),
+        registry_pull_qps: settings.registry_qps,
+        registry_burst: settings.registry_burst,
+        event_record_qps: settings.event_qps,
+        event_burst: settings.event_burst,
+        kube_api_qps: settings.kube_api_qps,
+        kube_api_burst: settings.kube_api_burst,
+        container_log_max_size: settings.container_log_max_size.as_ref(),
+        container_log_max_files: settings.container_log_max_files,
+        allowed_unsafe_sysctls: settings.allowed_unsafe_sysctls.iter().flatten().collect(),
+        server_tls_bootstrap: settings.server_tls_bootstrap,
+        tls_cert_file,
+        tls_private_key_file,
+        seccomp_default: settings.seccomp_default,
+        shutdown_grace_period: settings.shutdown_grace_period.as_ref(),
+        shutdown_grace_period_critical_pods: settings
+            .shutdown_grace_period_for_critical_pods
+            .as_ref(),
+        register_with_taints: taints(settings)?,
+    })
+}
+
+/// Builds the kubelet's `CredentialProviderConfig` from the enabled credential providers.
+pub fn credential_provider_config(settings: &KubernetesSettingsV2) -> CredentialProviderConfig<'_> {
+    let mut providers: Vec<_> = settings
+        .credential_providers
+        .iter()
+        .flatten()
+        .filter(|(_, provider)| provider.enabled())
+        .map(|(name, provider)| {
+            let mut env: Vec<_> = provider
+                .environment()
+                .into_iter()
+                .flatten()
+                .map(|(name, value)| EnvVar {
+                    name: name.as_ref(),
+                    value: value.as_ref(),
+                })
+                .collect();
+            env.sort_by_key(|var| var.name);
+
+            CredentialProviderEntry {
+                name: name.as_ref(),
+                match_images: provider.image_patterns(),
+                default_cache_duration: provider
+                    .cache_duration()
+                    .map_or(DEFAULT_CREDENTIAL_CACHE_DURATION, |duration| {
+                        duration.as_ref()
+                    }),
+                api_version: CREDENTIAL_PROVIDER_API_VERSION,
+                env,
+            }
+        })
+        .collect();
+    providers.sort_by_key(|provider| provider.name);
+
+    CredentialProviderConfig {
+        api_version: CREDENTIAL_PROVIDER_CONFIG_API_VERSION,
+        kind: "CredentialProviderConfig",
+        providers,
+    }
+}
+
+/// Renders the kubelet's `KubeletConfiguration` document from the kubernetes settings.
+pub fn render_kubelet_config(settings: &KubernetesSettingsV2) -> Result<String> {
+    serde_json::to_string_pretty(&kubelet_config(settings)?).context(error::RenderSnafu {
+        document: "KubeletConfiguration",
+    })
+}
+
+/// Renders the kubelet's `CredentialProviderConfig` document from the kubernetes settings.
+pub fn render_credential_provider_config(settings: &KubernetesSettingsV2) -> Result<String> {
+    serde_json::to_string_pretty(&credential_provider_config(settings)).context(
+        error::RenderSnafu {
+            document: "CredentialProviderConfig",
+        },
+    )
+}
+
+// V1 settings are rendered as v2 settings with only the settings both versions share, so the
+// kubelet's defaults are used for the settings v2 adds.
+#[template_helper(ident = kubelet_config_v1_helper, vis = "pub(crate)")]
+fn kubelet_config_v1_document(settings: KubernetesSettingsV1) -> Result<String> {
+    render_kubelet_config(&settings.shared_v2())
+}
+
+#[template_helper(ident = credential_provider_config_v1_helper, vis = "pub(crate)")]
+fn credential_provider_config_v1_document(settings: KubernetesSettingsV1) -> Result<String> {
+    render_credential_provider_config(&settings.shared_v2())
+}
+
+#[template_helper(ident = kubelet_config_v2_helper, vis = "pub(crate)")]
+fn kubelet_config_v2_document(settings: KubernetesSettingsV2) -> Result<String> {
+    render_kubelet_config(&settings)
+}
+
+#[template_helper(ident = credential_provider_config_v2_helper, vis = "pub(crate)")]
+fn credential_provider_config_v2_document(settings: KubernetesSettingsV2) -> Result<String> {
+    render_credential_provider_config(&settings)
+}
+
+/// Returns the value of an optional setting, or `default` if it isn't set.
+fn or_default<'a, T: AsRef<str>>(setting: &'a Option<T>, default: &'static str) -> &'a str {
+    setting.as_ref().map_or(default, AsRef::as_ref)
+}
+
+/// Converts a settings map into one with sorted string keys.
+fn sorted<'a, K, V, F, T>(map: &'a Option<HashMap<K, V>>, convert: F) -> BTreeMap<String, T>
+where
+    K: Display,
+    F: Fn(&'a V) -> T,
+{
+    map.iter()
+        .flatten()
+        .map(|(key, value)| (key.to_string(), convert(value)))
+        .collect()
+}
+
+/// Returns the enabled memory reservations, keyed by NUMA node.
+fn reserved_memory(settings: &KubernetesSettingsV2) -> Result<Vec<ReservedMemory<'_>>> {
+    let mut reserved_memory = Vec::new();
+    for (numa_node, reservation) in settings.memory_manager_reserved_memory.iter().flatten() {
+        if !reservation.enabled() {
+            continue;
+        }
+        let numa_node = numa_node
+            .parse()
+            .ok()
+            .context(error::InvalidNumaNodeSnafu {
+                numa_node: numa_node.to_string(),
+            })?;
+        reserved_memory.push(ReservedMemory {
+            numa_node,
+            limits: reservation
+                .limits()
+                .iter()
+                .map(|(key, quantity)| (key.to_string(), quantity))
+                .collect(),
+        });
+    }
+    reserved_memory.sort_by_key(|reservation| reservation.numa_node);
+    Ok(reserved_memory)
+}
+
+/// Returns the taints the node registers with, sorted by key and then value.
+///
+/// Taint values have the form `[value]:effect`; values without an effect the kubelet knows about
+/// are rejected, since the kubelet won't start with them.
+fn taints(settings: &KubernetesSettingsV2) -> Result<Vec<Taint<'_>>> {
+    let mut taints = Vec::new();
+    for (key, values) in settings.node_taints.iter().flatten() {
+        for taint in values {
+            let (value, effect) = taint
+                .split_once(':')
+                .filter(|(_, effect)| TAINT_EFFECTS.contains(effect))
+                .context(error::InvalidTaintSnafu {
+                    key: key.to_string(),
+                    value: taint.to_string(),
+                })?;
+            taints.push(Taint {
+                key: key.as_ref(),
+                value,
+                effect,
+            });
+        }
+    }
+    taints.sort_by_key(|taint| (taint.key, taint.value, taint.effect));
+    Ok(taints)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use serde_json::json;
+
+    const FULL_SETTINGS: &str = include_str!("../tests/data/kubelet/full-settings.json");
+    const MINIMAL_SETTINGS: &str = include_str!("../tests/data/kubelet/minimal-settings.json");
+
+    fn settings(fixture: &str) -> KubernetesSettingsV2 {
+        serde_json::from_str(fixture).unwrap()
+    }
+
+    #[test]
+    fn test_full_kubelet_config() {
+        assert_eq!(
+            render_kubelet_config(&settings(FULL_SETTINGS)).unwrap(),
+            include_str!("../tests/data/kubelet/full-kubelet-config.json").trim_end()
+        );
+    }
+
+    #[test]
+    fn test_minimal_kubelet_config() {
+        assert_eq!(
+            render_kubelet_config(&settings(MINIMAL_SETTINGS)).unwrap(),
+            include_str!("../tests/data/kubelet/minimal-kubelet-config.json").trim_end()
+        );
+    }
+
+    #[test]
+    fn test_credential_provider_config() {
+        assert_eq!(
+            render_credential_provider_config(&settings(FULL_SETTINGS)).unwrap(),
+            include_str!("../tests/data/kubelet/full-credential-provider-config.json").trim_end()
+        );
+        assert_eq!(
+            render_credential_provider_config(&settings(MINIMAL_SETTINGS)).unwrap(),
+            include_str!("../tests/data/kubelet/minimal-credential-provider-config.json")
+                .trim_end()
+        );
+    }
+
+    #[test]
+    fn test_helpers_render_documents() {
+        let settings_json: serde_json::Value = serde_json::from_str(FULL_SETTINGS).unwrap();
+        let settings = settings(FULL_SETTINGS);
+
+        for version_helper in [kubelet_config_v1_helper, kubelet_config_v2_helper] {
+            assert_eq!(
+                version_helper(vec![settings_json.clone()]).unwrap(),
+                json!(render_kubelet_config(&settings).unwrap())
+            );
+        }
+        for version_helper in [
+            credential_provider_config_v1_helper,
+            credential_provider_config_v2_helper,
+        ] {
+            assert_eq!(
+                version_helper(vec![settings_json.clone()]).unwrap(),
+                json!(render_credential_provider_config(&settings).unwrap())
+            );
+        }
+    }
+
+    #[test]
+    fn test_kubelet_config_overrides() {
+        let settings = settings(
+            r#"{
+                "anonymous-auth": true,
+                "authentication-webhook-cache-ttl": "1m",
+                "authorization-webhook-cache-authorized-ttl": "10m",
+                "authorization-webhook-cache-unauthorized-ttl": "1m",
+                "cgroup-driver": "cgroupfs",
+                "client-ca-file": "/etc/pki/ca.crt",
+                "server-certificate-file": "/etc/pki/server.crt",
+                "server-key-file": "/etc/pki/server.key"
+            }"#,
+        );
+        let config = serde_json::to_value(kubelet_config(&settings).unwrap()).unwrap();
+
+        assert_eq!(
+            config.pointer("/authentication").unwrap(),
+            &json!({
+                "anonymous": {"enabled": true},
+                "webhook": {"enabled": true, "cacheTTL": "1m"},
+                "x509": {"clientCAFile": "/etc/pki/ca.crt"},
+            })
+        );
+        assert_eq!(
+            config.pointer("/authorization/webhook").unwrap(),
+            &json!({"cacheAuthorizedTTL": "10m", "cacheUnauthorizedTTL": "1m"})
+        );
+        assert_eq!(config.pointer("/cgroupDriver").unwrap(), &json!("cgroupfs"));
+        assert_eq!(
+            config.pointer("/tlsCertFile").unwrap(),
+            &json!("/etc/pki/server.crt")
+        );
+        assert_eq!(
+            config.pointer("/tlsPrivateKeyFile").unwrap(),
+            &json!("/etc/pki/server.key")
+        );
+    }
+
+    #[test]
+    fn test_invalid_taint() {
+        let settings = settings(r#"{"node-taints": {"dedicated": ["experimental:NoRun"]}}"#);
+        assert!(matches!(
+            kubelet_config(&settings),
+            Err(Error::InvalidTaint { ref key, ref value })
+                if key == "dedicated" && value == "experimental:NoRun"
+        ));
+    }
+
+    #[test]
+    fn test_invalid_numa_node() {
+        let settings = settings(
+            r#"{"memory-manager-reserved-memory": {"first": {"enabled": true, "memory": "1Gi"}}}"#,
+        );
+        assert!(matches!(
+            kubelet_config(&settings),
+            Err(Error::InvalidNumaNode { ref numa_node }) if numa_node == "first"
+        ));
+    }
+}
@@ -1,23 +1,34 @@
 //! Modeled types for creating Kubernetes settings.
 use bottlerocket_model_derive::model;
 use bottlerocket_modeled_types::{
-    CpuManagerPolicy, CredentialProvider, DNSDomain, Identifier, IntegerPercent, KernelCpuSetValue,
-    KubernetesAuthenticationMode, KubernetesBootstrapToken, KubernetesCPUManagerPolicyOption,
-    KubernetesCloudProvider, KubernetesClusterDnsIp, KubernetesClusterName,
-    KubernetesDurationValue, KubernetesEvictionKey, KubernetesHostnameOverrideSource,
-    KubernetesLabelKey, KubernetesLabelValue, KubernetesMemoryManagerPolicy,
-    KubernetesMemoryReservation, KubernetesQuantityValue, KubernetesReservedResourceKey,
-    KubernetesTaintValue, KubernetesThresholdValue, NonNegativeInteger, SingleLineString,
-    TopologyManagerPolicy, TopologyManagerScope, Url, ValidBase64, ValidLinuxHostname,
+    Cidr, CpuManagerPolicy, CredentialProvider, DNSDomain, Identifier, ImageReference,
+    IntegerPercent, KernelCpuSetValue, KubernetesAuthenticationMode, KubernetesBootstrapToken,
+    KubernetesCPUManagerPolicyOption, KubernetesCgroupDriver, KubernetesCloudProvider,
+    KubernetesClusterDnsIp, KubernetesClusterName, KubernetesDurationValue, KubernetesEvictionKey,
+    KubernetesHostnameOverrideSource, KubernetesLabelKey, KubernetesLabelValue,
+    KubernetesMemoryManagerPolicy, KubernetesMemoryReservation, KubernetesQuantityValue,
+    KubernetesReservedResourceKey, KubernetesTaintValue, KubernetesThresholdValue,
+    NonNegativeInteger, SingleLineString, TopologyManagerPolicy, TopologyManagerScope, Url,
+    ValidBase64, ValidLinuxHostname,
 };
-use bottlerocket_settings_sdk::{GenerateResult, SettingsModel};
+use bottlerocket_settings_sdk::{
+    provide_template_helpers, DependentSettings, Environment, FieldChecks, FieldErrors,
+    GenerateResult, GenerationInputs, HelperDef, LinearlyMigrateable, NoMigration, SettingsModel,
+};
+use serde::Deserialize;
+use snafu::{OptionExt, ResultExt};
 
 use self::de::deserialize_node_taints;
+use self::generate::Imds;
 use std::collections::HashMap;
-use std::convert::Infallible;
 use std::net::IpAddr;
 
 mod de;
+pub mod error;
+pub mod generate;
+pub mod kubelet;
+mod static_pod;
+mod validate;
 
 // Kubernetes static pod manifest settings
 #[model]
@@ -26,81 +37,263 @@ pub struct StaticPod {
     manifest: ValidBase64,
 }
 
-#[model(impl_default = true)]
-pub struct KubernetesSettingsV1 {
-    // Settings that must be specified via user data or through API requests.  Not all settings are
-    // useful for all modes. For example, in standalone mode the user does not need to specify any
-    // cluster information, and the bootstrap token is only needed for TLS authentication mode.
-    cluster_name: KubernetesClusterName,
-    cluster_certificate: ValidBase64,
-    api_server: Url,
-    node_labels: HashMap<KubernetesLabelKey, KubernetesLabelValue>,
-    #[serde(
-        default,
-        skip_serializing_if = "Option::is_none",
-        deserialize_with = "deserialize_node_taints"
-    )]
-    node_taints: HashMap<KubernetesLabelKey, Vec<KubernetesTaintValue>>,
-    static_pods: HashMap<Identifier, StaticPod>,
-    authentication_mode: KubernetesAuthenticationMode,
-    bootstrap_token: KubernetesBootstrapToken,
-    standalone_mode: bool,
-    eviction_hard: HashMap<KubernetesEvictionKey, KubernetesThresholdValue>,
-    eviction_soft: HashMap<KubernetesEvictionKey, KubernetesThresholdValue>,
-    eviction_soft_grace_period: HashMap<KubernetesEvictionKey, KubernetesDurationValue>,
-    eviction_max_pod_grace_period: NonNegativeInteger,
-    kube_reserved: HashMap<KubernetesReservedResourceKey, KubernetesQuantityValue>,
-    system_reserved: HashMap<KubernetesReservedResourceKey, KubernetesQuantityValue>,
-    allowed_unsafe_sysctls: Vec<SingleLineString>,
-    server_tls_bootstrap: bool,
-    cloud_provider: KubernetesCloudProvider,
-    registry_qps: i32,
-    registry_burst: i32,
-    event_qps: i32,
-    event_burst: i32,
-    kube_api_qps: i32,
-    kube_api_burst: i32,
-    container_log_max_size: KubernetesQuantityValue,
-    container_log_max_files: i32,
-    cpu_cfs_quota_enforced: bool,
-    cpu_manager_policy: CpuManagerPolicy,
-    cpu_manager_reconcile_period: KubernetesDurationValue,
-    cpu_manager_policy_options: Vec<KubernetesCPUManagerPolicyOption>,
-    topology_manager_scope: TopologyManagerScope,
-    topology_manager_policy: TopologyManagerPolicy,
-    pod_pids_limit: i64,
-    image_gc_high_threshold_percent: IntegerPercent,
-    image_gc_low_threshold_percent: IntegerPercent,
-    provider_id: Url,
-    log_level: u8,
-    credential_providers: HashMap<Identifier, CredentialProvider>,
-    server_certificate: ValidBase64,
-    server_key: ValidBase64,
-    shutdown_grace_period: KubernetesDurationValue,
-    shutdown_grace_period_for_critical_pods: KubernetesDurationValue,
-    memory_manager_reserved_memory: HashMap<Identifier, KubernetesMemoryReservation>,
-    memory_manager_policy: KubernetesMemoryManagerPolicy,
-    reserved_cpus: KernelCpuSetValue,
-
-    // Settings where we generate a value based on the runtime environment.  The user can specify a
-    // value to override the generated one, but typically would not.
-    max_pods: u32,
-    cluster_dns_ip: KubernetesClusterDnsIp,
-    cluster_domain: DNSDomain,
-    node_ip: IpAddr,
-    pod_infra_container_image: SingleLineString,
-    // Generated in `aws-k8s-1.26*` variants only
-    hostname_override: ValidLinuxHostname,
-    hostname_override_source: KubernetesHostnameOverrideSource,
-    // Generated in `k8s-1.25+` variants only
-    seccomp_default: bool,
+/// Defines each version of the kubernetes settings from the fields every version shares, followed
+/// by the fields which differ between versions.
+macro_rules! kubernetes_settings {
+    (
+        shared {
+            $($(#[$($attr:tt)*])* $field:ident: $ty:ty,)*
+        }
+        v1 {
+            $($v1:tt)*
+        }
+        v2 {
+            $($v2:tt)*
+        }
+    ) => {
+        #[model(impl_default = true)]
+        pub struct KubernetesSettingsV1 {
+            $($(#[$($attr)*])* $field: $ty,)*
+            $($v1)*
+        }
+
+        /// The second version of the kubernetes settings, in which `pod-infra-container-image`
+        /// must be a valid image reference rather than any string.  It adds settings which
+        /// override the kubelet's authentication, authorization, cgroup driver, and certificate
+        /// paths, and `service-cidr` and `pause-image-tag`, from which `cluster-dns-ip` and
+        /// `pod-infra-container-image` are generated.  The settings are otherwise the same as
+        /// [`KubernetesSettingsV1`].
+        #[model(impl_default = true)]
+        pub struct KubernetesSettingsV2 {
+            $($(#[$($attr)*])* $field: $ty,)*
+            $($v2)*
+        }
+
+        impl KubernetesSettingsV1 {
+            /// Returns v2 settings with the fields shared with this version, and no others, set.
+            fn shared_v2(&self) -> KubernetesSettingsV2 {
+                KubernetesSettingsV2 {
+                    $($field: self.$field.clone(),)*
+                    ..Default::default()
+                }
+            }
+        }
+
+        impl KubernetesSettingsV2 {
+            /// Returns v1 settings with the fields shared with this version, and no others, set.
+            fn shared_v1(&self) -> KubernetesSettingsV1 {
+                KubernetesSettingsV1 {
+                    $($field: self.$field.clone(),)*
+                    ..Default::default()
+                }
+            }
+        }
+    };
+}
+
+kubernetes_settings! {
+    shared {
+        // Settings that must be specified via user data or through API requests.  Not all settings
+        // are useful for all modes. For example, in standalone mode the user does not need to
+        // specify any cluster information, and the bootstrap token is only needed for TLS
+        // authentication mode.
+        #[immutable]
+        cluster_name: KubernetesClusterName,
+        cluster_certificate: ValidBase64,
+        api_server: Url,
+        node_labels: HashMap<KubernetesLabelKey, KubernetesLabelValue>,
+        #[serde(
+            default,
+            skip_serializing_if = "Option::is_none",
+            deserialize_with = "deserialize_node_taints"
+        )]
+        node_taints: HashMap<KubernetesLabelKey, Vec<KubernetesTaintValue>>,
+        #[validate(nested)]
+        static_pods: HashMap<Identifier, StaticPod>,
+        authentication_mode: KubernetesAuthenticationMode,
+        bootstrap_token: KubernetesBootstrapToken,
+        standalone_mode: bool,
+        eviction_hard: HashMap<KubernetesEvictionKey, KubernetesThresholdValue>,
+        eviction_soft: HashMap<KubernetesEvictionKey, KubernetesThresholdValue>,
+        eviction_soft_grace_period: HashMap<KubernetesEvictionKey, KubernetesDurationValue>,
+        eviction_max_pod_grace_period: NonNegativeInteger,
+        kube_reserved: HashMap<KubernetesReservedResourceKey, KubernetesQuantityValue>,
+        system_reserved: HashMap<KubernetesReservedResourceKey, KubernetesQuantityValue>,
+        allowed_unsafe_sysctls: Vec<SingleLineString>,
+        server_tls_bootstrap: bool,
+        cloud_provider: KubernetesCloudProvider,
+        #[validate(range(min = 0))]
+        registry_qps: i32,
+        #[validate(range(min = 0))]
+        registry_burst: i32,
+        #[validate(range(min = 0))]
+        event_qps: i32,
+        #[validate(range(min = 0))]
+        event_burst: i32,
+        #[validate(range(min = 0))]
+        kube_api_qps: i32,
+        #[validate(range(min = 0))]
+        kube_api_burst: i32,
+        container_log_max_size: KubernetesQuantityValue,
+        #[validate(range(min = 2))]
+        container_log_max_files: i32,
+        cpu_cfs_quota_enforced: bool,
+        cpu_manager_policy: CpuManagerPolicy,
+        cpu_manager_reconcile_period: KubernetesDurationValue,
+        cpu_manager_policy_options: Vec<KubernetesCPUManagerPolicyOption>,
+        topology_manager_scope: TopologyManagerScope,
+        topology_manager_policy: TopologyManagerPolicy,
+        #[validate(range(min = -1))]
+        pod_pids_limit: i64,
+        image_gc_high_threshold_percent: IntegerPercent,
+        image_gc_low_threshold_percent: IntegerPercent,
+        provider_id: Url,
+        log_level: u8,
+        credential_providers: HashMap<Identifier, CredentialProvider>,
+        server_certificate: ValidBase64,
+        server_key: ValidBase64,
+        shutdown_grace_period: KubernetesDurationValue,
+        shutdown_grace_period_for_critical_pods: KubernetesDurationValue,
+        memory_manager_reserved_memory: HashMap<Identifier, KubernetesMemoryReservation>,
+        memory_manager_policy: KubernetesMemoryManagerPolicy,
+        reserved_cpus: KernelCpuSetValue,
+
+        // Settings where we generate a value based on the runtime environment.  The user can
+        // specify a value to override the generated one, but typically would not.
+        max_pods: u32,
+        cluster_dns_ip: KubernetesClusterDnsIp,
+        cluster_domain: DNSDomain,
+        #[immutable]
+        node_ip: IpAddr,
+        // Generated in `aws-k8s-1.26*` variants only
+        hostname_override: ValidLinuxHostname,
+        hostname_override_source: KubernetesHostnameOverrideSource,
+        // Generated in `k8s-1.25+` variants only
+        seccomp_default: bool,
+    }
+    v1 {
+        // Set by the user, or by a variant's defaults; never generated in this version.
+        pod_infra_container_image: SingleLineString,
+    }
+    v2 {
+        // Generated from `pause-image-tag`.
+        pod_infra_container_image: ImageReference,
+
+        // Settings which override how the kubelet authenticates and authorizes requests, manages
+        // cgroups, and finds its certificates.  The kubelet's configuration uses Bottlerocket's
+        // defaults for any that aren't set.
+        anonymous_auth: bool,
+        authentication_webhook_cache_ttl: KubernetesDurationValue,
+        authorization_webhook_cache_authorized_ttl: KubernetesDurationValue,
+        authorization_webhook_cache_unauthorized_ttl: KubernetesDurationValue,
+        cgroup_driver: KubernetesCgroupDriver,
+        client_ca_file: SingleLineString,
+        server_certificate_file: SingleLineString,
+        server_key_file: SingleLineString,
+
+        // Settings which generated values are derived from, typically given in a variant's
+        // defaults.  `cluster-dns-ip` is derived from `service-cidr`, and
+        // `pod-infra-container-image` from `pause-image-tag`; neither is generated without them.
+        service_cidr: Cidr,
+        pause_image_tag: SingleLineString,
+    }
+}
+
+/// The settings that kubernetes generation reads from other settings extensions.
+#[derive(Debug, Deserialize)]
+pub struct KubernetesGenerationInputs {
+    // Settings are generated from instance metadata, in the region the aws settings name.
+    aws: AwsGenerationInputs,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct AwsGenerationInputs {
+    region: Option<String>,
+}
+
+impl GenerationInputs for KubernetesGenerationInputs {
+    fn required_settings() -> &'static [&'static str] {
+        &["aws"]
+    }
 }
 
-type Result<T> = std::result::Result<T, Infallible>;
+impl KubernetesSettingsV2 {
+    /// Generates the settings which come from instance metadata.
+    fn generate_from_imds(&mut self, imds: &Imds<'_>, region: Option<String>) -> Result<()> {
+        if self.node_ip.is_none() {
+            self.node_ip = Some(imds.node_ip()?);
+        }
+
+        if self.cluster_dns_ip.is_none() {
+            self.cluster_dns_ip = self
+                .service_cidr
+                .as_ref()
+                .and_then(|cidr| generate::cluster_dns_ip(&cidr.network()))
+                .map(KubernetesClusterDnsIp::Scalar);
+        }
+
+        if self.max_pods.is_none() {
+            let instance_type = imds.required("meta-data/instance-type")?;
+            let max_pods = generate::max_pods(&instance_type)
+                .context(error::UnknownInstanceTypeSnafu { instance_type })?;
+            self.max_pods = Some(max_pods);
+        }
+
+        if self.provider_id.is_none() {
+            let provider_id = generate::provider_id(
+                &imds.required("meta-data/placement/availability-zone")?,
+                &imds.required("meta-data/instance-id")?,
+            );
+            self.provider_id = Some(Url::try_from(provider_id.as_str()).context(
+                error::GeneratedValueSnafu {
+                    setting: "provider-id",
+                },
+            )?);
+        }
+
+        if self.hostname_override.is_none() {
+            let path = match self.hostname_override_source {
+                Some(KubernetesHostnameOverrideSource::PrivateDNSName) => {
+                    Some("meta-data/local-hostname")
+                }
+                Some(KubernetesHostnameOverrideSource::InstanceID) => Some("meta-data/instance-id"),
+                None => None,
+            };
+            if let Some(path) = path {
+                let hostname = imds.required(path)?;
+                // Instances in VPCs with custom domains can have several names; use the first.
+                let hostname = hostname.split_whitespace().next().unwrap_or_default();
+                self.hostname_override = Some(ValidLinuxHostname::try_from(hostname).context(
+                    error::GeneratedValueSnafu {
+                        setting: "hostname-override",
+                    },
+                )?);
+            }
+        }
+
+        if let (None, Some(tag)) = (&self.pod_infra_container_image, &self.pause_image_tag) {
+            let region = match region {
+                Some(region) => region,
+                None => imds.required("meta-data/placement/region")?,
+            };
+            let image = generate::pod_infra_container_image(&region, tag);
+            self.pod_infra_container_image = Some(
+                ImageReference::try_from(image.as_str()).context(error::GeneratedValueSnafu {
+                    setting: "pod-infra-container-image",
+                })?,
+            );
+        }
+
+        Ok(())
+    }
+}
+
+type Result<T> = std::result::Result<T, error::Error>;
 
 impl SettingsModel for KubernetesSettingsV1 {
     type PartialKind = Self;
-    type ErrorKind = Infallible;
+    type ErrorKind = error::Error;
 
     fn get_version() -> &'static str {
         "v1"
@@ -113,27 +306,225 @@ impl SettingsModel for KubernetesSettingsV1 {
 
     fn generate(
         existing_partial: Option<Self::PartialKind>,
-        _dependent_settings: Option<serde_json::Value>,
+        dependent_settings: Option<serde_json::Value>,
+    ) -> Result<GenerateResult<Self::PartialKind, Self>> {
+        Self::generate_with_environment(
+            existing_partial,
+            dependent_settings,
+            &Environment::default(),
+        )
+    }
+
+    fn generate_with_environment(
+        existing_partial: Option<Self::PartialKind>,
+        dependent_settings: Option<serde_json::Value>,
+        environment: &Environment,
     ) -> Result<GenerateResult<Self::PartialKind, Self>> {
-        // TODO this should eventually replace `pluto`
-        Ok(GenerateResult::Complete(
-            existing_partial.unwrap_or_default(),
-        ))
+        // This version has no `service-cidr` or `pause-image-tag`, so only the settings it shares
+        // with v2 are generated, and its own `pod-infra-container-image` is kept as it was given.
+        let existing = existing_partial.unwrap_or_default();
+        let with_image = |settings: KubernetesSettingsV2| KubernetesSettingsV1 {
+            pod_infra_container_image: existing.pod_infra_container_image.clone(),
+            ..settings.shared_v1()
+        };
+        Ok(
+            match KubernetesSettingsV2::generate_with_environment(
+                Some(existing.shared_v2()),
+                dependent_settings,
+                environment,
+            )? {
+                GenerateResult::Complete(settings) => {
+                    GenerateResult::Complete(with_image(settings))
+                }
+                GenerateResult::NeedsData(partial) => {
+                    GenerateResult::NeedsData(partial.map(with_image))
+                }
+            },
+        )
+    }
+
+    fn validate(value: Self, _validated_settings: Option<serde_json::Value>) -> Result<()> {
+        let errors = validate::cross_field_errors(&value);
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(FieldErrors::from(errors)).context(error::InvalidSettingsSnafu)
+        }
+    }
+
+    fn field_checks() -> FieldChecks<Self> {
+        FieldChecks::declared()
+    }
+
+    fn template_helpers() -> Result<HashMap<String, Box<dyn HelperDef>>> {
+        use kubelet::{credential_provider_config_v1_helper, kubelet_config_v1_helper};
+
+        Ok(provide_template_helpers! {
+            "kubelet_config" => kubelet_config_v1_helper,
+            "credential_provider_config" => credential_provider_config_v1_helper,
+        })
+    }
+}
+
+impl LinearlyMigrateable for KubernetesSettingsV1 {
+    type ForwardMigrationTarget = KubernetesSettingsV2;
+    type BackwardMigrationTarget = NoMigration;
+
+    /// `pod-infra-container-image` must be a valid image reference to migrate forward.
+    fn migrate_forward(&self) -> Result<Self::ForwardMigrationTarget> {
+        Ok(KubernetesSettingsV2 {
+            pod_infra_container_image: self
+                .pod_infra_container_image
+                .as_ref()
+                .map(|image| ImageReference::try_from(image.as_ref()))
+                .transpose()
+                .context(error::MigrationSnafu { version: "v2" })?,
+            ..self.shared_v2()
+        })
     }
 
-    fn validate(_value: Self, _validated_settings: Option<serde_json::Value>) -> Result<()> {
+    fn migrate_backward(&self) -> Result<Self::BackwardMigrationTarget> {
+        NoMigration::no_defined_migration()
+    }
+}
+
+impl SettingsModel for KubernetesSettingsV2 {
+    type PartialKind = Self;
+    type ErrorKind = error::Error;
+
+    fn get_version() -> &'static str {
+        "v2"
+    }
+
+    fn set(_current_value: Option<Self>, _target: Self) -> Result<()> {
+        // allow anything that parses as KubernetesSettingsV2
         Ok(())
     }
+
+    fn generate(
+        existing_partial: Option<Self::PartialKind>,
+        dependent_settings: Option<serde_json::Value>,
+    ) -> Result<GenerateResult<Self::PartialKind, Self>> {
+        Self::generate_with_environment(
+            existing_partial,
+            dependent_settings,
+            &Environment::default(),
+        )
+    }
+
+    fn generate_with_environment(
+        existing_partial: Option<Self::PartialKind>,
+        dependent_settings: Option<serde_json::Value>,
+        environment: &Environment,
+    ) -> Result<GenerateResult<Self::PartialKind, Self>> {
+        let inputs: KubernetesGenerationInputs = DependentSettings::new(dependent_settings)
+            .get()
+            .context(error::GenerationInputsSnafu)?;
+        let mut settings = existing_partial.unwrap_or_default();
+
+        if settings.cluster_domain.is_none() {
+            settings.cluster_domain = Some(
+                DNSDomain::try_from(generate::DEFAULT_CLUSTER_DOMAIN).context(
+                    error::GeneratedValueSnafu {
+                        setting: "cluster-domain",
+                    },
+                )?,
+            );
+        }
+
+        settings.generate_from_imds(&Imds::new(environment.metadata()), inputs.aws.region)?;
+
+        Ok(GenerateResult::Complete(settings))
+    }
+
+    fn validate(value: Self, validated_settings: Option<serde_json::Value>) -> Result<()> {
+        KubernetesSettingsV1::validate(value.migrate_backward()?, validated_settings)
+    }
+
+    fn field_checks() -> FieldChecks<Self> {
+        FieldChecks::declared()
+    }
+
+    fn template_helpers() -> Result<HashMap<String, Box<dyn HelperDef>>> {
+        use kubelet::{credential_provider_config_v2_helper, kubelet_config_v2_helper};
+
+        Ok(provide_template_helpers! {
+            "kubelet_config" => kubelet_config_v2_helper,
+            "credential_provider_config" => credential_provider_config_v2_helper,
+        })
+    }
+}
+
+impl LinearlyMigrateable for KubernetesSettingsV2 {
+    type ForwardMigrationTarget = NoMigration;
+    type BackwardMigrationTarget = KubernetesSettingsV1;
+
+    fn migrate_forward(&self) -> Result<Self::ForwardMigrationTarget> {
+        NoMigration::no_defined_migration()
+    }
+
+    fn migrate_backward(&self) -> Result<Self::BackwardMigrationTarget> {
+        Ok(KubernetesSettingsV1 {
+            pod_infra_container_image: self
+                .pod_infra_container_image
+                .as_ref()
+                .map(|image| SingleLineString::try_from(image.as_ref()))
+                .transpose()
+                .context(error::MigrationSnafu { version: "v1" })?,
+            ..self.shared_v1()
+        })
+    }
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
-    use bottlerocket_modeled_types::KubernetesHostnameOverrideSource;
+    use bottlerocket_settings_sdk::environment::stand_in::StaticMetadata;
+    use bottlerocket_settings_sdk::environment::{ImdsClient, MetadataStub};
+    use bottlerocket_settings_sdk::GenerationDependencyError;
+    use bottlerocket_settings_sdk::ValidateFields;
+    use serde_json::json;
+
+    fn aws_metadata() -> StaticMetadata {
+        StaticMetadata::new([
+            ("meta-data/local-ipv4", "10.0.1.5"),
+            ("meta-data/instance-type", "m5.large"),
+            ("meta-data/instance-id", "i-0123456789abcdef0"),
+            ("meta-data/placement/availability-zone", "us-west-2a"),
+            ("meta-data/placement/region", "us-west-2"),
+            (
+                "meta-data/local-hostname",
+                "ip-10-0-1-5.us-west-2.compute.internal",
+            ),
+        ])
+    }
+
+    fn generate_complete(
+        existing_partial: Option<KubernetesSettingsV2>,
+        dependent_settings: serde_json::Value,
+        environment: &Environment,
+    ) -> KubernetesSettingsV2 {
+        match KubernetesSettingsV2::generate_with_environment(
+            existing_partial,
+            Some(dependent_settings),
+            environment,
+        )
+        .unwrap()
+        {
+            GenerateResult::Complete(settings) => settings,
+            GenerateResult::NeedsData(_) => panic!("kubernetes generation is always complete"),
+        }
+    }
 
     #[test]
     fn test_generate_kubernetes() {
-        let generated = KubernetesSettingsV1::generate(None, None).unwrap();
+        let environment = Environment::in_memory().with_metadata(aws_metadata());
+        let generated = KubernetesSettingsV1::generate_with_environment(
+            None,
+            Some(json!({"aws": {"region": "us-west-2"}})),
+            &environment,
+        )
+        .unwrap();
 
         assert_eq!(
             generated,
@@ -173,7 +564,7 @@ mod test {
                 pod_pids_limit: None,
                 image_gc_high_threshold_percent: None,
                 image_gc_low_threshold_percent: None,
-                provider_id: None,
+                provider_id: Some(Url::try_from("aws:///us-west-2a/i-0123456789abcdef0").unwrap()),
                 log_level: None,
                 credential_providers: None,
                 server_certificate: None,
@@ -183,10 +574,10 @@ mod test {
                 memory_manager_reserved_memory: None,
                 memory_manager_policy: None,
                 reserved_cpus: None,
-                max_pods: None,
+                max_pods: Some(29),
                 cluster_dns_ip: None,
-                cluster_domain: None,
-                node_ip: None,
+                cluster_domain: Some(DNSDomain::try_from("cluster.local").unwrap()),
+                node_ip: Some("10.0.1.5".parse().unwrap()),
                 pod_infra_container_image: None,
                 hostname_override: None,
                 hostname_override_source: None,
@@ -195,6 +586,184 @@ mod test {
         );
     }
 
+    #[test]
+    fn test_generate_kubernetes_aws() {
+        let environment = Environment::in_memory().with_metadata(aws_metadata());
+        let partial: KubernetesSettingsV2 = serde_json::from_value(json!({
+            "hostname-override-source": "private-dns-name",
+            "service-cidr": "172.20.0.0/16",
+            "pause-image-tag": "3.1-eksbuild.1",
+        }))
+        .unwrap();
+        let generated = generate_complete(
+            Some(partial),
+            json!({"aws": {"region": "us-west-2"}}),
+            &environment,
+        );
+
+        assert_eq!(generated.node_ip, Some("10.0.1.5".parse().unwrap()));
+        assert_eq!(generated.max_pods, Some(29));
+        assert_eq!(
+            generated.cluster_dns_ip,
+            Some(KubernetesClusterDnsIp::Scalar(
+                "172.20.0.10".parse().unwrap()
+            ))
+        );
+        assert_eq!(
+            generated.provider_id.unwrap().as_ref(),
+            "aws:///us-west-2a/i-0123456789abcdef0"
+        );
+        assert_eq!(
+            generated.hostname_override.unwrap().as_ref(),
+            "ip-10-0-1-5.us-west-2.compute.internal"
+        );
+        assert_eq!(
+            generated.pod_infra_container_image.unwrap().as_ref(),
+            "602401143452.dkr.ecr.us-west-2.amazonaws.com/eks/pause:3.1-eksbuild.1"
+        );
+        assert_eq!(generated.cluster_domain.unwrap().as_ref(), "cluster.local");
+    }
+
+    #[test]
+    fn test_generate_kubernetes_keeps_user_values() {
+        let environment = Environment::in_memory().with_metadata(aws_metadata());
+        let partial: KubernetesSettingsV2 = serde_json::from_value(json!({
+            "max-pods": 58,
+            "cluster-dns-ip": "10.100.0.10",
+            "cluster-domain": "example.local",
+            "hostname-override": "my-node",
+            "hostname-override-source": "instance-id",
+            "service-cidr": "172.20.0.0/16",
+            "pause-image-tag": "3.1-eksbuild.1",
+        }))
+        .unwrap();
+        let generated = generate_complete(Some(partial), json!({"aws": {}}), &environment);
+
+        assert_eq!(generated.max_pods, Some(58));
+        assert_eq!(
+            generated.cluster_dns_ip,
+            Some(KubernetesClusterDnsIp::Scalar(
+                "10.100.0.10".parse().unwrap()
+            ))
+        );
+        assert_eq!(generated.cluster_domain.unwrap().as_ref(), "example.local");
+        assert_eq!(generated.hostname_override.unwrap().as_ref(), "my-node");
+        // The region comes from instance metadata when the aws settings don't have one.
+        assert_eq!(
+            generated.pod_infra_container_image.unwrap().as_ref(),
+            "602401143452.dkr.ecr.us-west-2.amazonaws.com/eks/pause:3.1-eksbuild.1"
+        );
+    }
+
+    #[test]
+    fn test_generate_kubernetes_from_imds() {
+        let stub = MetadataStub::start([
+            ("meta-data/local-ipv4", "192.168.1.5"),
+            ("meta-data/instance-type", "t3.medium"),
+            ("meta-data/instance-id", "i-0123456789abcdef0"),
+            ("meta-data/placement/availability-zone", "cn-north-1a"),
+            ("meta-data/placement/region", "cn-north-1"),
+        ])
+        .unwrap();
+        let environment = Environment::in_memory().with_metadata(ImdsClient::new(stub.endpoint()));
+        let partial: KubernetesSettingsV2 = serde_json::from_value(json!({
+            "service-cidr": "10.100.0.0/16",
+            "pause-image-tag": "3.1-eksbuild.1",
+        }))
+        .unwrap();
+        let generated = generate_complete(Some(partial), json!({"aws": {}}), &environment);
+
+        assert_eq!(generated.max_pods, Some(17));
+        assert_eq!(
+            generated.cluster_dns_ip,
+            Some(KubernetesClusterDnsIp::Scalar(
+                "10.100.0.10".parse().unwrap()
+            ))
+        );
+        assert_eq!(
+            generated.pod_infra_container_image.unwrap().as_ref(),
+            "918309763551.dkr.ecr.cn-north-1.amazonaws.com.cn/eks/pause:3.1-eksbuild.1"
+        );
+        assert!(generated.hostname_override.is_none());
+    }
+
+    #[test]
+    fn test_generate_kubernetes_without_derived_settings() {
+        let environment = Environment::in_memory().with_metadata(StaticMetadata::new([
+            ("meta-data/local-ipv4", "10.0.1.5"),
+            ("meta-data/instance-type", "m6a.large"),
+            ("meta-data/instance-id", "i-0123456789abcdef0"),
+            ("meta-data/placement/availability-zone", "us-west-2a"),
+        ]));
+        let generated = generate_complete(None, json!({"aws": {}}), &environment);
+
+        // Without a service CIDR or pause image tag, these are left for the user to set.
+        assert!(generated.cluster_dns_ip.is_none());
+        assert!(generated.pod_infra_container_image.is_none());
+        assert_eq!(generated.max_pods, Some(29));
+    }
+
+    #[test]
+    fn test_generate_kubernetes_unknown_instance_type() {
+        let environment = Environment::in_memory().with_metadata(StaticMetadata::new([
+            ("meta-data/local-ipv4", "10.0.1.5"),
+            ("meta-data/instance-type", "x9.unknown"),
+            ("meta-data/instance-id", "i-0123456789abcdef0"),
+            ("meta-data/placement/availability-zone", "us-west-2a"),
+        ]));
+        let err = KubernetesSettingsV2::generate_with_environment(
+            None,
+            Some(json!({"aws": {}})),
+            &environment,
+        )
+        .unwrap_err();
+        assert!(matches!(
+            err,
+            error::Error::UnknownInstanceType { ref instance_type } if instance_type == "x9.unknown"
+        ));
+
+        // Users can set max-pods for instance types the table doesn't know.
+        let partial: KubernetesSettingsV2 =
+            serde_json::from_value(json!({"max-pods": 110})).unwrap();
+        let generated = generate_complete(Some(partial), json!({"aws": {}}), &environment);
+        assert_eq!(generated.max_pods, Some(110));
+    }
+
+    #[test]
+    fn test_generate_kubernetes_missing_metadata() {
+        let environment = Environment::in_memory()
+            .with_metadata(StaticMetadata::new([("meta-data/local-ipv4", "10.0.1.5")]));
+        let err = KubernetesSettingsV1::generate_with_environment(
+            None,
+            Some(json!({"aws": {}})),
+            &environment,
+        )
+        .unwrap_err();
+
+        assert!(matches!(
+            err,
+            error::Error::MissingMetadata { ref path } if path == "meta-data/instance-type"
+        ));
+    }
+
+    #[test]
+    fn test_generate_kubernetes_needs_aws() {
+        let environment = Environment::in_memory().with_metadata(aws_metadata());
+        let err = KubernetesSettingsV1::generate_with_environment(
+            None,
+            Some(json!({"network": {}})),
+            &environment,
+        )
+        .unwrap_err();
+
+        assert!(matches!(
+            err,
+            error::Error::GenerationInputs {
+                source: GenerationDependencyError::MissingSettings { ref names }
+            } if names == &["aws"]
+        ));
+    }
+
     #[test]
     fn test_serde_kubernetes() {
         let test_json = r#"{
@@ -215,4 +784,127 @@ mod test {
             }
         );
     }
+
+    #[test]
+    fn test_validate_fields_kubernetes() {
+        let kubernetes = KubernetesSettingsV1 {
+            registry_qps: Some(-1),
+            container_log_max_files: Some(1),
+            pod_pids_limit: Some(-1),
+            ..Default::default()
+        };
+
+        let err = KubernetesSettingsV1::field_checks()
+            .check_fields(&kubernetes)
+            .unwrap_err();
+        let paths: Vec<String> = err.errors().iter().map(|e| e.path().to_string()).collect();
+        assert_eq!(paths, vec!["registry-qps", "container-log-max-files"]);
+    }
+
+    #[test]
+    fn test_immutable_fields_kubernetes() {
+        assert_eq!(
+            KubernetesSettingsV1::immutable_fields(),
+            &["cluster-name", "node-ip"]
+        );
+    }
+
+    #[test]
+    fn test_migrate_kubernetes() {
+        let v1: KubernetesSettingsV1 = serde_json::from_value(json!({
+            "cluster-name": "cluster",
+            "pod-infra-container-image": "public.ecr.aws/eks-distro/kubernetes/pause:3.9",
+        }))
+        .unwrap();
+
+        let v2 = v1.migrate_forward().unwrap();
+        let image = v2.pod_infra_container_image.as_ref().unwrap();
+        assert_eq!(image.registry(), Some("public.ecr.aws"));
+        assert_eq!(image.tag(), Some("3.9"));
+        assert_eq!(v2.cluster_name, v1.cluster_name);
+
+        assert_eq!(v2.migrate_backward().unwrap(), v1);
+    }
+
+    #[test]
+    fn test_migrate_kubernetes_failure() {
+        let v1: KubernetesSettingsV1 = serde_json::from_value(json!({
+            "pod-infra-container-image": "https://example.com/pause",
+        }))
+        .unwrap();
+        assert!(matches!(
+            v1.migrate_forward(),
+            Err(error::Error::Migration { version: "v2", .. })
+        ));
+
+        assert!(serde_json::from_value::<KubernetesSettingsV2>(json!({
+            "pod-infra-container-image": "https://example.com/pause",
+        }))
+        .is_err());
+    }
+
+    #[test]
+    fn test_generate_kubernetes_v1() {
+        let environment = Environment::in_memory().with_metadata(aws_metadata());
+        let partial: KubernetesSettingsV1 = serde_json::from_value(json!({
+            "pod-infra-container-image": "localhost/pause",
+        }))
+        .unwrap();
+        let generated = KubernetesSettingsV1::generate_with_environment(
+            Some(partial),
+            Some(json!({"aws": {"region": "us-west-2"}})),
+            &environment,
+        )
+        .unwrap();
+
+        let GenerateResult::Complete(generated) = generated else {
+            panic!("kubernetes generation is always complete");
+        };
+        // The image is kept as given, and nothing is derived from settings v1 doesn't have.
+        assert_eq!(
+            generated.pod_infra_container_image.unwrap().as_ref(),
+            "localhost/pause"
+        );
+        assert!(generated.cluster_dns_ip.is_none());
+        assert_eq!(generated.node_ip, Some("10.0.1.5".parse().unwrap()));
+        assert_eq!(generated.max_pods, Some(29));
+    }
+
+    #[test]
+    fn test_migrate_kubernetes_v2_settings() {
+        let v2: KubernetesSettingsV2 = serde_json::from_value(json!({
+            "cluster-name": "cluster",
+            "cgroup-driver": "cgroupfs",
+            "service-cidr": "172.20.0.0/16",
+            "pause-image-tag": "3.1-eksbuild.1",
+        }))
+        .unwrap();
+
+        // The settings only v2 has are dropped when migrating backward, and unset when migrating
+        // forward.
+        let v1 = v2.migrate_backward().unwrap();
+        assert_eq!(
+            serde_json::to_value(&v1).unwrap(),
+            json!({"cluster-name": "cluster"})
+        );
+        assert_eq!(
+            v1.migrate_forward().unwrap(),
+            KubernetesSettingsV2 {
+                cluster_name: v2.cluster_name.clone(),
+                ..Default::default()
+            }
+        );
+        for v2_only in [
+            "service-cidr",
+            "pause-image-tag",
+            "anonymous-auth",
+            "cgroup-driver",
+        ] {
+            assert!(
+                serde_json::from_value::<KubernetesSettingsV1>(json!({ v2_only: null })).is_err(),
+                "{} isn't a v1 setting",
+                v2_only
+            );
+        }
+    }
 }
@@ -1,12 +1,15 @@
-use bottlerocket_settings_sdk::{BottlerocketSetting, NullMigratorExtensionBuilder};
-use settings_extension_kubernetes::KubernetesSettingsV1;
+use bottlerocket_settings_sdk::{BottlerocketSetting, LinearMigratorExtensionBuilder};
+use settings_extension_kubernetes::{KubernetesSettingsV1, KubernetesSettingsV2};
 use std::process::ExitCode;
 
 fn main() -> ExitCode {
     env_logger::init();
 
-    match NullMigratorExtensionBuilder::with_name("kubernetes")
-        .with_models(vec![BottlerocketSetting::<KubernetesSettingsV1>::model()])
+    match LinearMigratorExtensionBuilder::with_name("kubernetes")
+        .with_models(vec![
+            BottlerocketSetting::<KubernetesSettingsV1>::model(),
+            BottlerocketSetting::<KubernetesSettingsV2>::model(),
+        ])
         .build()
     {
         Ok(extension) => extension.run(),
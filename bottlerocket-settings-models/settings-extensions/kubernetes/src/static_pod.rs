@@ -0,0 +1,213 @@
+//! Checks that static pod manifests describe a Kubernetes `Pod`.
+use crate::StaticPod;
+use base64::Engine;
+use bottlerocket_modeled_types::ValidBase64;
+use bottlerocket_settings_sdk::{FieldError, FieldPath, ValidateFields};
+use serde_json::Value;
+
+impl ValidateFields for StaticPod {
+    fn collect_field_errors(&self, path: &FieldPath, errors: &mut Vec<FieldError>) {
+        // Disabled static pods aren't written out, so their manifests aren't checked.
+        if self.enabled != Some(true) {
+            return;
+        }
+        if let Some(manifest) = &self.manifest {
+            if let Err(message) = check_manifest(manifest) {
+                errors.push(FieldError::new(path.join("manifest"), message));
+            }
+        }
+    }
+}
+
+/// Decodes a static pod manifest and checks that it's a well-formed `Pod` object, returning a
+/// description of the first problem found.
+fn check_manifest(manifest: &ValidBase64) -> Result<(), String> {
+    let decoded = base64::engine::general_purpose::STANDARD
+        .decode(manifest.as_bytes())
+        .map_err(|e| format!("is not valid base64: {}", e))?;
+    let document = String::from_utf8(decoded).map_err(|_| "is not valid UTF-8".to_string())?;
+
+    // JSON manifests are also valid YAML.  Merge keys are applied like the kubelet applies them.
+    let invalid = |e: serde_yaml::Error| format!("is not a valid YAML or JSON document: {}", e);
+    let mut pod: serde_yaml::Value = serde_yaml::from_str(&document).map_err(invalid)?;
+    pod.apply_merge().map_err(invalid)?;
+    let pod: Value = serde_yaml::from_value(pod).map_err(invalid)?;
+    check_pod(&pod).map_err(|problem| format!("is not a valid Pod: {}", problem))
+}
+
+/// Checks the fields that every `Pod` must have.
+fn check_pod(pod: &Value) -> Result<(), String> {
+    if !pod.is_object() {
+        return Err("expected an object".to_string());
+    }
+
+    match pod.get("apiVersion") {
+        Some(Value::String(version)) if version == "v1" => {}
+        Some(other) => return Err(format!("'apiVersion' must be 'v1', not {}", other)),
+        None => return Err("'apiVersion' is required".to_string()),
+    }
+    match pod.get("kind") {
+        Some(Value::String(kind)) if kind == "Pod" => {}
+        Some(other) => return Err(format!("'kind' must be 'Pod', not {}", other)),
+        None => return Err("'kind' is required".to_string()),
+    }
+    required_string(pod, &["metadata", "name"])?;
+
+    let containers = match pod.pointer("/spec/containers") {
+        Some(Value::Array(containers)) if !containers.is_empty() => containers,
+        Some(Value::Array(_)) | None => {
+            return Err("'spec.containers' must list at least one container".to_string())
+        }
+        Some(_) => return Err("'spec.containers' must be a list".to_string()),
+    };
+    for (index, container) in containers.iter().enumerate() {
+        let index = index.to_string();
+        for field in ["name", "image"] {
+            required_string(container, &[field])
+                .map_err(|_| format!("'spec.containers.{}.{}' is required", index, field))?;
+        }
+    }
+    Ok(())
+}
+
+/// Checks that the value at `path` is a non-empty string.
+fn required_string(value: &Value, path: &[&str]) -> Result<(), String> {
+    let found = path
+        .iter()
+        .try_fold(value, |value, key| value.get(key))
+        .and_then(Value::as_str);
+    match found {
+        Some(found) if !found.is_empty() => Ok(()),
+        _ => Err(format!("'{}' is required", path.join("."))),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::KubernetesSettingsV1;
+    use serde_json::json;
+
+    fn encode(manifest: &str) -> String {
+        base64::engine::general_purpose::STANDARD.encode(manifest)
+    }
+
+    fn check(manifest: &str) -> Result<(), String> {
+        check_manifest(&ValidBase64::try_from(encode(manifest).as_str()).unwrap())
+    }
+
+    const POD_YAML: &str = r#"
+apiVersion: v1
+kind: Pod
+metadata:
+  name: static-web
+spec:
+  containers:
+    - name: web
+      image: nginx
+      ports:
+        - name: web
+          containerPort: 80
+"#;
+
+    #[test]
+    fn test_valid_manifests() {
+        check(POD_YAML).unwrap();
+        check(
+            r#"{
+                "apiVersion": "v1",
+                "kind": "Pod",
+                "metadata": {"name": "static-web"},
+                "spec": {"containers": [{"name": "web", "image": "nginx"}]}
+            }"#,
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_valid_yaml_features() {
+        // Multi-line flow collections, block scalars, anchors, and quoted keys are all valid YAML.
+        check(
+            r#"
+# A static pod with comments.
+apiVersion: "v1"
+kind: Pod
+metadata: {
+  name: static-web,
+  labels: {app: web}
+}
+spec:
+  containers:
+    - &web
+      name: web
+      image: nginx
+      command: [
+        "sh", "-c"
+      ]
+      args:
+        - |
+          echo starting
+          exec nginx -g 'daemon off;'
+    - <<: *web
+      "name": sidecar
+"#,
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_invalid_manifests() {
+        for (manifest, expected) in [
+            (
+                "kind: Pod\nmetadata: {name: a}",
+                "is not a valid Pod: 'apiVersion' is required",
+            ),
+            (
+                "apiVersion: apps/v1\nkind: Deployment",
+                "is not a valid Pod: 'apiVersion' must be 'v1', not \"apps/v1\"",
+            ),
+            (
+                "apiVersion: v1\nkind: Service",
+                "is not a valid Pod: 'kind' must be 'Pod', not \"Service\"",
+            ),
+            (
+                "apiVersion: v1\nkind: Pod\nmetadata:\n  labels: {}",
+                "is not a valid Pod: 'metadata.name' is required",
+            ),
+            (
+                "apiVersion: v1\nkind: Pod\nmetadata: {name: a}\nspec: {containers: []}",
+                "is not a valid Pod: 'spec.containers' must list at least one container",
+            ),
+            (
+                "apiVersion: v1\nkind: Pod\nmetadata: {name: a}\nspec:\n  containers:\n  - name: a",
+                "is not a valid Pod: 'spec.containers.0.image' is required",
+            ),
+            (
+                "apiVersion: v1\nkind: Pod\n  metadata: {}",
+                "is not a valid YAML or JSON document: mapping values are not allowed in this context at line 3 column 11",
+            ),
+            ("- just\n- a list", "is not a valid Pod: expected an object"),
+        ] {
+            assert_eq!(check(manifest).unwrap_err(), expected, "{}", manifest);
+        }
+    }
+
+    #[test]
+    fn test_static_pod_errors_name_the_entry() {
+        let settings: KubernetesSettingsV1 = serde_json::from_value(json!({
+            "static-pods": {
+                "good": {"enabled": true, "manifest": encode(POD_YAML)},
+                "bad": {"enabled": true, "manifest": encode("kind: Service")},
+                "disabled": {"enabled": false, "manifest": encode("kind: Service")},
+                "binary": {"enabled": true, "manifest": "//79"},
+            },
+        }))
+        .unwrap();
+
+        assert_eq!(
+            settings.check_fields().unwrap_err().to_string(),
+            "'static-pods.bad.manifest' is not a valid Pod: 'apiVersion' is required; \
+             'static-pods.binary.manifest' is not valid UTF-8"
+        );
+    }
+}
@@ -0,0 +1,330 @@
+//! Checks the relationships between kubernetes settings which the kubelet would otherwise reject
+//! at startup.
+use crate::KubernetesSettingsV1;
+use bottlerocket_modeled_types::{
+    self as modeled_types, ImageReference, KubernetesEvictionKey, KubernetesMemoryManagerPolicy,
+    KubernetesMemoryReservationKey, KubernetesQuantityValue, KubernetesReservedResourceKey,
+};
+use bottlerocket_settings_sdk::{FieldError, FieldPath};
+use std::collections::HashMap;
+
+/// The kubelet's hard eviction threshold for available memory when `eviction-hard` isn't set.
+const DEFAULT_EVICTION_HARD_MEMORY: &str = "100Mi";
+
+/// Returns a [`FieldError`] for each relationship between fields that `settings` violates.
+///
+/// Relationships are only checked when the fields involved are set.
+pub(crate) fn cross_field_errors(settings: &KubernetesSettingsV1) -> Vec<FieldError> {
+    let mut errors = Vec::new();
+
+    if let (Some(low), Some(high)) = (
+        &settings.image_gc_low_threshold_percent,
+        &settings.image_gc_high_threshold_percent,
+    ) {
+        if low.value() >= high.value() {
+            errors.push(FieldError::new(
+                "image-gc-low-threshold-percent",
+                format!(
+                    "must be less than image-gc-high-threshold-percent ({})",
+                    high
+                ),
+            ));
+        }
+    }
+
+    if let Some(eviction_soft) = &settings.eviction_soft {
+        let mut keys: Vec<_> = eviction_soft
+            .keys()
+            .filter(|key| {
+                settings
+                    .eviction_soft_grace_period
+                    .as_ref()
+                    .is_none_or(|grace_periods| !grace_periods.contains_key(*key))
+            })
+            .map(ToString::to_string)
+            .collect();
+        keys.sort();
+        errors.extend(keys.into_iter().map(|key| {
+            FieldError::new(
+                FieldPath::from("eviction-soft").join(key),
+                "requires a matching eviction-soft-grace-period",
+            )
+        }));
+    }
+
+    if settings
+        .authentication_mode
+        .as_ref()
+        .is_some_and(|mode| mode == "tls")
+        && settings.bootstrap_token.is_none()
+    {
+        errors.push(FieldError::new(
+            "bootstrap-token",
+            "is required when authentication-mode is 'tls'",
+        ));
+    }
+
+    if settings.standalone_mode != Some(true) {
+        if settings.api_server.is_none() {
+            errors.push(FieldError::new(
+                "api-server",
+                "is required unless standalone-mode is enabled",
+            ));
+        }
+        if settings.cluster_certificate.is_none() {
+            errors.push(FieldError::new(
+                "cluster-certificate",
+                "is required unless standalone-mode is enabled",
+            ));
+        }
+    }
+
+    match (&settings.server_certificate, &settings.server_key) {
+        (Some(_), None) => errors.push(FieldError::new(
+            "server-key",
+            "is required when server-certificate is set",
+        )),
+        (None, Some(_)) => errors.push(FieldError::new(
+            "server-certificate",
+            "is required when server-key is set",
+        )),
+        _ => {}
+    }
+
+    if let (Some(critical), Some(total)) = (
+        &settings.shutdown_grace_period_for_critical_pods,
+        &settings.shutdown_grace_period,
+    ) {
+        if critical.duration() > total.duration() {
+            errors.push(FieldError::new(
+                "shutdown-grace-period-for-critical-pods",
+                format!("must not be longer than shutdown-grace-period ({})", total),
+            ));
+        }
+    }
+
+    // The image is kept as a string in this version of the settings, so that stored values which
+    // aren't valid references can still be read.
+    if let Some(image) = &settings.pod_infra_container_image {
+        if let Err(modeled_types::error::Error::InvalidImageReference { msg, .. }) =
+            ImageReference::try_from(image.as_ref())
+        {
+            errors.push(FieldError::new(
+                "pod-infra-container-image",
+                format!("is not a valid image reference: {}", msg),
+            ));
+        }
+    }
+
+    errors.extend(reservation_errors(settings));
+    errors
+}
+
+/// Checks that resource reservations aren't negative, and that memory reserved per NUMA node adds
+/// up to the memory reserved for the system, as the kubelet's static memory manager requires.
+fn reservation_errors(settings: &KubernetesSettingsV1) -> Vec<FieldError> {
+    let mut errors = Vec::new();
+    for (field, reserved) in [
+        ("kube-reserved", &settings.kube_reserved),
+        ("system-reserved", &settings.system_reserved),
+    ] {
+        let mut negative: Vec<_> = reserved
+            .iter()
+            .flatten()
+            .filter(|(_, quantity)| quantity.nanos() < 0)
+            .map(|(resource, _)| resource.to_string())
+            .collect();
+        negative.sort();
+        errors.extend(negative.into_iter().map(|resource| {
+            FieldError::new(
+                FieldPath::from(field).join(resource),
+                "must not be negative",
+            )
+        }));
+    }
+
+    if settings.memory_manager_policy != Some(KubernetesMemoryManagerPolicy::Static) {
+        return errors;
+    }
+    let numa_memory: Vec<_> = settings
+        .memory_manager_reserved_memory
+        .iter()
+        .flatten()
+        .filter(|(_, reservation)| reservation.enabled())
+        .filter_map(|(_, reservation)| {
+            reservation
+                .limits()
+                .get(&KubernetesMemoryReservationKey::Memory)
+        })
+        .collect();
+    if numa_memory.is_empty() {
+        return errors;
+    }
+
+    let memory = KubernetesReservedResourceKey::try_from("memory").ok();
+    let reserved_memory = |reserved: &Option<HashMap<_, KubernetesQuantityValue>>| {
+        reserved
+            .as_ref()
+            .zip(memory.as_ref())
+            .and_then(|(reserved, memory)| reserved.get(memory).cloned())
+    };
+    // Percentage thresholds depend on the node's memory, so can't be checked here.
+    let eviction_memory = match &settings.eviction_hard {
+        None => KubernetesQuantityValue::try_from(DEFAULT_EVICTION_HARD_MEMORY).ok(),
+        Some(eviction_hard) => match eviction_hard.get(&KubernetesEvictionKey::MemoryAvailable) {
+            Some(threshold) => match KubernetesQuantityValue::try_from(threshold.as_ref()) {
+                Ok(quantity) => Some(quantity),
+                Err(_) => return errors,
+            },
+            None => None,
+        },
+    };
+
+    let expected = sum([
+        reserved_memory(&settings.kube_reserved).as_ref(),
+        reserved_memory(&settings.system_reserved).as_ref(),
+        eviction_memory.as_ref(),
+    ]
+    .into_iter()
+    .flatten());
+    let reserved = sum(numa_memory.into_iter());
+    if let (Some(expected), Some(reserved)) = (expected, reserved) {
+        if expected.nanos() != reserved.nanos() {
+            errors.push(FieldError::new(
+                "memory-manager-reserved-memory",
+                format!(
+                    "reserves {} of memory, but must match the {} reserved by kube-reserved, \
+                     system-reserved, and the hard eviction threshold",
+                    reserved, expected
+                ),
+            ));
+        }
+    }
+    errors
+}
+
+/// Adds quantities, returning `None` if there are none or the total overflows.
+fn sum<'a, I>(mut quantities: I) -> Option<KubernetesQuantityValue>
+where
+    I: Iterator<Item = &'a KubernetesQuantityValue>,
+{
+    let first = quantities.next()?.clone();
+    quantities.try_fold(first, |total, quantity| total.checked_add(quantity))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use serde_json::json;
+
+    fn errors_for(value: serde_json::Value) -> Vec<String> {
+        let settings: KubernetesSettingsV1 = serde_json::from_value(value).unwrap();
+        cross_field_errors(&settings)
+            .iter()
+            .map(ToString::to_string)
+            .collect()
+    }
+
+    #[test]
+    fn test_standalone_needs_nothing() {
+        assert!(errors_for(json!({"standalone-mode": true})).is_empty());
+    }
+
+    #[test]
+    fn test_cluster_mode_requires_cluster_settings() {
+        assert_eq!(
+            errors_for(json!({})),
+            vec![
+                "'api-server' is required unless standalone-mode is enabled",
+                "'cluster-certificate' is required unless standalone-mode is enabled",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_relationships() {
+        let errors = errors_for(json!({
+            "standalone-mode": true,
+            "image-gc-high-threshold-percent": 80,
+            "image-gc-low-threshold-percent": "80",
+            "eviction-soft": {"memory.available": "10%", "nodefs.available": "10%"},
+            "eviction-soft-grace-period": {"memory.available": "30s"},
+            "authentication-mode": "tls",
+            "server-key": "a2V5",
+            "shutdown-grace-period": "1m",
+            "shutdown-grace-period-for-critical-pods": "90s",
+            "pod-infra-container-image": "https://example.com/pause",
+        }));
+        assert_eq!(
+            errors,
+            vec![
+                "'image-gc-low-threshold-percent' must be less than \
+                 image-gc-high-threshold-percent (80)",
+                "'eviction-soft.nodefs.available' requires a matching eviction-soft-grace-period",
+                "'bootstrap-token' is required when authentication-mode is 'tls'",
+                "'server-certificate' is required when server-key is set",
+                "'shutdown-grace-period-for-critical-pods' must not be longer than \
+                 shutdown-grace-period (1m)",
+                "'pod-infra-container-image' is not a valid image reference: invalid registry",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_reservations() {
+        let base = json!({
+            "standalone-mode": true,
+            "memory-manager-policy": "Static",
+            "kube-reserved": {"memory": "512Mi", "cpu": "-10m"},
+            "system-reserved": {"memory": "412Mi"},
+            "memory-manager-reserved-memory": {
+                "0": {"enabled": true, "memory": "512Mi"},
+                "1": {"enabled": true, "memory": "512Mi"},
+                "2": {"enabled": false, "memory": "1Gi"},
+            },
+        });
+        assert_eq!(
+            errors_for(base.clone()),
+            vec!["'kube-reserved.cpu' must not be negative"]
+        );
+
+        let mut mismatched = base.clone();
+        mismatched
+            .as_object_mut()
+            .unwrap()
+            .insert("eviction-hard".into(), json!({"memory.available": "200Mi"}));
+        assert_eq!(
+            errors_for(mismatched).get(1).unwrap(),
+            "'memory-manager-reserved-memory' reserves 1Gi of memory, but must match the \
+             1124Mi reserved by kube-reserved, system-reserved, and the hard eviction threshold"
+        );
+
+        // Percentage thresholds can't be checked without knowing the node's memory.
+        let mut percentage = base;
+        percentage
+            .as_object_mut()
+            .unwrap()
+            .insert("eviction-hard".into(), json!({"memory.available": "5%"}));
+        assert_eq!(errors_for(percentage).len(), 1);
+    }
+
+    #[test]
+    fn test_valid_relationships() {
+        let errors = errors_for(json!({
+            "api-server": "https://example.com",
+            "cluster-certificate": "Y2VydA==",
+            "image-gc-high-threshold-percent": 85,
+            "image-gc-low-threshold-percent": 80,
+            "eviction-soft": {"memory.available": "10%"},
+            "eviction-soft-grace-period": {"memory.available": "30s"},
+            "authentication-mode": "tls",
+            "bootstrap-token": "abcdef.0123456789abcdef",
+            "server-certificate": "Y2VydA==",
+            "server-key": "a2V5",
+            "shutdown-grace-period": "1m",
+            "shutdown-grace-period-for-critical-pods": "30s",
+            "pod-infra-container-image": "602401143452.dkr.ecr.us-west-2.amazonaws.com/eks/pause:3.1-eksbuild.1",
+        }));
+        assert!(errors.is_empty(), "{:?}", errors);
+    }
+}
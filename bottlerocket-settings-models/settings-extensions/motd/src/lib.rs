@@ -1,6 +1,13 @@
 //! The motd setting is used to set the "message of the day" that is shown to users when logging
 //! into the Bottlerocket control container.
-use bottlerocket_settings_sdk::{GenerateResult, LinearlyMigrateable, NoMigration, SettingsModel};
+
+// `string_impls_for` expects a `TryFrom<&str>` implementation, even though any string is a valid
+// motd.
+#![allow(clippy::infallible_try_from)]
+
+use bottlerocket_settings_sdk::{
+    GenerateResult, KnownNames, LinearlyMigrateable, NoMigration, SettingsModel,
+};
 use bottlerocket_string_impls_for::string_impls_for;
 use std::convert::Infallible;
 
@@ -9,6 +16,9 @@ pub struct MotdV1 {
     inner: String,
 }
 
+// The motd is a single string, so it has no names to suggest.
+impl KnownNames for MotdV1 {}
+
 type Result<T> = std::result::Result<T, Infallible>;
 
 impl SettingsModel for MotdV1 {
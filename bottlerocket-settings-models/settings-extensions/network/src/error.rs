@@ -0,0 +1,14 @@
+//! The error type returned by the network settings extension.
+use snafu::Snafu;
+
+/// The error type returned when migrating network settings between versions.
+#[derive(Debug, Snafu)]
+#[snafu(visibility(pub(crate)))]
+pub enum Error {
+    #[snafu(display("Cannot migrate no-proxy entry '{}' to {}: {}", entry, version, source))]
+    Migration {
+        entry: String,
+        version: &'static str,
+        source: bottlerocket_modeled_types::error::Error,
+    },
+}
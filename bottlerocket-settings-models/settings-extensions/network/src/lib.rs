@@ -1,10 +1,14 @@
 //! Settings related to networking configuration.
 use bottlerocket_model_derive::model;
-use bottlerocket_modeled_types::{EtcHostsEntries, SingleLineString, Url, ValidLinuxHostname};
-use bottlerocket_settings_sdk::{GenerateResult, SettingsModel};
-use std::convert::Infallible;
+use bottlerocket_modeled_types::{
+    EtcHostsEntries, NoProxyEntry, SingleLineString, Url, ValidLinuxHostname,
+};
+use bottlerocket_settings_sdk::{GenerateResult, LinearlyMigrateable, NoMigration, SettingsModel};
+use snafu::ResultExt;
 
-#[model(impl_default = true)]
+pub mod error;
+
+#[model(impl_default = true, accessors = true)]
 struct NetworkSettingsV1 {
     hostname: ValidLinuxHostname,
     hosts: EtcHostsEntries,
@@ -12,11 +16,11 @@ struct NetworkSettingsV1 {
     no_proxy: Vec<SingleLineString>,
 }
 
-type Result<T> = std::result::Result<T, Infallible>;
+type Result<T> = std::result::Result<T, error::Error>;
 
 impl SettingsModel for NetworkSettingsV1 {
     type PartialKind = Self;
-    type ErrorKind = Infallible;
+    type ErrorKind = error::Error;
 
     fn get_version() -> &'static str {
         "v1"
@@ -42,6 +46,106 @@ impl SettingsModel for NetworkSettingsV1 {
     }
 }
 
+impl LinearlyMigrateable for NetworkSettingsV1 {
+    type ForwardMigrationTarget = NetworkSettingsV2;
+    type BackwardMigrationTarget = NoMigration;
+
+    /// No-proxy entries must be valid hosts, domains, or networks to migrate forward.
+    fn migrate_forward(&self) -> Result<Self::ForwardMigrationTarget> {
+        Ok(NetworkSettingsV2 {
+            hostname: self.hostname.clone(),
+            hosts: self.hosts.clone(),
+            https_proxy: self.https_proxy.clone(),
+            no_proxy: migrate_no_proxy(&self.no_proxy, "v2")?,
+        })
+    }
+
+    fn migrate_backward(&self) -> Result<Self::BackwardMigrationTarget> {
+        NoMigration::no_defined_migration()
+    }
+}
+
+/// The second version of the network settings, in which each no-proxy entry must be `*`, an IP
+/// address or network, or a hostname with an optional port, rather than any string.
+#[model(impl_default = true, accessors = true)]
+struct NetworkSettingsV2 {
+    hostname: ValidLinuxHostname,
+    hosts: EtcHostsEntries,
+    https_proxy: Url,
+    no_proxy: Vec<NoProxyEntry>,
+}
+
+impl SettingsModel for NetworkSettingsV2 {
+    type PartialKind = Self;
+    type ErrorKind = error::Error;
+
+    fn get_version() -> &'static str {
+        "v2"
+    }
+
+    fn set(_current_value: Option<Self>, _target: Self) -> Result<()> {
+        // Set anything that can be parsed as NetworkSettingsV2.
+        Ok(())
+    }
+
+    fn generate(
+        existing_partial: Option<Self::PartialKind>,
+        _dependent_settings: Option<serde_json::Value>,
+    ) -> Result<GenerateResult<Self::PartialKind, Self>> {
+        Ok(GenerateResult::Complete(
+            existing_partial.unwrap_or_default(),
+        ))
+    }
+
+    fn validate(_value: Self, _validated_settings: Option<serde_json::Value>) -> Result<()> {
+        // NetworkSettingsV2 is validated during deserialization.
+        Ok(())
+    }
+}
+
+impl LinearlyMigrateable for NetworkSettingsV2 {
+    type ForwardMigrationTarget = NoMigration;
+    type BackwardMigrationTarget = NetworkSettingsV1;
+
+    fn migrate_forward(&self) -> Result<Self::ForwardMigrationTarget> {
+        NoMigration::no_defined_migration()
+    }
+
+    fn migrate_backward(&self) -> Result<Self::BackwardMigrationTarget> {
+        Ok(NetworkSettingsV1 {
+            hostname: self.hostname.clone(),
+            hosts: self.hosts.clone(),
+            https_proxy: self.https_proxy.clone(),
+            no_proxy: migrate_no_proxy(&self.no_proxy, "v1")?,
+        })
+    }
+}
+
+/// Converts each no-proxy entry to the type used by `version`, naming the entry in any error.
+fn migrate_no_proxy<T, U>(
+    no_proxy: &Option<Vec<T>>,
+    version: &'static str,
+) -> Result<Option<Vec<U>>>
+where
+    T: AsRef<str>,
+    for<'a> U: TryFrom<&'a str, Error = bottlerocket_modeled_types::error::Error>,
+{
+    no_proxy
+        .as_ref()
+        .map(|entries| {
+            entries
+                .iter()
+                .map(|entry| {
+                    U::try_from(entry.as_ref()).context(error::MigrationSnafu {
+                        entry: entry.as_ref(),
+                        version,
+                    })
+                })
+                .collect()
+        })
+        .transpose()
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -49,13 +153,13 @@ mod test {
     #[test]
     fn test_generate_network_settings() {
         assert_eq!(
-            NetworkSettingsV1::generate(None, None),
-            Ok(GenerateResult::Complete(NetworkSettingsV1 {
+            NetworkSettingsV1::generate(None, None).unwrap(),
+            GenerateResult::Complete(NetworkSettingsV1 {
                 hostname: None,
                 hosts: None,
                 https_proxy: None,
                 no_proxy: None,
-            }))
+            })
         )
     }
 
@@ -83,4 +187,77 @@ mod test {
             }
         );
     }
+
+    #[test]
+    fn test_serde_network_v2() {
+        let test_json = r#"{
+            "hostname": "foo",
+            "hosts": [["127.0.0.1", ["localhost"]]],
+            "https-proxy": "https://example.net",
+            "no-proxy": ["foo", ".example.com", "10.0.0.0/8"]
+        }"#;
+
+        let network: NetworkSettingsV2 = serde_json::from_str(test_json).unwrap();
+
+        assert_eq!(
+            network,
+            NetworkSettingsV2 {
+                hostname: Some(ValidLinuxHostname::try_from("foo").unwrap()),
+                hosts: Some(
+                    serde_json::from_str::<EtcHostsEntries>(r#"[["127.0.0.1", ["localhost"]]]"#)
+                        .unwrap()
+                ),
+                https_proxy: Some(Url::try_from("https://example.net").unwrap()),
+                no_proxy: Some(vec![
+                    NoProxyEntry::try_from("foo").unwrap(),
+                    NoProxyEntry::try_from(".example.com").unwrap(),
+                    NoProxyEntry::try_from("10.0.0.0/8").unwrap(),
+                ]),
+            }
+        );
+    }
+
+    #[test]
+    fn test_invalid_no_proxy() {
+        let test_json = r#"{"no-proxy": ["https://example.com"]}"#;
+        assert!(serde_json::from_str::<NetworkSettingsV1>(test_json).is_ok());
+        assert!(serde_json::from_str::<NetworkSettingsV2>(test_json).is_err());
+    }
+
+    #[test]
+    fn test_migrate_network() {
+        let v1: NetworkSettingsV1 = serde_json::from_str(
+            r#"{"hostname": "foo", "no-proxy": ["localhost", ".example.com", "10.0.0.0/8"]}"#,
+        )
+        .unwrap();
+
+        let v2 = v1.migrate_forward().unwrap();
+        assert_eq!(v2.hostname, v1.hostname);
+        assert_eq!(
+            v2.no_proxy,
+            Some(vec![
+                NoProxyEntry::try_from("localhost").unwrap(),
+                NoProxyEntry::try_from(".example.com").unwrap(),
+                NoProxyEntry::try_from("10.0.0.0/8").unwrap(),
+            ])
+        );
+        assert_eq!(v2.migrate_backward().unwrap(), v1);
+
+        let unset = NetworkSettingsV1::default().migrate_forward().unwrap();
+        assert_eq!(unset.no_proxy, None);
+    }
+
+    #[test]
+    fn test_migrate_network_failure() {
+        let v1: NetworkSettingsV1 =
+            serde_json::from_str(r#"{"no-proxy": ["https://example.com"]}"#).unwrap();
+        let error = v1.migrate_forward().unwrap_err();
+        assert!(
+            error
+                .to_string()
+                .starts_with("Cannot migrate no-proxy entry 'https://example.com' to v2"),
+            "{}",
+            error
+        );
+    }
 }
@@ -1,12 +1,15 @@
-use bottlerocket_settings_sdk::{BottlerocketSetting, NullMigratorExtensionBuilder};
-use settings_extension_network::NetworkSettingsV1;
+use bottlerocket_settings_sdk::{BottlerocketSetting, LinearMigratorExtensionBuilder};
+use settings_extension_network::{NetworkSettingsV1, NetworkSettingsV2};
 use std::process::ExitCode;
 
 fn main() -> ExitCode {
     env_logger::init();
 
-    match NullMigratorExtensionBuilder::with_name("network")
-        .with_models(vec![BottlerocketSetting::<NetworkSettingsV1>::model()])
+    match LinearMigratorExtensionBuilder::with_name("network")
+        .with_models(vec![
+            BottlerocketSetting::<NetworkSettingsV1>::model(),
+            BottlerocketSetting::<NetworkSettingsV2>::model(),
+        ])
         .build()
     {
         Ok(extension) => extension.run(),
@@ -0,0 +1,169 @@
+//! Renders a chrony configuration fragment from [`NtpSettingsV2`].
+//!
+//! The fragment holds a line for each time server, in the order they're set, followed by any
+//! global directives. It's meant to be included in a chrony configuration file that sets
+//! everything else, such as where to keep the drift file.
+use crate::{NtpSettingsV2, NtpTimeServerV2};
+use bottlerocket_settings_sdk::helper::template_helper;
+use std::convert::Infallible;
+
+/// Renders the `pool` or `server` line for each time server, followed by the global directives.
+pub fn render_chrony_config(settings: &NtpSettingsV2) -> String {
+    let mut lines = Vec::new();
+    for server in settings.time_servers.iter().flatten() {
+        let Some(address) = &server.address else {
+            continue;
+        };
+        let source = if server.pool == Some(true) {
+            "pool"
+        } else {
+            "server"
+        };
+        let mut line = vec![source.to_string(), address.to_string()];
+        if server.iburst == Some(true) {
+            line.push("iburst".to_string());
+        }
+        if let Some(port) = server.port {
+            line.extend(["port".to_string(), port.to_string()]);
+        }
+        line.extend(source_options(server));
+        lines.push(line.join(" "));
+    }
+
+    if let Some(directives) = &settings.directives {
+        if let Some(makestep) = &directives.makestep {
+            if let (Some(threshold), Some(limit)) = (makestep.threshold, makestep.limit) {
+                lines.push(format!("makestep {} {}", threshold, limit));
+            }
+        }
+        if let Some(minsources) = directives.minsources {
+            lines.push(format!("minsources {}", minsources));
+        }
+        if let Some(maxupdateskew) = directives.maxupdateskew {
+            lines.push(format!("maxupdateskew {}", maxupdateskew));
+        }
+        if directives.rtcsync == Some(true) {
+            lines.push("rtcsync".to_string());
+        }
+    }
+
+    lines.into_iter().map(|line| line + "\n").collect()
+}
+
+#[template_helper(ident = chrony_config_helper, vis = "pub(crate)")]
+fn chrony_config(settings: NtpSettingsV2) -> Result<String, Infallible> {
+    Ok(render_chrony_config(&settings))
+}
+
+/// Returns the options for a time server other than `iburst`, which the v1 settings always set,
+/// and `port`, which they set as part of the time server.
+pub(crate) fn source_options(server: &NtpTimeServerV2) -> Vec<String> {
+    let mut options = Vec::new();
+    if server.prefer == Some(true) {
+        options.push("prefer".to_string());
+    }
+    if let Some(minpoll) = server.minpoll {
+        options.extend(["minpoll".to_string(), minpoll.to_string()]);
+    }
+    if let Some(maxpoll) = server.maxpoll {
+        options.extend(["maxpoll".to_string(), maxpoll.to_string()]);
+    }
+    if server.nts == Some(true) {
+        options.push("nts".to_string());
+    }
+    options.extend(server.extra_options.iter().flatten().cloned());
+    options
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use serde_json::json;
+
+    fn render(value: serde_json::Value) -> String {
+        render_chrony_config(&serde_json::from_value(value).unwrap())
+    }
+
+    #[test]
+    fn test_sources() {
+        // Servers are rendered in the order they're set, as pools only when asked, and servers
+        // without an address are skipped.
+        assert_eq!(
+            render(json!({"time-servers": [
+                {"address": "2.amazon.pool.ntp.org", "pool": true, "iburst": true},
+                {"pool": true},
+                {"address": "fd00:ec2::123", "pool": false, "iburst": false},
+            ]})),
+            "pool 2.amazon.pool.ntp.org iburst\nserver fd00:ec2::123\n"
+        );
+    }
+
+    #[test]
+    fn test_source_options_order() {
+        assert_eq!(
+            render(json!({"time-servers": [{
+                "address": "169.254.169.123",
+                "extra-options": ["key", "5", "xleave"],
+                "nts": true,
+                "maxpoll": 8,
+                "minpoll": -2,
+                "prefer": true,
+                "port": 4460,
+                "iburst": true,
+            }]})),
+            "server 169.254.169.123 iburst port 4460 prefer minpoll -2 maxpoll 8 nts key 5 \
+             xleave\n"
+        );
+        // Options that are unset or false aren't rendered.
+        assert_eq!(
+            render(json!({"time-servers": [
+                {"address": "time.aws.com", "prefer": false, "nts": false, "extra-options": []},
+            ]})),
+            "server time.aws.com\n"
+        );
+    }
+
+    #[test]
+    fn test_directives() {
+        assert_eq!(
+            render(json!({"directives": {
+                "rtcsync": true,
+                "maxupdateskew": 100.5,
+                "minsources": 2,
+                "makestep": {"threshold": 1.0, "limit": -1},
+            }})),
+            "makestep 1 -1\nminsources 2\nmaxupdateskew 100.5\nrtcsync\n"
+        );
+        // makestep needs both of its values, and rtcsync is only written when it's enabled.
+        assert_eq!(
+            render(json!({"directives": {"makestep": {"threshold": 0.5}, "rtcsync": false}})),
+            ""
+        );
+    }
+
+    #[test]
+    fn test_servers_before_directives() {
+        assert_eq!(
+            render(json!({
+                "directives": {"minsources": 1},
+                "time-servers": [{"address": "time.aws.com"}],
+            })),
+            "server time.aws.com\nminsources 1\n"
+        );
+        assert_eq!(render_chrony_config(&NtpSettingsV2::default()), "");
+    }
+
+    #[test]
+    fn test_helper() {
+        assert_eq!(
+            chrony_config_helper(vec![json!({
+                "time-servers": [{"address": "time.aws.com", "pool": true, "iburst": true}],
+            })])
+            .unwrap(),
+            json!("pool time.aws.com iburst\n")
+        );
+        // The helper reads the v2 settings, so the v1 list of time servers is rejected.
+        assert!(chrony_config_helper(vec![json!({"time-servers": ["time.aws.com"]})]).is_err());
+        assert!(chrony_config_helper(vec![]).is_err());
+    }
+}
@@ -0,0 +1,20 @@
+//! The error type returned by the ntp settings extension.
+use snafu::Snafu;
+
+/// The error type returned when migrating ntp settings between versions.
+#[derive(Debug, Snafu)]
+#[snafu(visibility(pub(crate)))]
+pub enum Error {
+    #[snafu(display("Cannot migrate time server '{}' to {}: {}", server, version, source))]
+    Migration {
+        server: String,
+        version: &'static str,
+        source: bottlerocket_modeled_types::error::Error,
+    },
+
+    #[snafu(display("Cannot migrate ntp settings to {}: {}", version, reason))]
+    Unrepresentable {
+        version: &'static str,
+        reason: &'static str,
+    },
+}
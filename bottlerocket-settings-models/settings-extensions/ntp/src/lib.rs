@@ -1,23 +1,31 @@
 //! The ntp settings can be used to specify time servers with which to synchronize the instance's
 //! clock.
 use bottlerocket_model_derive::model;
-use bottlerocket_modeled_types::Url;
-use bottlerocket_settings_sdk::{GenerateResult, LinearlyMigrateable, NoMigration, SettingsModel};
-use std::convert::Infallible;
+use bottlerocket_modeled_types::{NetworkHost, Url};
+use bottlerocket_settings_sdk::{
+    provide_template_helpers, FieldChecks, GenerateResult, HelperDef, LinearlyMigrateable,
+    NoMigration, SettingsModel,
+};
+use snafu::{ensure, OptionExt, ResultExt};
+use std::collections::HashMap;
 
-#[model(impl_default = true)]
+pub mod chrony;
+pub mod error;
+mod validate;
+
+#[model(impl_default = true, accessors = true)]
 pub struct NtpSettingsV1 {
     time_servers: Vec<Url>,
     options: Vec<String>,
 }
 
-type Result<T> = std::result::Result<T, Infallible>;
+type Result<T> = std::result::Result<T, error::Error>;
 
 impl SettingsModel for NtpSettingsV1 {
     /// the `model` macro makes every field of the `NtpSettingsV1` struct an `Option`, so we can use
     /// the type as its own `PartialKind`.
     type PartialKind = Self;
-    type ErrorKind = Infallible;
+    type ErrorKind = error::Error;
 
     fn get_version() -> &'static str {
         "v1"
@@ -44,11 +52,41 @@ impl SettingsModel for NtpSettingsV1 {
 }
 
 impl LinearlyMigrateable for NtpSettingsV1 {
-    type ForwardMigrationTarget = NoMigration;
+    type ForwardMigrationTarget = NtpSettingsV2;
     type BackwardMigrationTarget = NoMigration;
 
+    /// The v1 settings are rendered as a pool with `iburst` for each time server, followed by the
+    /// options, so each server migrates to a pool with `iburst` and the same options. Options
+    /// that the v2 settings don't model are kept as extra options.
     fn migrate_forward(&self) -> Result<Self::ForwardMigrationTarget> {
-        NoMigration::no_defined_migration()
+        let template = server_template(self.options.as_deref().unwrap_or_default());
+        let time_servers = self
+            .time_servers
+            .as_ref()
+            .map(|servers| {
+                servers
+                    .iter()
+                    .map(|server| {
+                        let (host, port) = url_host_port(server);
+                        let address =
+                            NetworkHost::try_from(host).context(error::MigrationSnafu {
+                                server: server.to_string(),
+                                version: "v2",
+                            })?;
+                        Ok(NtpTimeServerV2 {
+                            address: Some(address),
+                            port,
+                            ..template.clone()
+                        })
+                    })
+                    .collect::<Result<Vec<_>>>()
+            })
+            .transpose()?;
+
+        Ok(NtpSettingsV2 {
+            time_servers,
+            directives: None,
+        })
     }
 
     fn migrate_backward(&self) -> Result<Self::BackwardMigrationTarget> {
@@ -56,6 +94,229 @@ impl LinearlyMigrateable for NtpSettingsV1 {
     }
 }
 
+/// Parses the v1 options into a v2 time server with no address. Options the v2 settings don't
+/// model, or whose values they can't hold, are kept as extra options in the order they're given.
+fn server_template(options: &[String]) -> NtpTimeServerV2 {
+    let mut server = NtpTimeServerV2 {
+        pool: Some(true),
+        iburst: Some(true),
+        ..Default::default()
+    };
+
+    let mut extra_options = Vec::new();
+    let mut options = options.iter().peekable();
+    while let Some(option) = options.next() {
+        let poll = match option.as_str() {
+            "iburst" => continue,
+            "prefer" => {
+                server.prefer = Some(true);
+                continue;
+            }
+            "nts" => {
+                server.nts = Some(true);
+                continue;
+            }
+            "minpoll" => &mut server.minpoll,
+            "maxpoll" => &mut server.maxpoll,
+            _ => {
+                extra_options.push(option.clone());
+                continue;
+            }
+        };
+        match options
+            .peek()
+            .and_then(|value| value.parse().ok())
+            .filter(|value| validate::POLL_RANGE.contains(value))
+        {
+            Some(value) => {
+                *poll = Some(value);
+                options.next();
+            }
+            None => extra_options.push(option.clone()),
+        }
+    }
+
+    if !extra_options.is_empty() {
+        server.extra_options = Some(extra_options);
+    }
+    server
+}
+
+/// Returns the host and port of a v1 time server, which may have been written with a scheme or
+/// path.
+fn url_host_port(server: &Url) -> (&str, Option<u16>) {
+    let host = server
+        .split_once("://")
+        .map_or(server.as_ref(), |(_, rest)| rest);
+    let host = host.split('/').next().unwrap_or_default();
+
+    if let Some((address, rest)) = host.strip_prefix('[').and_then(|host| host.split_once(']')) {
+        let port = rest.strip_prefix(':').and_then(|port| port.parse().ok());
+        return (address, port);
+    }
+    // Unbracketed IPv6 addresses have several colons, and can't be given a port.
+    match host.split_once(':') {
+        Some((address, port)) if !port.contains(':') => match port.parse() {
+            Ok(port) => (address, Some(port)),
+            Err(_) => (host, None),
+        },
+        _ => (host, None),
+    }
+}
+
+#[model(impl_default = true)]
+struct NtpTimeServerV2 {
+    address: NetworkHost,
+    port: u16,
+    // Pools may resolve to several servers, all of which are used.
+    pool: bool,
+    iburst: bool,
+    prefer: bool,
+    minpoll: i8,
+    maxpoll: i8,
+    nts: bool,
+    // Options that are passed to chrony as they're given, such as those kept from v1.
+    extra_options: Vec<String>,
+}
+
+#[model(impl_default = true)]
+struct MakeStepV2 {
+    #[validate(requires = "limit", custom = validate::positive)]
+    threshold: f64,
+    // A negative limit allows the clock to be stepped at any time.
+    #[validate(requires = "threshold")]
+    limit: i32,
+}
+
+#[model(impl_default = true)]
+struct ChronyDirectivesV2 {
+    #[validate(nested)]
+    makestep: MakeStepV2,
+    #[validate(range(min = 1))]
+    minsources: u32,
+    #[validate(custom = validate::positive)]
+    maxupdateskew: f64,
+    rtcsync: bool,
+}
+
+/// The second version of the ntp settings, in which each time server has its own chrony options,
+/// and chrony's global directives can be set.
+#[model(impl_default = true, accessors = true)]
+pub struct NtpSettingsV2 {
+    #[validate(nested)]
+    time_servers: Vec<NtpTimeServerV2>,
+    #[validate(nested)]
+    directives: ChronyDirectivesV2,
+}
+
+impl SettingsModel for NtpSettingsV2 {
+    type PartialKind = Self;
+    type ErrorKind = error::Error;
+
+    fn get_version() -> &'static str {
+        "v2"
+    }
+
+    fn set(_current_value: Option<Self>, _target: Self) -> Result<()> {
+        // Anything that parses as NtpSettingsV2 and passes field validation is ok
+        Ok(())
+    }
+
+    fn generate(
+        existing_partial: Option<Self::PartialKind>,
+        _dependent_settings: Option<serde_json::Value>,
+    ) -> Result<GenerateResult<Self::PartialKind, Self>> {
+        Ok(GenerateResult::Complete(
+            existing_partial.unwrap_or_default(),
+        ))
+    }
+
+    fn validate(_value: Self, _validated_settings: Option<serde_json::Value>) -> Result<()> {
+        // NtpSettingsV2 is checked by its field validation.
+        Ok(())
+    }
+
+    fn field_checks() -> FieldChecks<Self> {
+        FieldChecks::declared()
+    }
+
+    fn template_helpers() -> Result<HashMap<String, Box<dyn HelperDef>>> {
+        use chrony::chrony_config_helper;
+
+        Ok(provide_template_helpers! {
+            "chrony_config" => chrony_config_helper,
+        })
+    }
+}
+
+impl LinearlyMigrateable for NtpSettingsV2 {
+    type ForwardMigrationTarget = NoMigration;
+    type BackwardMigrationTarget = NtpSettingsV1;
+
+    fn migrate_forward(&self) -> Result<Self::ForwardMigrationTarget> {
+        NoMigration::no_defined_migration()
+    }
+
+    /// The v1 settings can only describe pools with `iburst` which share the same options, and
+    /// have no global directives.
+    fn migrate_backward(&self) -> Result<Self::BackwardMigrationTarget> {
+        ensure!(
+            self.directives
+                .as_ref()
+                .is_none_or(|directives| *directives == ChronyDirectivesV2::default()),
+            error::UnrepresentableSnafu {
+                version: "v1",
+                reason: "directives can't be set",
+            }
+        );
+
+        let servers = self.time_servers.as_deref().unwrap_or_default();
+        let mut options = None;
+        let mut time_servers = Vec::new();
+        for server in servers {
+            ensure!(
+                server.pool == Some(true) && server.iburst == Some(true),
+                error::UnrepresentableSnafu {
+                    version: "v1",
+                    reason: "every time server must be a pool with iburst",
+                }
+            );
+            let server_options = chrony::source_options(server);
+            ensure!(
+                options.get_or_insert_with(|| server_options.clone()) == &server_options,
+                error::UnrepresentableSnafu {
+                    version: "v1",
+                    reason: "every time server must have the same options",
+                }
+            );
+
+            let address = server
+                .address
+                .as_ref()
+                .context(error::UnrepresentableSnafu {
+                    version: "v1",
+                    reason: "every time server must have an address",
+                })?;
+            let url = match server.port {
+                Some(port) if address.ip().is_some_and(|ip| ip.is_ipv6()) => {
+                    format!("[{}]:{}", address, port)
+                }
+                Some(port) => format!("{}:{}", address, port),
+                None => address.to_string(),
+            };
+            time_servers.push(Url::try_from(url.as_str()).context(error::MigrationSnafu {
+                server: url.clone(),
+                version: "v1",
+            })?);
+        }
+
+        Ok(NtpSettingsV1 {
+            time_servers: self.time_servers.as_ref().map(|_| time_servers),
+            options: options.filter(|options| !options.is_empty()),
+        })
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -63,11 +324,11 @@ mod test {
     #[test]
     fn test_generate_ntp_settings() {
         assert_eq!(
-            NtpSettingsV1::generate(None, None),
-            Ok(GenerateResult::Complete(NtpSettingsV1 {
+            NtpSettingsV1::generate(None, None).unwrap(),
+            GenerateResult::Complete(NtpSettingsV1 {
                 time_servers: None,
                 options: None,
-            }))
+            })
         )
     }
 
@@ -101,4 +362,225 @@ mod test {
         let results = serde_json::to_string(&ntp).unwrap();
         assert_eq!(results, test_json);
     }
+
+    #[test]
+    fn test_accessors_ntp() {
+        let ntp = NtpSettingsV1::default()
+            .with_time_servers(vec![Url::try_from("https://example.net").unwrap()]);
+
+        assert_eq!(
+            ntp.time_servers(),
+            Some(&vec![Url::try_from("https://example.net").unwrap()])
+        );
+        assert_eq!(ntp.options(), None);
+    }
+
+    #[test]
+    fn test_migrate_forward() {
+        let v1: NtpSettingsV1 = serde_json::from_value(serde_json::json!({
+            "time-servers": ["ntp://time.aws.com/path", "169.254.169.123", "[fd00:ec2::123]"],
+            "options": ["minpoll", "4", "maxpoll", "8", "prefer"],
+        }))
+        .unwrap();
+
+        let v2 = v1.migrate_forward().unwrap();
+        let server = |address: &str| {
+            serde_json::json!({
+                "address": address,
+                "pool": true,
+                "iburst": true,
+                "prefer": true,
+                "minpoll": 4,
+                "maxpoll": 8,
+            })
+        };
+        assert_eq!(
+            serde_json::to_value(&v2).unwrap(),
+            serde_json::json!({
+                "time-servers": [
+                    server("time.aws.com"),
+                    server("169.254.169.123"),
+                    server("fd00:ec2::123"),
+                ],
+            })
+        );
+        assert_eq!(
+            NtpSettingsV1::default().migrate_forward().unwrap(),
+            NtpSettingsV2::default()
+        );
+    }
+
+    #[test]
+    fn test_migrate_forward_extra_options() {
+        let v1 = NtpSettingsV1::default().with_options(
+            [
+                "offline", "key", "5", "prefer", "minpoll", "often", "maxpoll", "30", "xleave",
+            ]
+            .into_iter()
+            .map(str::to_string)
+            .collect(),
+        );
+
+        let v2 = v1.migrate_forward().unwrap();
+        assert_eq!(v2.time_servers, None);
+        let template = server_template(v1.options.as_deref().unwrap());
+        assert_eq!(template.prefer, Some(true));
+        assert_eq!((template.minpoll, template.maxpoll), (None, None));
+        assert_eq!(
+            template.extra_options.unwrap(),
+            vec!["offline", "key", "5", "minpoll", "often", "maxpoll", "30", "xleave"]
+        );
+
+        let v1 = v1.with_time_servers(vec![Url::try_from("time.aws.com").unwrap()]);
+        let v2 = v1.migrate_forward().unwrap();
+        assert!(NtpSettingsV2::field_checks().check_fields(&v2).is_ok());
+        assert_eq!(
+            v2.migrate_backward().unwrap().options.unwrap(),
+            vec!["prefer", "offline", "key", "5", "minpoll", "often", "maxpoll", "30", "xleave"]
+        );
+    }
+
+    #[test]
+    fn test_migrate_forward_ports() {
+        let v1: NtpSettingsV1 = serde_json::from_value(serde_json::json!({
+            "time-servers": [
+                "time.aws.com:123",
+                "ntp://169.254.169.123:4460/path",
+                "[fd00:ec2::123]:123",
+                "fd00:ec2::123",
+            ],
+        }))
+        .unwrap();
+
+        let v2 = v1.migrate_forward().unwrap();
+        let servers: Vec<_> = v2
+            .time_servers
+            .iter()
+            .flatten()
+            .map(|server| (server.address.as_ref().unwrap().to_string(), server.port))
+            .collect();
+        assert_eq!(
+            servers,
+            vec![
+                ("time.aws.com".to_string(), Some(123)),
+                ("169.254.169.123".to_string(), Some(4460)),
+                ("fd00:ec2::123".to_string(), Some(123)),
+                ("fd00:ec2::123".to_string(), None),
+            ]
+        );
+
+        let v1_again = v2.migrate_backward().unwrap();
+        assert_eq!(
+            v1_again.time_servers.unwrap(),
+            vec![
+                Url::try_from("time.aws.com:123").unwrap(),
+                Url::try_from("169.254.169.123:4460").unwrap(),
+                Url::try_from("[fd00:ec2::123]:123").unwrap(),
+                Url::try_from("fd00:ec2::123").unwrap(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_migrate_backward() {
+        let v1: NtpSettingsV1 = serde_json::from_value(serde_json::json!({
+            "time-servers": ["time.aws.com", "169.254.169.123"],
+            "options": ["prefer", "minpoll", "4", "maxpoll", "8", "nts"],
+        }))
+        .unwrap();
+        assert_eq!(
+            v1.migrate_forward().unwrap().migrate_backward().unwrap(),
+            v1
+        );
+
+        let v1 = NtpSettingsV1::default()
+            .with_time_servers(vec![Url::try_from("time.aws.com").unwrap()]);
+        assert_eq!(
+            v1.migrate_forward().unwrap().migrate_backward().unwrap(),
+            v1
+        );
+    }
+
+    #[test]
+    fn test_migrate_backward_unrepresentable() {
+        for (value, reason) in [
+            (
+                serde_json::json!({"directives": {"rtcsync": true}}),
+                "directives can't be set",
+            ),
+            (
+                serde_json::json!({"time-servers": [{"address": "time.aws.com", "iburst": true}]}),
+                "every time server must be a pool with iburst",
+            ),
+            (
+                serde_json::json!({"time-servers": [
+                    {"address": "a.example.com", "pool": true, "iburst": true, "prefer": true},
+                    {"address": "b.example.com", "pool": true, "iburst": true},
+                ]}),
+                "every time server must have the same options",
+            ),
+        ] {
+            let v2: NtpSettingsV2 = serde_json::from_value(value).unwrap();
+            assert_eq!(
+                v2.migrate_backward().unwrap_err().to_string(),
+                format!("Cannot migrate ntp settings to v1: {}", reason)
+            );
+        }
+    }
+
+    #[test]
+    fn test_v2_field_errors() {
+        let v2: NtpSettingsV2 = serde_json::from_value(serde_json::json!({
+            "time-servers": [
+                {"address": "time.aws.com", "minpoll": 10, "maxpoll": 6},
+                {"pool": true, "maxpoll": 30},
+            ],
+            "directives": {
+                "makestep": {"threshold": 0.0},
+                "minsources": 0,
+                "maxupdateskew": 100.0,
+            },
+        }))
+        .unwrap();
+
+        assert_eq!(
+            NtpSettingsV2::field_checks()
+                .check_fields(&v2)
+                .unwrap_err()
+                .to_string(),
+            "'time-servers.0.minpoll' must not be greater than maxpoll (6); \
+             'time-servers.1.address' is required; \
+             'time-servers.1.maxpoll' must be between -6 and 24; \
+             'directives.makestep.threshold' requires 'limit' to be set; \
+             'directives.makestep.threshold' must be greater than 0; \
+             'directives.minsources' must be at least 1"
+        );
+    }
+
+    #[test]
+    fn test_template_helpers_listed() {
+        let config: toml::Value = toml::from_str(include_str!("../ntp.toml")).unwrap();
+        let listed = |version: &str| {
+            let mut helpers: Vec<_> = config
+                .get(version)
+                .and_then(|settings| settings.get("templating"))
+                .and_then(|templating| templating.get("helpers"))
+                .and_then(toml::Value::as_array)
+                .unwrap()
+                .iter()
+                .map(|helper| helper.as_str().unwrap().to_string())
+                .collect();
+            helpers.sort();
+            helpers
+        };
+
+        assert!(NtpSettingsV1::template_helpers().unwrap().is_empty());
+        assert!(listed("v1").is_empty());
+        let mut registered: Vec<_> = NtpSettingsV2::template_helpers()
+            .unwrap()
+            .into_keys()
+            .collect();
+        registered.sort();
+        assert_eq!(registered, listed("v2"));
+    }
 }
@@ -0,0 +1,45 @@
+//! Checks the options chrony would reject for a time server.
+use crate::NtpTimeServerV2;
+use bottlerocket_settings_sdk::{FieldError, FieldPath, ValidateFields};
+
+/// The range of polling intervals chrony accepts, as powers of 2 seconds.
+pub(crate) const POLL_RANGE: std::ops::RangeInclusive<i8> = -6..=24;
+
+impl ValidateFields for NtpTimeServerV2 {
+    fn collect_field_errors(&self, path: &FieldPath, errors: &mut Vec<FieldError>) {
+        let field = |name| path.join(name);
+        if self.address.is_none() {
+            errors.push(FieldError::new(field("address"), "is required"));
+        }
+
+        if let (Some(minpoll), Some(maxpoll)) = (self.minpoll, self.maxpoll) {
+            if minpoll > maxpoll {
+                errors.push(FieldError::new(
+                    field("minpoll"),
+                    format!("must not be greater than maxpoll ({})", maxpoll),
+                ));
+            }
+        }
+        for (name, poll) in [("minpoll", self.minpoll), ("maxpoll", self.maxpoll)] {
+            if poll.is_some_and(|poll| !POLL_RANGE.contains(&poll)) {
+                errors.push(FieldError::new(
+                    field(name),
+                    format!(
+                        "must be between {} and {}",
+                        POLL_RANGE.start(),
+                        POLL_RANGE.end()
+                    ),
+                ));
+            }
+        }
+    }
+}
+
+/// Checks that a number of seconds or parts per million is greater than zero.
+pub(crate) fn positive(value: &f64) -> Result<(), &'static str> {
+    if *value > 0.0 && value.is_finite() {
+        Ok(())
+    } else {
+        Err("must be greater than 0")
+    }
+}
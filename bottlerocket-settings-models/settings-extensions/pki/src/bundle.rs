@@ -0,0 +1,193 @@
+//! Renders the CA certificate bundles described by [`PkiSettingsV1`].
+//!
+//! Certificates are written in the order of the names of the bundles that contain them, and then in
+//! the order they appear in each bundle. A certificate is only written once, even if several
+//! bundles contain it, so that the rendered files only change when the set of certificates does.
+use crate::PkiSettingsV1;
+use bottlerocket_modeled_types::CertificateMetadata;
+use bottlerocket_settings_sdk::helper::template_helper;
+use std::collections::HashSet;
+use std::convert::Infallible;
+
+/// Renders the certificates from every bundle with `trusted = true` as a single PEM bundle.
+///
+/// Certificates that are also in a bundle with `trusted = false` are left out, so that distrusting
+/// a certificate takes precedence over trusting it.
+pub fn render_trust_bundle(settings: &PkiSettingsV1) -> String {
+    let distrusted: HashSet<_> = certificates(settings, false)
+        .into_iter()
+        .map(CertificateMetadata::fingerprint)
+        .collect();
+    certificates(settings, true)
+        .into_iter()
+        .filter(|certificate| !distrusted.contains(certificate.fingerprint()))
+        .map(CertificateMetadata::to_pem)
+        .collect()
+}
+
+/// Renders the certificates from every bundle with `trusted = false` as a single PEM bundle, for
+/// consumers that need to remove them from another source of trusted certificates.
+pub fn render_distrust_list(settings: &PkiSettingsV1) -> String {
+    certificates(settings, false)
+        .into_iter()
+        .map(CertificateMetadata::to_pem)
+        .collect()
+}
+
+#[template_helper(ident = trust_bundle_helper, vis = "pub(crate)")]
+fn trust_bundle(settings: PkiSettingsV1) -> Result<String, Infallible> {
+    Ok(render_trust_bundle(&settings))
+}
+
+#[template_helper(ident = distrust_list_helper, vis = "pub(crate)")]
+fn distrust_list(settings: PkiSettingsV1) -> Result<String, Infallible> {
+    Ok(render_distrust_list(&settings))
+}
+
+/// Returns the certificates in the bundles whose `trusted` setting matches, without duplicates.
+/// Bundles that don't set `trusted` are treated as distrusted.
+fn certificates(settings: &PkiSettingsV1, trusted: bool) -> Vec<&CertificateMetadata> {
+    let mut seen = HashSet::new();
+    settings
+        .unique_bundles()
+        .into_iter()
+        .filter(|(_, bundle)| bundle.trusted.unwrap_or_default() == trusted)
+        .flat_map(|(_, bundle)| bundle.data.iter().flat_map(|data| data.certificates()))
+        .filter(|certificate| seen.insert(certificate.fingerprint()))
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use base64::Engine;
+    use bottlerocket_modeled_types::PemCertificateString;
+    use serde_json::json;
+
+    // Each fixture holds a single distinct certificate: two different self-signed CAs, each
+    // repeated, and a leaf signed by the CA in `test-ca-pem`.
+    static VALID_PEM: &str = include_str!("../tests/data/test-pem");
+    static CA_PEM: &str = include_str!("../tests/data/test-ca-pem");
+    static LEAF_PEM: &str = include_str!("../tests/data/test-leaf-pem");
+
+    /// Returns the fingerprints of the certificates in a base64-encoded bundle.
+    fn fingerprints(bundle: &str) -> Vec<String> {
+        PemCertificateString::try_from(bundle)
+            .unwrap()
+            .certificates()
+            .iter()
+            .map(|certificate| certificate.fingerprint().to_string())
+            .collect()
+    }
+
+    /// Returns the fingerprint of the certificate in a fixture.
+    fn fingerprint(fixture: &str) -> String {
+        fingerprints(fixture).swap_remove(0)
+    }
+
+    /// Returns the fingerprints of the certificates in a rendered PEM bundle.
+    fn rendered_fingerprints(rendered: &str) -> Vec<String> {
+        if rendered.is_empty() {
+            return Vec::new();
+        }
+        fingerprints(&base64::engine::general_purpose::STANDARD.encode(rendered))
+    }
+
+    fn settings(value: serde_json::Value) -> PkiSettingsV1 {
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn test_bundles_in_name_order() {
+        let settings = settings(json!({
+            "b-leaf": {"data": LEAF_PEM, "trusted": true},
+            "a-valid": {"data": VALID_PEM, "trusted": true},
+        }));
+        assert_eq!(
+            rendered_fingerprints(&render_trust_bundle(&settings)),
+            vec![fingerprint(VALID_PEM), fingerprint(LEAF_PEM)]
+        );
+        assert_eq!(render_distrust_list(&settings), "");
+    }
+
+    #[test]
+    fn test_repeated_certificates_written_once() {
+        // The CA appears twice in its own bundle, and again in a second bundle.
+        let settings = settings(json!({
+            "ca": {"data": CA_PEM, "trusted": true},
+            "ca-copy": {"data": CA_PEM, "trusted": true},
+        }));
+        let rendered = render_trust_bundle(&settings);
+        assert_eq!(rendered.matches("-----BEGIN CERTIFICATE-----").count(), 1);
+        assert_eq!(rendered_fingerprints(&rendered), vec![fingerprint(CA_PEM)]);
+    }
+
+    #[test]
+    fn test_distrust_takes_precedence() {
+        let settings = settings(json!({
+            "ca": {"data": CA_PEM, "trusted": true},
+            "leaf": {"data": LEAF_PEM, "trusted": true},
+            "revoked-ca": {"data": CA_PEM, "trusted": false},
+        }));
+        assert_eq!(
+            rendered_fingerprints(&render_trust_bundle(&settings)),
+            vec![fingerprint(LEAF_PEM)]
+        );
+        assert_eq!(
+            rendered_fingerprints(&render_distrust_list(&settings)),
+            vec![fingerprint(CA_PEM)]
+        );
+    }
+
+    #[test]
+    fn test_unset_trust_and_data() {
+        // A bundle that doesn't set `trusted` is distrusted, and one without data adds nothing.
+        let settings = settings(json!({
+            "unset": {"data": LEAF_PEM},
+            "empty": {"trusted": true},
+        }));
+        assert_eq!(render_trust_bundle(&settings), "");
+        assert_eq!(
+            rendered_fingerprints(&render_distrust_list(&settings)),
+            vec![fingerprint(LEAF_PEM)]
+        );
+
+        let settings = PkiSettingsV1::default();
+        assert_eq!(render_trust_bundle(&settings), "");
+        assert_eq!(render_distrust_list(&settings), "");
+    }
+
+    #[test]
+    fn test_rendered_pem_format() {
+        let rendered = render_trust_bundle(&settings(json!({
+            "leaf": {"data": LEAF_PEM, "trusted": true},
+        })));
+        let lines: Vec<_> = rendered.lines().collect();
+        assert_eq!(lines.first(), Some(&"-----BEGIN CERTIFICATE-----"));
+        assert_eq!(lines.last(), Some(&"-----END CERTIFICATE-----"));
+        assert!(lines.iter().all(|line| line.len() <= 64));
+        assert!(rendered.ends_with("-----END CERTIFICATE-----\n"));
+    }
+
+    #[test]
+    fn test_helpers() {
+        let settings = json!({
+            "trusted": {"data": LEAF_PEM, "trusted": true},
+            "distrusted": {"data": VALID_PEM, "trusted": false},
+        });
+        let trust_bundle = trust_bundle_helper(vec![settings.clone()]).unwrap();
+        assert_eq!(
+            rendered_fingerprints(trust_bundle.as_str().unwrap()),
+            vec![fingerprint(LEAF_PEM)]
+        );
+        let distrust_list = distrust_list_helper(vec![settings]).unwrap();
+        assert_eq!(
+            rendered_fingerprints(distrust_list.as_str().unwrap()),
+            vec![fingerprint(VALID_PEM)]
+        );
+
+        // The helpers take exactly one argument, which must be the pki settings.
+        assert!(trust_bundle_helper(vec![]).is_err());
+        assert!(distrust_list_helper(vec![json!({"ca": {"data": "not a bundle"}})]).is_err());
+    }
+}
@@ -1,10 +1,16 @@
 //! Settings related to Custom CA Certificates.
 use bottlerocket_model_derive::model;
-use bottlerocket_modeled_types::{Identifier, PemCertificateString};
-use bottlerocket_settings_sdk::{GenerateResult, SettingsModel};
+use bottlerocket_modeled_types::{CertificateValidity, Identifier, PemCertificateString};
+use bottlerocket_settings_sdk::{
+    provide_template_helpers, FieldChecks, FieldError, FieldPath, GenerateResult, HelperDef,
+    KnownNames, Names, PathSegment, SettingsModel, ValidateFields,
+};
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::time::SystemTime;
 use std::{collections::HashMap, convert::Infallible};
 
+pub mod bundle;
+
 #[derive(Clone, Debug, Default, PartialEq)]
 pub struct PkiSettingsV1 {
     pub pki: HashMap<Identifier, PemCertificate>,
@@ -29,12 +35,127 @@ impl<'de> Deserialize<'de> for PkiSettingsV1 {
     }
 }
 
+impl KnownNames for PkiSettingsV1 {
+    fn known_names(path: &[PathSegment]) -> Option<Names> {
+        HashMap::<Identifier, PemCertificate>::known_names(path)
+    }
+}
+
+impl ValidateFields for PkiSettingsV1 {
+    fn collect_field_errors(&self, path: &FieldPath, errors: &mut Vec<FieldError>) {
+        self.pki.collect_field_errors(path, errors);
+    }
+}
+
+impl PkiSettingsV1 {
+    /// Returns the bundles sorted by name, skipping any bundle whose certificates and trust are the
+    /// same as an earlier one's.
+    pub fn unique_bundles(&self) -> Vec<(&Identifier, &PemCertificate)> {
+        let mut bundles: Vec<_> = self.pki.iter().collect();
+        bundles.sort_by_key(|(name, _)| *name);
+
+        let mut seen = Vec::new();
+        bundles.retain(|(_, bundle)| {
+            let key = (bundle.trusted, bundle.fingerprints());
+            if seen.contains(&key) {
+                false
+            } else {
+                seen.push(key);
+                true
+            }
+        });
+        bundles
+    }
+
+    /// Returns warnings about certificates which aren't valid at `time`, and about bundles that
+    /// repeat an earlier bundle.  A certificate that appears more than once in a bundle is only
+    /// warned about once.
+    fn warnings_at(&self, time: SystemTime) -> Vec<FieldError> {
+        let mut names: Vec<_> = self.pki.keys().collect();
+        names.sort();
+
+        let unique = self.unique_bundles();
+        let mut warnings = Vec::new();
+        for name in names {
+            let Some(bundle) = self.pki.get(name) else {
+                continue;
+            };
+            let path = FieldPath::new([name.to_string()]).join("data");
+            let mut seen = Vec::new();
+            for certificate in bundle.data.iter().flat_map(|data| data.certificates()) {
+                if seen.contains(&certificate.fingerprint()) {
+                    continue;
+                }
+                seen.push(certificate.fingerprint());
+                let problem = match certificate.validity_at(time) {
+                    CertificateValidity::Valid => continue,
+                    CertificateValidity::NotYetValid => "is not valid yet",
+                    CertificateValidity::Expired => "has expired",
+                };
+                warnings.push(FieldError::new(
+                    &path,
+                    format!(
+                        "contains a certificate for '{}' which {}",
+                        certificate.subject(),
+                        problem
+                    ),
+                ));
+            }
+
+            if !unique.iter().any(|(unique_name, _)| *unique_name == name) {
+                if let Some((original, _)) = unique.iter().find(|(_, original)| {
+                    original.trusted == bundle.trusted
+                        && original.fingerprints() == bundle.fingerprints()
+                }) {
+                    warnings.push(FieldError::new(
+                        &path,
+                        format!("contains the same certificates as '{}'", original),
+                    ));
+                }
+            }
+        }
+        warnings
+    }
+}
+
 #[model(impl_default = true)]
 struct PemCertificate {
     data: PemCertificateString,
     trusted: bool,
 }
 
+impl PemCertificate {
+    /// Returns the fingerprint of each certificate in the bundle, in order.
+    fn fingerprints(&self) -> Vec<&str> {
+        self.data
+            .iter()
+            .flat_map(|data| data.certificates())
+            .map(|certificate| certificate.fingerprint())
+            .collect()
+    }
+}
+
+impl ValidateFields for PemCertificate {
+    fn collect_field_errors(&self, path: &FieldPath, errors: &mut Vec<FieldError>) {
+        // Only CA certificates can be used to verify other certificates, so trusting anything
+        // else would have no effect.
+        if self.trusted != Some(true) {
+            return;
+        }
+        for certificate in self.data.iter().flat_map(|data| data.certificates()) {
+            if !certificate.is_ca() {
+                errors.push(FieldError::new(
+                    path.join("data"),
+                    format!(
+                        "contains a certificate for '{}' which is not a CA, so can't be trusted",
+                        certificate.subject()
+                    ),
+                ));
+            }
+        }
+    }
+}
+
 type Result<T> = std::result::Result<T, Infallible>;
 
 impl SettingsModel for PkiSettingsV1 {
@@ -63,6 +184,23 @@ impl SettingsModel for PkiSettingsV1 {
         // Validate anything that parses as PkiSettingsV1.
         Ok(())
     }
+
+    fn field_checks() -> FieldChecks<Self> {
+        FieldChecks::declared()
+    }
+
+    fn warnings(&self) -> Vec<FieldError> {
+        self.warnings_at(SystemTime::now())
+    }
+
+    fn template_helpers() -> Result<HashMap<String, Box<dyn HelperDef>>> {
+        use bundle::{distrust_list_helper, trust_bundle_helper};
+
+        Ok(provide_template_helpers! {
+            "trust_bundle" => trust_bundle_helper,
+            "distrust_list" => distrust_list_helper,
+        })
+    }
 }
 
 #[cfg(test)]
@@ -71,6 +209,20 @@ mod test {
     use serde_json::json;
 
     static VALID_PEM: &str = include_str!("../tests/data/test-pem");
+    // A CA certificate, repeated, and a leaf certificate it signed, both valid from 2020 to 2120.
+    static CA_PEM: &str = include_str!("../tests/data/test-ca-pem");
+    static LEAF_PEM: &str = include_str!("../tests/data/test-leaf-pem");
+
+    /// Returns the time `seconds` after the Unix epoch, so that checks don't depend on the clock.
+    fn time(seconds: u64) -> SystemTime {
+        SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(seconds)
+    }
+
+    /// A time within the validity window of the certificates in `test-ca-pem` and
+    /// `test-leaf-pem`.
+    fn valid_time() -> SystemTime {
+        time(1_700_000_000)
+    }
 
     #[test]
     fn test_generate_pki_settings() {
@@ -112,4 +264,115 @@ mod test {
 
         assert_eq!(serialized_json, test_json);
     }
+
+    #[test]
+    fn test_trusted_certificates_must_be_cas() {
+        let pki: PkiSettingsV1 = serde_json::from_value(json!({
+            "ca": {"data": CA_PEM, "trusted": true},
+            "distrusted-leaf": {"data": LEAF_PEM, "trusted": false},
+            "leaf": {"data": LEAF_PEM, "trusted": true},
+        }))
+        .unwrap();
+
+        assert_eq!(
+            PkiSettingsV1::field_checks()
+                .check_fields(&pki)
+                .unwrap_err()
+                .to_string(),
+            "'leaf.data' contains a certificate for 'C=US, O=Bottlerocket, \
+             CN=leaf.bottlerocket.aws' which is not a CA, so can't be trusted"
+        );
+    }
+
+    #[test]
+    fn test_validity_warnings() {
+        let pki: PkiSettingsV1 = serde_json::from_value(json!({
+            "ca": {"data": CA_PEM, "trusted": true},
+            "leaf": {"data": LEAF_PEM, "trusted": false},
+        }))
+        .unwrap();
+        assert!(pki.warnings_at(valid_time()).is_empty());
+
+        // 2019 and 2121, on either side of the certificates' validity window.
+        for (time, problem) in [
+            (time(1_550_000_000), "is not valid yet"),
+            (time(4_800_000_000), "has expired"),
+        ] {
+            let warnings: Vec<_> = pki
+                .warnings_at(time)
+                .iter()
+                .map(ToString::to_string)
+                .collect();
+            // The CA bundle contains the same certificate twice, which is only reported once.
+            assert_eq!(
+                warnings,
+                vec![
+                    format!(
+                        "'ca.data' contains a certificate for 'CN=bottlerocket, C=US, \
+                         L=WASHINGTON' which {}",
+                        problem
+                    ),
+                    format!(
+                        "'leaf.data' contains a certificate for 'C=US, O=Bottlerocket, \
+                         CN=leaf.bottlerocket.aws' which {}",
+                        problem
+                    ),
+                ]
+            );
+        }
+    }
+
+    #[test]
+    fn test_duplicate_bundles() {
+        let pki: PkiSettingsV1 = serde_json::from_value(json!({
+            "b": {"data": CA_PEM, "trusted": true},
+            "a": {"data": CA_PEM, "trusted": true},
+            "c": {"data": CA_PEM, "trusted": false},
+            "d": {"data": CA_PEM, "trusted": true},
+        }))
+        .unwrap();
+
+        let unique: Vec<_> = pki
+            .unique_bundles()
+            .into_iter()
+            .map(|(name, _)| name.to_string())
+            .collect();
+        assert_eq!(unique, vec!["a", "c"]);
+
+        let warnings: Vec<_> = pki
+            .warnings_at(valid_time())
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        assert_eq!(
+            warnings,
+            vec![
+                "'b.data' contains the same certificates as 'a'",
+                "'d.data' contains the same certificates as 'a'",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_template_helpers_listed() {
+        let config: toml::Value = toml::from_str(include_str!("../pki.toml")).unwrap();
+        let listed: Vec<_> = config
+            .get("v1")
+            .and_then(|v1| v1.get("templating"))
+            .and_then(|templating| templating.get("helpers"))
+            .and_then(toml::Value::as_array)
+            .unwrap()
+            .iter()
+            .map(|helper| helper.as_str().unwrap().to_string())
+            .collect();
+
+        let mut registered: Vec<_> = PkiSettingsV1::template_helpers()
+            .unwrap()
+            .into_keys()
+            .collect();
+        registered.sort();
+        let mut expected = listed.clone();
+        expected.sort();
+        assert_eq!(registered, expected);
+    }
 }
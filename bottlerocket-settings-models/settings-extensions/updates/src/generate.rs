@@ -1,7 +1,10 @@
 /// Generators for updates settings.
-use rand::{thread_rng, Rng};
+use bottlerocket_settings_sdk::environment::RandomProvider;
 
-pub fn generate_seed() -> u32 {
-    let mut rng = thread_rng();
-    rng.gen_range(0..2048)
+/// The number of possible update seeds; seeds are in the range `0..SEED_RANGE`.
+const SEED_RANGE: u64 = 2048;
+
+pub fn generate_seed(random: &dyn RandomProvider) -> u32 {
+    // The range fits in a u32, so the conversion can't fail.
+    u32::try_from(random.random_range(0..SEED_RANGE)).unwrap_or_default()
 }
@@ -4,13 +4,15 @@ pub mod generate;
 
 use bottlerocket_model_derive::model;
 use bottlerocket_modeled_types::{FriendlyVersion, Url};
-use bottlerocket_settings_sdk::{GenerateResult, SettingsModel};
+use bottlerocket_settings_sdk::{Environment, FieldChecks, GenerateResult, SettingsModel};
 use std::convert::Infallible;
 
 #[model(impl_default = true)]
 pub struct UpdatesSettingsV1 {
     metadata_base_url: Url,
     targets_base_url: Url,
+    // The seed determines the instance's update wave, so it must not change once established.
+    #[immutable]
     seed: u32,
     // Version to update to when updating via the API.
     version_lock: FriendlyVersion,
@@ -33,13 +35,29 @@ impl SettingsModel for UpdatesSettingsV1 {
     }
 
     fn generate(
+        existing_partial: Option<Self::PartialKind>,
+        dependent_settings: Option<serde_json::Value>,
+    ) -> Result<GenerateResult<Self::PartialKind, Self>> {
+        Self::generate_with_environment(
+            existing_partial,
+            dependent_settings,
+            &Environment::default(),
+        )
+    }
+
+    fn generate_with_environment(
         existing_partial: Option<Self::PartialKind>,
         _dependent_settings: Option<serde_json::Value>,
+        environment: &Environment,
     ) -> Result<GenerateResult<Self::PartialKind, Self>> {
         let partial = existing_partial.unwrap_or_default();
 
         Ok(GenerateResult::Complete(UpdatesSettingsV1 {
-            seed: Some(partial.seed.unwrap_or_else(generate::generate_seed)),
+            seed: Some(
+                partial
+                    .seed
+                    .unwrap_or_else(|| generate::generate_seed(environment.random())),
+            ),
             ..partial
         }))
     }
@@ -47,11 +65,19 @@ impl SettingsModel for UpdatesSettingsV1 {
     fn validate(_value: Self, _validated_settings: Option<serde_json::Value>) -> Result<()> {
         Ok(())
     }
+
+    fn field_checks() -> FieldChecks<Self> {
+        FieldChecks::declared()
+    }
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
+    use bottlerocket_settings_sdk::environment::stand_in::FixedRandom;
+    use bottlerocket_settings_sdk::model::TypeErasedModel;
+    use bottlerocket_settings_sdk::BottlerocketSetting;
+    use serde_json::json;
 
     #[test]
     fn test_generate_updates() {
@@ -68,6 +94,33 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_generate_seed_from_environment() {
+        let environment = Environment::in_memory().with_random(FixedRandom::new([1234]));
+        let generated =
+            UpdatesSettingsV1::generate_with_environment(None, None, &environment).unwrap();
+        assert_eq!(
+            generated,
+            GenerateResult::Complete(UpdatesSettingsV1 {
+                seed: Some(1234),
+                ..Default::default()
+            })
+        );
+
+        // An existing seed is kept.
+        let existing = UpdatesSettingsV1 {
+            seed: Some(7),
+            ..Default::default()
+        };
+        let generated =
+            UpdatesSettingsV1::generate_with_environment(Some(existing), None, &environment)
+                .unwrap();
+        assert!(matches!(
+            generated,
+            GenerateResult::Complete(UpdatesSettingsV1 { seed: Some(7), .. })
+        ));
+    }
+
     #[test]
     fn test_serde_updates() {
         let test_json = r#"{
@@ -91,4 +144,20 @@ mod test {
             }
         );
     }
+
+    #[test]
+    fn test_seed_is_immutable() {
+        let model = BottlerocketSetting::<UpdatesSettingsV1>::model();
+
+        assert!(model.set(None, json!({"seed": 1})).is_ok());
+        assert!(model
+            .set(
+                Some(json!({"seed": 1})),
+                json!({"seed": 1, "ignore-waves": true})
+            )
+            .is_ok());
+        assert!(model
+            .set(Some(json!({"seed": 1})), json!({"seed": 2}))
+            .is_err());
+    }
 }
@@ -28,20 +28,24 @@ pub use crate::boot::BootSettingsV1;
 pub use settings_extension_autoscaling::{self, AutoScalingSettingsV1};
 pub use settings_extension_aws::{self, AwsSettingsV1};
 pub use settings_extension_bootstrap_commands::{self, BootstrapCommandsSettingsV1};
-pub use settings_extension_bootstrap_containers::{self, BootstrapContainersSettingsV1};
+pub use settings_extension_bootstrap_containers::{
+    self, BootstrapContainersSettingsV1, BootstrapContainersSettingsV2,
+};
 pub use settings_extension_cloudformation::{self, CloudFormationSettingsV1};
-pub use settings_extension_container_registry::{self, RegistrySettingsV1};
+pub use settings_extension_container_registry::{self, RegistrySettingsV1, RegistrySettingsV2};
 pub use settings_extension_container_runtime::{self, ContainerRuntimeSettingsV1};
 pub use settings_extension_dns::{self, DnsSettingsV1};
 pub use settings_extension_ecs::{self, ECSSettingsV1};
-pub use settings_extension_host_containers::{self, HostContainersSettingsV1};
+pub use settings_extension_host_containers::{
+    self, HostContainersSettingsV1, HostContainersSettingsV2,
+};
 pub use settings_extension_kernel::{self, KernelSettingsV1};
 pub use settings_extension_kubelet_device_plugins::{self, KubeletDevicePluginsV1};
-pub use settings_extension_kubernetes::{self, KubernetesSettingsV1};
+pub use settings_extension_kubernetes::{self, KubernetesSettingsV1, KubernetesSettingsV2};
 pub use settings_extension_metrics::{self, MetricsSettingsV1};
 pub use settings_extension_motd::{self, MotdV1};
-pub use settings_extension_network::{self, NetworkSettingsV1};
-pub use settings_extension_ntp::{self, NtpSettingsV1};
+pub use settings_extension_network::{self, NetworkSettingsV1, NetworkSettingsV2};
+pub use settings_extension_ntp::{self, NtpSettingsV1, NtpSettingsV2};
 pub use settings_extension_nvidia_container_runtime::{self, NvidiaContainerRuntimeSettingsV1};
 pub use settings_extension_oci_defaults::{self, OciDefaultsV1};
 pub use settings_extension_oci_hooks::{self, OciHooksSettingsV1};
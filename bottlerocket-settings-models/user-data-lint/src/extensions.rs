@@ -0,0 +1,314 @@
+//! Maps each `[settings.<extension>]` table to the model used to check it.
+use crate::{Finding, Severity};
+use bottlerocket_deserialize_helper::PathSegment;
+use bottlerocket_settings_models::*;
+use bottlerocket_settings_sdk::{
+    CrossValidationError, DeprecationWarning, FieldError, FieldErrors, KnownNames, SettingsModel,
+    ValidatedSettings,
+};
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::Value;
+
+/// Checks the value given for a settings extension against the rest of the settings in the
+/// user-data, returning each problem found.
+pub(crate) type Check = fn(Value, &ValidatedSettings) -> Vec<Finding>;
+
+/// A settings extension, and the check for each version of its model.
+pub(crate) struct Extension {
+    /// The name used in `[settings.<extension>]`.
+    name: &'static str,
+    /// The extension's configuration file, which names the version used for its settings.
+    /// Settings without a configuration file have a single version.
+    config: Option<&'static str>,
+    /// The check for each version of the extension's model.
+    versions: &'static [(&'static str, Check)],
+}
+
+/// The part of an extension's configuration file that names its default version.
+#[derive(Deserialize)]
+struct ExtensionConfig {
+    extension: ExtensionInfo,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct ExtensionInfo {
+    default_version: String,
+}
+
+impl Extension {
+    /// Returns the check for the version of the model that's used for the extension's settings,
+    /// if there is one.
+    fn default_check(&self) -> Option<Check> {
+        let config = match self.config {
+            Some(config) => config,
+            None => return self.versions.first().map(|(_, check)| *check),
+        };
+        let config: ExtensionConfig = toml::from_str(config).ok()?;
+        self.versions
+            .iter()
+            .find(|(version, _)| *version == config.extension.default_version)
+            .map(|(_, check)| *check)
+    }
+}
+
+/// Defines an extension with the configuration file from its directory in
+/// `settings-extensions`, and the model for each of its versions.
+macro_rules! extension {
+    ($name:literal, [$(($version:literal, $model:ty)),+ $(,)?]) => {
+        Extension {
+            name: $name,
+            config: Some(include_str!(concat!(
+                "../../settings-extensions/",
+                $name,
+                "/",
+                $name,
+                ".toml"
+            ))),
+            versions: &[$(($version, check_model::<$model>)),+],
+        }
+    };
+}
+
+/// Each known settings extension.
+pub(crate) const EXTENSIONS: &[Extension] = &[
+    extension!("autoscaling", [("v1", AutoScalingSettingsV1)]),
+    extension!("aws", [("v1", AwsSettingsV1)]),
+    Extension {
+        name: "boot",
+        config: None,
+        versions: &[("v1", check_boot)],
+    },
+    extension!("bootstrap-commands", [("v1", BootstrapCommandsSettingsV1)]),
+    extension!(
+        "bootstrap-containers",
+        [
+            ("v1", BootstrapContainersSettingsV1),
+            ("v2", BootstrapContainersSettingsV2),
+        ]
+    ),
+    extension!("cloudformation", [("v1", CloudFormationSettingsV1)]),
+    extension!(
+        "container-registry",
+        [("v1", RegistrySettingsV1), ("v2", RegistrySettingsV2)]
+    ),
+    extension!("container-runtime", [("v1", ContainerRuntimeSettingsV1)]),
+    extension!("dns", [("v1", DnsSettingsV1)]),
+    extension!("ecs", [("v1", ECSSettingsV1)]),
+    extension!(
+        "host-containers",
+        [
+            ("v1", HostContainersSettingsV1),
+            ("v2", HostContainersSettingsV2),
+        ]
+    ),
+    extension!("kernel", [("v1", KernelSettingsV1)]),
+    extension!("kubelet-device-plugins", [("v1", KubeletDevicePluginsV1)]),
+    extension!(
+        "kubernetes",
+        [("v1", KubernetesSettingsV1), ("v2", KubernetesSettingsV2)]
+    ),
+    extension!("metrics", [("v1", MetricsSettingsV1)]),
+    extension!("motd", [("v1", MotdV1)]),
+    extension!(
+        "network",
+        [("v1", NetworkSettingsV1), ("v2", NetworkSettingsV2)]
+    ),
+    extension!("ntp", [("v1", NtpSettingsV1), ("v2", NtpSettingsV2)]),
+    extension!(
+        "nvidia-container-runtime",
+        [("v1", NvidiaContainerRuntimeSettingsV1)]
+    ),
+    extension!("oci-defaults", [("v1", OciDefaultsV1)]),
+    extension!("oci-hooks", [("v1", OciHooksSettingsV1)]),
+    extension!("pki", [("v1", PkiSettingsV1)]),
+    extension!("updates", [("v1", UpdatesSettingsV1)]),
+];
+
+/// Returns the check for the named settings extension's default version, if the extension is
+/// known.
+pub(crate) fn find(name: &str) -> Option<Check> {
+    EXTENSIONS
+        .iter()
+        .find(|extension| extension.name == name)
+        .and_then(Extension::default_check)
+}
+
+/// Returns the names of all known settings extensions.
+pub(crate) fn names() -> Vec<&'static str> {
+    EXTENSIONS.iter().map(|extension| extension.name).collect()
+}
+
+/// Checks a value against a settings extension's model, including its deprecated names, field
+/// constraints, the model's own validation, and any warnings the model reports about the value.
+fn check_model<T: SettingsModel>(value: Value, settings: &ValidatedSettings) -> Vec<Finding> {
+    let checks = T::field_checks();
+    let mut findings = deprecations(checks.find_deprecated_aliases(&value));
+    let model = match deserialize::<T>(value, &mut findings) {
+        Some(model) => model,
+        None => return findings,
+    };
+
+    if let Err(errors) = checks.check_fields(&model) {
+        findings.extend(field_findings(&errors));
+    }
+    findings.extend(
+        model
+            .warnings()
+            .iter()
+            .map(|warning| field_finding(Severity::Warning, warning)),
+    );
+    if let Err(e) = T::validate(model, settings.as_value().cloned()) {
+        findings.extend(validation_findings(e.into().as_ref()));
+    }
+    findings
+}
+
+/// Deserializes a value, adding a finding for every field that can't be deserialized.
+///
+/// serde stops at the first problem, so each field at fault is removed from the value before trying
+/// again. Returns the model only if the whole value could be deserialized.
+fn deserialize<T: DeserializeOwned + KnownNames>(
+    mut value: Value,
+    findings: &mut Vec<Finding>,
+) -> Option<T> {
+    // The paths removed so far, with indexes as they were given by the user.
+    let mut removed: Vec<Vec<PathSegment>> = Vec::new();
+    loop {
+        let e = match bottlerocket_deserialize_helper::from_model_value::<T>(value.clone()) {
+            Ok(model) => return removed.is_empty().then_some(model),
+            Err(e) => e,
+        };
+        let segments = original_segments(e.segments(), &removed);
+        // A problem with a value that holds a removed field may only be caused by its removal.
+        if removed.iter().any(|path| path.starts_with(&segments)) {
+            return None;
+        }
+        let found = remove(&mut value, e.segments());
+        findings.push(Finding {
+            segments: segments.clone(),
+            ..Finding::from(e)
+        });
+        if !found {
+            return None;
+        }
+        removed.push(segments);
+    }
+}
+
+/// Maps a path within a value that has had fields removed back to the path given by the user, by
+/// accounting for the array elements removed before each index.
+fn original_segments(segments: &[PathSegment], removed: &[Vec<PathSegment>]) -> Vec<PathSegment> {
+    let mut original: Vec<PathSegment> = Vec::with_capacity(segments.len());
+    for segment in segments {
+        let segment = match segment {
+            PathSegment::Index(index) => {
+                let mut earlier: Vec<usize> = removed
+                    .iter()
+                    .filter_map(|path| match path.split_last() {
+                        Some((PathSegment::Index(removed), parent)) if parent == original => {
+                            Some(*removed)
+                        }
+                        _ => None,
+                    })
+                    .collect();
+                earlier.sort_unstable();
+                let index = earlier.into_iter().fold(*index, |index, removed| {
+                    index + usize::from(removed <= index)
+                });
+                PathSegment::Index(index)
+            }
+            key => key.clone(),
+        };
+        original.push(segment);
+    }
+    original
+}
+
+/// Removes the field or element at `segments` from the value, returning whether it was found.
+fn remove(value: &mut Value, segments: &[PathSegment]) -> bool {
+    let Some((last, parents)) = segments.split_last() else {
+        return false;
+    };
+    let mut parent = value;
+    for segment in parents {
+        let next = match segment {
+            PathSegment::Key(key) => parent.get_mut(key),
+            PathSegment::Index(index) => parent.get_mut(index),
+        };
+        match next {
+            Some(next) => parent = next,
+            None => return false,
+        }
+    }
+    match (last, parent) {
+        (PathSegment::Key(key), Value::Object(map)) => map.remove(key).is_some(),
+        (PathSegment::Index(index), Value::Array(array)) if *index < array.len() => {
+            array.remove(*index);
+            true
+        }
+        _ => false,
+    }
+}
+
+/// Builds findings for an error returned by a model's `validate`, locating any field errors it
+/// holds.
+///
+/// Settings left out of user-data are generated at boot, and malformed settings are reported by
+/// their own extension, so errors reading the settings the model is validated against are ignored.
+fn validation_findings(error: &(dyn std::error::Error + 'static)) -> Vec<Finding> {
+    let mut chain = std::iter::successors(Some(error), |error| error.source());
+    if chain
+        .clone()
+        .any(|error| error.is::<CrossValidationError>())
+    {
+        return Vec::new();
+    }
+    let field_errors = chain.find_map(|error| error.downcast_ref::<FieldErrors>());
+    match field_errors {
+        Some(errors) => field_findings(errors).collect(),
+        None => vec![Finding::error(Vec::new(), error.to_string())],
+    }
+}
+
+/// Builds a finding for each field constraint that was violated.
+fn field_findings(errors: &FieldErrors) -> impl Iterator<Item = Finding> + '_ {
+    errors
+        .errors()
+        .iter()
+        .map(|error| field_finding(Severity::Error, error))
+}
+
+/// Builds a finding for a problem the model reported at a field's path.
+fn field_finding(severity: Severity, error: &FieldError) -> Finding {
+    Finding {
+        severity,
+        segments: error.path().segments().to_vec(),
+        message: error.message().to_string(),
+        suggestions: Vec::new(),
+    }
+}
+
+/// Checks a value against the boot settings, which aren't provided by a settings extension.
+fn check_boot(value: Value, _settings: &ValidatedSettings) -> Vec<Finding> {
+    let mut findings = Vec::new();
+    deserialize::<BootSettingsV1>(value, &mut findings);
+    findings
+}
+
+/// Returns a warning for each deprecated field name used in the value.
+fn deprecations(warnings: Vec<DeprecationWarning>) -> Vec<Finding> {
+    warnings
+        .into_iter()
+        .map(|warning| Finding {
+            severity: Severity::Warning,
+            segments: warning.alias().segments().to_vec(),
+            message: match warning.name().segments().last() {
+                Some(name) => format!("deprecated; use '{}' instead", name),
+                None => "deprecated".to_string(),
+            },
+            suggestions: Vec::new(),
+        })
+        .collect()
+}
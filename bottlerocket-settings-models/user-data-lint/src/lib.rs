@@ -0,0 +1,577 @@
+/*!
+This library checks Bottlerocket user-data against the settings models, so that mistakes can be
+caught before an instance boots with them.
+
+Each `[settings.<extension>]` table is deserialized with the model from
+`bottlerocket-settings-models`, using the version that the extension's configuration names as its
+default, then checked by the model's field constraints and validation, with the other settings in
+the user-data available for cross-validation.
+Every problem is reported as a [`Diagnostic`] with the line and column where it appears.
+Unknown keys come with suggestions for the known names they most closely match, and the use of
+deprecated names is reported as a warning.
+
+```
+let diagnostics = bottlerocket_user_data_lint::lint(
+    r#"
+[settings.kubernetes]
+cluster-nme = "my-cluster"
+"#,
+);
+
+assert_eq!(diagnostics.len(), 1);
+let diagnostic = diagnostics.first().unwrap();
+assert_eq!((diagnostic.line(), diagnostic.column()), (3, 1));
+assert_eq!(diagnostic.path(), "settings.kubernetes.cluster-nme");
+assert_eq!(diagnostic.suggestions(), &["cluster-name"]);
+```
+
+The `user-data-lint` binary runs these checks against files, exiting non-zero if any errors are
+found.
+*/
+
+mod extensions;
+mod location;
+
+use bottlerocket_deserialize_helper::{DeserializeError, PathSegment};
+use bottlerocket_settings_sdk::ValidatedSettings;
+use std::fmt::{self, Display, Formatter};
+use std::ops::Range;
+use toml_edit::ImDocument;
+
+/// The top-level key under which all settings are given.
+const SETTINGS_KEY: &str = "settings";
+
+/// Checks user-data TOML, returning every problem found in the order it appears.
+pub fn lint(source: &str) -> Vec<Diagnostic> {
+    let document = match ImDocument::parse(source) {
+        Ok(document) => document,
+        Err(e) => {
+            return vec![Diagnostic::new(
+                source,
+                e.span(),
+                Finding::error(Vec::new(), e.message().trim_end()),
+            )]
+        }
+    };
+    // `toml_edit` only tracks where values appear, so the values themselves are read with `toml`,
+    // which can convert them to JSON for the models.
+    let user_data: toml::Table = match toml::from_str(source) {
+        Ok(user_data) => user_data,
+        Err(e) => {
+            return vec![Diagnostic::new(
+                source,
+                e.span(),
+                Finding::error(Vec::new(), e.message().trim_end()),
+            )]
+        }
+    };
+
+    let mut findings = Vec::new();
+    for (key, value) in user_data {
+        let segments = vec![PathSegment::Key(key.clone())];
+        if key != SETTINGS_KEY {
+            findings.push(Finding {
+                suggestions: bottlerocket_deserialize_helper::suggest(&key, &[SETTINGS_KEY]),
+                ..Finding::error(segments, "unknown top-level key")
+            });
+            continue;
+        }
+
+        let settings = match value {
+            toml::Value::Table(settings) => settings,
+            _ => {
+                findings.push(Finding::error(segments, "must be a table"));
+                continue;
+            }
+        };
+        // Each extension is cross-validated against all of the settings given in the user-data.
+        let validated_settings = ValidatedSettings::new(serde_json::to_value(&settings).ok());
+        for (name, value) in settings {
+            findings.extend(check_extension(name, value, &validated_settings));
+        }
+    }
+
+    let mut diagnostics: Vec<Diagnostic> = findings
+        .into_iter()
+        .map(|finding| {
+            let span = location::locate(&document, &finding.segments);
+            Diagnostic::new(source, span, finding)
+        })
+        .collect();
+    diagnostics.sort_by_key(|diagnostic| (diagnostic.line, diagnostic.column));
+    diagnostics
+}
+
+/// Returns whether any of the diagnostics are errors, rather than warnings.
+pub fn has_errors(diagnostics: &[Diagnostic]) -> bool {
+    diagnostics
+        .iter()
+        .any(|diagnostic| diagnostic.severity == Severity::Error)
+}
+
+/// Checks the value given in `[settings.<name>]`, returning findings located from the root of
+/// the user-data.
+fn check_extension(
+    name: String,
+    value: toml::Value,
+    validated_settings: &ValidatedSettings,
+) -> Vec<Finding> {
+    let base = vec![
+        PathSegment::Key(SETTINGS_KEY.to_string()),
+        PathSegment::Key(name.clone()),
+    ];
+    let check = match extensions::find(&name) {
+        Some(check) => check,
+        None => {
+            return vec![Finding {
+                suggestions: bottlerocket_deserialize_helper::suggest(&name, &extensions::names()),
+                ..Finding::error(base, "unknown settings extension")
+            }]
+        }
+    };
+    let value = match serde_json::to_value(value) {
+        Ok(value) => value,
+        Err(e) => return vec![Finding::error(base, e.to_string())],
+    };
+
+    check(value, validated_settings)
+        .into_iter()
+        .map(|finding| Finding {
+            segments: base.iter().cloned().chain(finding.segments).collect(),
+            ..finding
+        })
+        .collect()
+}
+
+/// How serious a problem is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    /// The user-data would be rejected.
+    Error,
+    /// The user-data would be accepted, but should be changed.
+    Warning,
+}
+
+impl Display for Severity {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Severity::Error => f.write_str("error"),
+            Severity::Warning => f.write_str("warning"),
+        }
+    }
+}
+
+/// A problem found in user-data, along with where it appears.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    severity: Severity,
+    line: usize,
+    column: usize,
+    path: String,
+    message: String,
+    suggestions: Vec<String>,
+}
+
+impl Diagnostic {
+    fn new(source: &str, span: Option<Range<usize>>, finding: Finding) -> Self {
+        let (line, column) = location::line_column(source, span.map_or(0, |span| span.start));
+        Self {
+            severity: finding.severity,
+            line,
+            column,
+            path: display_path(&finding.segments),
+            message: finding.message,
+            suggestions: finding.suggestions,
+        }
+    }
+
+    /// Returns how serious the problem is.
+    pub fn severity(&self) -> Severity {
+        self.severity
+    }
+
+    /// Returns the 1-based line where the problem appears.
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    /// Returns the 1-based column where the problem appears.
+    pub fn column(&self) -> usize {
+        self.column
+    }
+
+    /// Returns the dotted path of the offending key, e.g. `settings.kubernetes.cluster-name`, or
+    /// an empty string if the problem isn't with a particular key.
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// Returns a description of the problem.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// Returns the known names that most closely match an unknown key, closest first.
+    pub fn suggestions(&self) -> &[String] {
+        &self.suggestions
+    }
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}: {}: ", self.line, self.column, self.severity)?;
+        if !self.path.is_empty() {
+            write!(f, "'{}' ", self.path)?;
+        }
+        write!(f, "{}", self.message)?;
+        match self.suggestions.as_slice() {
+            [] => Ok(()),
+            [suggestion] => write!(f, " (did you mean '{}'?)", suggestion),
+            suggestions => write!(f, " (did you mean one of '{}'?)", suggestions.join("', '")),
+        }
+    }
+}
+
+/// A problem found in a settings value, located by its path from the root of the user-data.
+#[derive(Debug)]
+pub(crate) struct Finding {
+    severity: Severity,
+    segments: Vec<PathSegment>,
+    message: String,
+    suggestions: Vec<String>,
+}
+
+impl Finding {
+    fn error<S: Into<String>>(segments: Vec<PathSegment>, message: S) -> Self {
+        Self {
+            severity: Severity::Error,
+            segments,
+            message: message.into(),
+            suggestions: Vec::new(),
+        }
+    }
+}
+
+impl From<DeserializeError> for Finding {
+    fn from(e: DeserializeError) -> Self {
+        Self {
+            severity: Severity::Error,
+            segments: e.segments().to_vec(),
+            message: e.inner().to_string(),
+            suggestions: e.suggestions().to_vec(),
+        }
+    }
+}
+
+/// Formats a path like `settings.host-containers.admin.enabled` or `settings.ntp.time-servers[0]`.
+fn display_path(segments: &[PathSegment]) -> String {
+    let mut path = String::new();
+    for segment in segments {
+        match segment {
+            PathSegment::Key(key) => {
+                if !path.is_empty() {
+                    path.push('.');
+                }
+                path.push_str(key);
+            }
+            PathSegment::Index(index) => path.push_str(&format!("[{}]", index)),
+        }
+    }
+    path
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn single(source: &str) -> Diagnostic {
+        let diagnostics = lint(source);
+        assert_eq!(diagnostics.len(), 1, "{:?}", diagnostics);
+        diagnostics.into_iter().next().unwrap()
+    }
+
+    #[test]
+    fn test_valid_user_data() {
+        let diagnostics = lint(
+            r#"
+[settings.kubernetes]
+cluster-name = "my-cluster"
+api-server = "https://example.com"
+cluster-certificate = "Y2VydA=="
+
+[settings.host-containers.admin]
+enabled = true
+
+[settings.ntp]
+time-servers = ["169.254.169.123"]
+"#,
+        );
+        assert!(diagnostics.is_empty(), "{:?}", diagnostics);
+    }
+
+    #[test]
+    fn test_unknown_field() {
+        let diagnostic = single(
+            r#"
+[settings.kubernetes]
+cluster-name = "my-cluster"
+  api-sever = "https://example.com"
+"#,
+        );
+        assert_eq!(diagnostic.severity(), Severity::Error);
+        assert_eq!((diagnostic.line(), diagnostic.column()), (4, 3));
+        assert_eq!(diagnostic.path(), "settings.kubernetes.api-sever");
+        assert_eq!(diagnostic.suggestions(), &["api-server"]);
+        assert!(diagnostic
+            .to_string()
+            .starts_with("4:3: error: 'settings.kubernetes.api-sever' unknown field `api-sever`"));
+        assert!(diagnostic
+            .to_string()
+            .ends_with("(did you mean 'api-server'?)"));
+    }
+
+    #[test]
+    fn test_unknown_field_in_map_of_models() {
+        let diagnostic = single(
+            r#"
+[settings.host-containers.admin]
+enabeld = true
+"#,
+        );
+        assert_eq!(diagnostic.path(), "settings.host-containers.admin.enabeld");
+        assert_eq!(diagnostic.suggestions(), &["enabled"]);
+    }
+
+    #[test]
+    fn test_nested_value() {
+        let diagnostic = single(
+            r#"
+[settings.host-containers.admin]
+enabled = "yes"
+"#,
+        );
+        assert_eq!((diagnostic.line(), diagnostic.column()), (3, 1));
+        assert_eq!(diagnostic.path(), "settings.host-containers.admin.enabled");
+    }
+
+    #[test]
+    fn test_array_element() {
+        let diagnostic = single(
+            r#"
+[settings.ntp]
+time-servers = ["169.254.169.123", 42]
+"#,
+        );
+        assert_eq!((diagnostic.line(), diagnostic.column()), (3, 36));
+        assert_eq!(diagnostic.path(), "settings.ntp.time-servers[1]");
+    }
+
+    #[test]
+    fn test_unknown_extension() {
+        let diagnostic = single(
+            r#"
+[settings.kubernets]
+cluster-name = "my-cluster"
+"#,
+        );
+        assert_eq!((diagnostic.line(), diagnostic.column()), (2, 11));
+        assert_eq!(diagnostic.path(), "settings.kubernets");
+        assert_eq!(diagnostic.suggestions(), &["kubernetes"]);
+    }
+
+    #[test]
+    fn test_every_extension_has_a_default_version() {
+        for name in extensions::names() {
+            assert!(extensions::find(name).is_some(), "{}", name);
+        }
+    }
+
+    #[test]
+    fn test_default_version() {
+        // Kubernetes settings default to v1, where the pod infra container image can be any
+        // single-line string and the service CIDR isn't known.
+        let diagnostic = single(
+            r#"
+[settings.kubernetes]
+pod-infra-container-image = "not an image reference!"
+service-cidr = "10.100.0.0/16"
+"#,
+        );
+        assert_eq!(diagnostic.path(), "settings.kubernetes.service-cidr");
+    }
+
+    #[test]
+    fn test_unknown_top_level_key() {
+        let diagnostic = single("[setting.motd]\n");
+        assert_eq!(diagnostic.path(), "setting");
+        assert_eq!(diagnostic.suggestions(), &["settings"]);
+    }
+
+    #[test]
+    fn test_boot_alias() {
+        let diagnostics = lint(
+            r#"
+[settings.boot]
+reboot-to-reconcile = true
+kernel = { "console" = ["tty0"] }
+"#,
+        );
+        assert!(diagnostics.is_empty());
+    }
+
+    #[test]
+    fn test_deprecated_alias() {
+        let diagnostic = single(
+            r#"
+[[settings.container-registry.mirrors]]
+registry = "docker.io"
+endpoint = ["https://mirror.example.com"]
+
+[[settings.container-registry.creds]]
+registry = "docker.io"
+auth = "dXNlcjpwYXNz"
+"#,
+        );
+        assert_eq!(diagnostic.severity(), Severity::Warning);
+        assert_eq!(diagnostic.path(), "settings.container-registry.creds");
+        assert_eq!(
+            diagnostic.message(),
+            "deprecated; use 'credentials' instead"
+        );
+        assert_eq!((diagnostic.line(), diagnostic.column()), (6, 31));
+    }
+
+    #[test]
+    fn test_field_constraints() {
+        let diagnostic = single(
+            r#"
+[settings.kubernetes]
+standalone-mode = true
+registry-qps = -1
+"#,
+        );
+        assert_eq!((diagnostic.line(), diagnostic.column()), (4, 1));
+        assert_eq!(diagnostic.path(), "settings.kubernetes.registry-qps");
+    }
+
+    #[test]
+    fn test_every_deserialize_error() {
+        let diagnostics = lint(
+            r#"
+[settings.dns]
+name-servers = [1, "10.0.0.2", "10.0.0.3", 2]
+serch-list = ["example.com"]
+"#,
+        );
+        let paths: Vec<&str> = diagnostics.iter().map(Diagnostic::path).collect();
+        assert_eq!(
+            paths,
+            vec![
+                "settings.dns.name-servers[0]",
+                "settings.dns.name-servers[3]",
+                "settings.dns.serch-list",
+            ]
+        );
+        let columns: Vec<usize> = diagnostics.iter().map(Diagnostic::column).collect();
+        assert_eq!(columns, vec![17, 44, 1]);
+    }
+
+    #[test]
+    fn test_model_validation() {
+        let diagnostic = single(
+            r#"
+[settings.kubernetes]
+standalone-mode = true
+image-gc-high-threshold-percent = 70
+image-gc-low-threshold-percent = 80
+"#,
+        );
+        assert_eq!((diagnostic.line(), diagnostic.column()), (5, 1));
+        assert_eq!(
+            diagnostic.path(),
+            "settings.kubernetes.image-gc-low-threshold-percent"
+        );
+    }
+
+    #[test]
+    fn test_model_validation_keys_with_dots() {
+        let diagnostic = single(
+            r#"
+[settings.kubernetes]
+standalone-mode = true
+
+[settings.kubernetes.eviction-soft]
+"memory.available" = "10%"
+"#,
+        );
+        assert_eq!((diagnostic.line(), diagnostic.column()), (6, 1));
+        assert_eq!(
+            diagnostic.path(),
+            "settings.kubernetes.eviction-soft.memory.available"
+        );
+    }
+
+    #[test]
+    fn test_cross_validation() {
+        let source = r#"
+[settings.network]
+hostname = "node.us-west-2.compute.internal"
+
+[settings.dns]
+search-list = ["example.com"]
+"#;
+        let diagnostic = single(source);
+        assert_eq!(diagnostic.severity(), Severity::Error);
+        assert_eq!(diagnostic.path(), "settings.dns.search-list");
+        assert_eq!((diagnostic.line(), diagnostic.column()), (6, 1));
+
+        // The other setting's value decides whether there's a problem.
+        let diagnostics = lint(&source.replace("node.us-west-2.compute.internal", "node"));
+        assert!(diagnostics.is_empty(), "{:?}", diagnostics);
+    }
+
+    #[test]
+    fn test_missing_required_fields() {
+        let diagnostics = lint(
+            r#"
+[settings.kubernetes]
+cluster-name = "my-cluster"
+"#,
+        );
+        // Fields that aren't given are reported at the table that should hold them.
+        let found: Vec<_> = diagnostics
+            .iter()
+            .map(|diagnostic| (diagnostic.line(), diagnostic.column(), diagnostic.path()))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                (2, 11, "settings.kubernetes.api-server"),
+                (2, 11, "settings.kubernetes.cluster-certificate"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_syntax_error() {
+        let diagnostic = single("[settings.motd]\nmotd = \n");
+        assert_eq!(diagnostic.severity(), Severity::Error);
+        assert_eq!(diagnostic.line(), 2);
+        assert_eq!(diagnostic.path(), "");
+    }
+
+    #[test]
+    fn test_multiple_problems_in_order() {
+        let diagnostics = lint(
+            r#"
+[settings.ntp]
+[settings.kubernetes]
+clustr-name = "a"
+[settings.updates]
+seed = "abc"
+"#,
+        );
+        let lines: Vec<usize> = diagnostics.iter().map(Diagnostic::line).collect();
+        assert_eq!(lines, vec![4, 6]);
+    }
+}
@@ -0,0 +1,56 @@
+//! Finds where settings appear in the user-data source.
+use bottlerocket_deserialize_helper::PathSegment;
+use std::ops::Range;
+use toml_edit::{ImDocument, Item};
+
+/// Returns the span of the deepest part of `segments` that appears in the document.
+///
+/// Keys are preferred over their values, so that problems with a setting point at its name.
+pub(crate) fn locate(
+    document: &ImDocument<&str>,
+    segments: &[PathSegment],
+) -> Option<Range<usize>> {
+    let mut item = document.as_item();
+    let mut span = None;
+
+    for segment in segments {
+        let (key_span, next) = match (segment, item.as_table_like()) {
+            (PathSegment::Key(key), Some(table)) => match table.get_key_value(key) {
+                Some((key, value)) => (key.span(), value),
+                None => break,
+            },
+            // Paths from field constraints refer to indexes as keys.
+            (PathSegment::Key(key), None) => {
+                match key.parse::<usize>().ok().and_then(|i| item.get(i)) {
+                    Some(value) => (None, value),
+                    None => break,
+                }
+            }
+            (PathSegment::Index(index), _) => match item.get(*index) {
+                Some(value) => (None, value),
+                None => break,
+            },
+        };
+        span = key_span.or_else(|| item_span(next)).or(span);
+        item = next;
+    }
+
+    span
+}
+
+/// Returns the span of an item, if it was parsed from the source.
+fn item_span(item: &Item) -> Option<Range<usize>> {
+    match item {
+        Item::Value(value) => value.span(),
+        _ => item.span(),
+    }
+}
+
+/// Converts a byte offset within `source` into a 1-based line and column.
+pub(crate) fn line_column(source: &str, offset: usize) -> (usize, usize) {
+    let before = source.get(..offset).unwrap_or(source);
+    let line = before.matches('\n').count() + 1;
+    let line_start = before.rfind('\n').map(|i| i + 1).unwrap_or(0);
+    let column = before.get(line_start..).unwrap_or_default().chars().count() + 1;
+    (line, column)
+}
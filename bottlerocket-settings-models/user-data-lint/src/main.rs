@@ -0,0 +1,40 @@
+//! Checks Bottlerocket user-data files against the settings models.
+use argh::FromArgs;
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+/// Checks Bottlerocket user-data TOML files for settings that would be rejected.
+#[derive(FromArgs)]
+struct Args {
+    /// the user-data files to check
+    #[argh(positional)]
+    files: Vec<PathBuf>,
+}
+
+fn main() -> ExitCode {
+    let args: Args = argh::from_env();
+
+    let mut failed = false;
+    for file in &args.files {
+        let source = match std::fs::read_to_string(file) {
+            Ok(source) => source,
+            Err(e) => {
+                eprintln!("{}: failed to read file: {}", file.display(), e);
+                failed = true;
+                continue;
+            }
+        };
+
+        let diagnostics = bottlerocket_user_data_lint::lint(&source);
+        for diagnostic in &diagnostics {
+            println!("{}:{}", file.display(), diagnostic);
+        }
+        failed |= bottlerocket_user_data_lint::has_errors(&diagnostics);
+    }
+
+    if failed {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
+}
@@ -8,6 +8,8 @@ functions.
 // Avoid false positive improper ctypes warnings for abi_stable's PhantomData markers. We rely on
 // the StableAbi trait to catch any real problems.
 #![allow(improper_ctypes_definitions)]
+// The StableAbi derive macro emits trait impls inside of anonymous constants.
+#![allow(non_local_definitions)]
 
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use serde_json::Value as JsonValue;
@@ -150,11 +152,14 @@ impl BottlerocketDefaults for BottlerocketSettingsProvider {
 
 /// Helper function that plugins can use to implement the deserialize function.
 /// This runs on the plugin side of the FFI boundary.
+///
+/// Errors include the path of the field at fault, and suggestions for unknown field names from
+/// those the settings type knows, as implemented by `#[model]`.
 pub fn deserialize_json<'a, T>(s: RStr<'a>) -> RResult<T, RBoxError>
 where
-    T: serde::Deserialize<'a>,
+    T: serde::Deserialize<'a> + bottlerocket_deserialize_helper::KnownNames,
 {
-    match serde_json::from_str::<T>(s.into()) {
+    match bottlerocket_deserialize_helper::from_model_str::<T>(s.into()) {
         Ok(x) => ROk(x),
         Err(e) => RErr(RBoxError::new(e)),
     }
@@ -0,0 +1,36 @@
+//! Reads files from the host that the settings extension runs on.
+use super::{error, EnvironmentError, FileProvider};
+use snafu::ResultExt;
+use std::path::Path;
+
+/// Reads files from the host's filesystem.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct HostFiles;
+
+impl FileProvider for HostFiles {
+    fn read_to_string(&self, path: &Path) -> Result<Option<String>, EnvironmentError> {
+        read_optional(path)
+    }
+}
+
+/// Reads the file at `path`, returning `None` if it doesn't exist.
+pub(super) fn read_optional(path: &Path) -> Result<Option<String>, EnvironmentError> {
+    match std::fs::read_to_string(path) {
+        Ok(contents) => Ok(Some(contents)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e).context(error::ReadFileSnafu { path }),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_missing_host_file() {
+        assert!(HostFiles
+            .read_to_string(Path::new("/nonexistent/settings-sdk"))
+            .unwrap()
+            .is_none());
+    }
+}
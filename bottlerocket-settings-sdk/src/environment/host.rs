@@ -0,0 +1,223 @@
+//! Providers which read from the host that the settings extension runs on.
+use super::{
+    error, EnvironmentError, InterfaceProvider, MetadataProvider, NetworkInterface, RandomProvider,
+};
+use nix::sys::socket::SockaddrStorage;
+use rand::Rng;
+use snafu::{OptionExt, ResultExt};
+use std::net::IpAddr;
+use std::ops::Range;
+use std::sync::OnceLock;
+use std::time::Duration;
+
+/// The address of the EC2 instance metadata service.
+const IMDS_ENDPOINT: &str = "http://169.254.169.254";
+/// How long to wait for the metadata service to respond.
+const IMDS_TIMEOUT: Duration = Duration::from_secs(5);
+/// How long session tokens should remain valid.
+const IMDS_TOKEN_TTL_SECONDS: &str = "21600";
+
+/// Reads instance metadata from the EC2 instance metadata service, using IMDSv2 session tokens.
+///
+/// The client keeps its connection to the metadata service open between requests, and reuses its
+/// session token.
+#[derive(Debug)]
+pub struct ImdsClient {
+    base_url: String,
+    agent: ureq::Agent,
+    token: OnceLock<String>,
+}
+
+impl ImdsClient {
+    /// Creates a new [`ImdsClient`] which sends requests to `endpoint`, e.g. `http://127.0.0.1:8080`.
+    pub fn new<E: AsRef<str>>(endpoint: E) -> Self {
+        Self {
+            base_url: format!("{}/latest", endpoint.as_ref().trim_end_matches('/')),
+            agent: agent(IMDS_TIMEOUT),
+            token: OnceLock::new(),
+        }
+    }
+
+    /// Waits at most `timeout` for the metadata service to respond to each request.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.agent = agent(timeout);
+        self
+    }
+
+    /// Returns a session token, requesting one if this client doesn't have one yet.
+    fn token(&self) -> Result<&str, EnvironmentError> {
+        if let Some(token) = self.token.get() {
+            return Ok(token);
+        }
+
+        let path = "api/token";
+        let token = send(
+            path,
+            self.agent.put(&format!("{}/{}", self.base_url, path)).set(
+                "X-aws-ec2-metadata-token-ttl-seconds",
+                IMDS_TOKEN_TTL_SECONDS,
+            ),
+        )?
+        .context(error::MetadataStatusSnafu {
+            path,
+            status: 404u16,
+        })?;
+        Ok(self.token.get_or_init(|| token))
+    }
+}
+
+impl Default for ImdsClient {
+    fn default() -> Self {
+        Self::new(IMDS_ENDPOINT)
+    }
+}
+
+impl MetadataProvider for ImdsClient {
+    fn metadata(&self, path: &str) -> Result<Option<String>, EnvironmentError> {
+        let token = self.token()?;
+        send(
+            path,
+            self.agent
+                .get(&format!(
+                    "{}/{}",
+                    self.base_url,
+                    path.trim_start_matches('/')
+                ))
+                .set("X-aws-ec2-metadata-token", token),
+        )
+    }
+}
+
+/// Returns an HTTP agent which waits at most `timeout` for each request.
+fn agent(timeout: Duration) -> ureq::Agent {
+    ureq::AgentBuilder::new().timeout(timeout).build()
+}
+
+/// Sends a request for the metadata at `path`, returning `None` if there is none.
+///
+/// Response bodies are read up to the limit imposed by [`ureq::Response::into_string`].
+fn send(path: &str, request: ureq::Request) -> Result<Option<String>, EnvironmentError> {
+    match request.call() {
+        Ok(response) => response
+            .into_string()
+            .map(Some)
+            .context(error::ReadMetadataSnafu { path }),
+        Err(ureq::Error::Status(404, _)) => Ok(None),
+        Err(ureq::Error::Status(status, _)) => error::MetadataStatusSnafu { path, status }.fail(),
+        Err(ureq::Error::Transport(e)) => {
+            Err(Box::new(e)).context(error::MetadataRequestSnafu { path })
+        }
+    }
+}
+
+/// Lists the host's network interfaces using `getifaddrs(3)`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct HostInterfaces;
+
+impl InterfaceProvider for HostInterfaces {
+    fn interfaces(&self) -> Result<Vec<NetworkInterface>, EnvironmentError> {
+        let addresses = nix::ifaddrs::getifaddrs().context(error::ListInterfacesSnafu)?;
+        Ok(group_by_interface(addresses.map(|entry| {
+            let address = entry.address.as_ref().and_then(ip_address);
+            (entry.interface_name, address)
+        })))
+    }
+}
+
+/// Gathers the addresses of each interface, which `getifaddrs` lists separately, keeping the
+/// interfaces in the order they're first listed.
+fn group_by_interface<I>(entries: I) -> Vec<NetworkInterface>
+where
+    I: IntoIterator<Item = (String, Option<IpAddr>)>,
+{
+    let mut interfaces: Vec<NetworkInterface> = Vec::new();
+    for (name, address) in entries {
+        match interfaces
+            .iter_mut()
+            .find(|interface| interface.name == name)
+        {
+            Some(interface) => interface.addresses.extend(address),
+            None => interfaces.push(NetworkInterface::new(name, address.into_iter().collect())),
+        }
+    }
+    interfaces
+}
+
+/// Returns the IP address in `address`, if it is an IPv4 or IPv6 socket address.
+fn ip_address(address: &SockaddrStorage) -> Option<IpAddr> {
+    if let Some(address) = address.as_sockaddr_in() {
+        Some(IpAddr::V4(address.ip()))
+    } else {
+        address
+            .as_sockaddr_in6()
+            .map(|address| IpAddr::V6(address.ip()))
+    }
+}
+
+/// Generates random numbers with the thread-local random number generator.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ThreadRandom;
+
+impl RandomProvider for ThreadRandom {
+    fn random_range(&self, range: Range<u64>) -> u64 {
+        if range.is_empty() {
+            return range.start;
+        }
+        rand::thread_rng().gen_range(range)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::net::SocketAddr;
+
+    fn socket_address(address: &str) -> SockaddrStorage {
+        SockaddrStorage::from(address.parse::<SocketAddr>().unwrap())
+    }
+
+    #[test]
+    fn test_ip_address() {
+        assert_eq!(
+            ip_address(&socket_address("10.0.0.1:0")),
+            Some("10.0.0.1".parse().unwrap())
+        );
+        assert_eq!(
+            ip_address(&socket_address("[fe80::1]:0")),
+            Some("fe80::1".parse().unwrap())
+        );
+    }
+
+    #[test]
+    fn test_group_by_interface() {
+        let address = |address: &str| Some(address.parse::<IpAddr>().unwrap());
+        let interfaces = group_by_interface([
+            ("lo".to_string(), address("127.0.0.1")),
+            ("eth0".to_string(), None),
+            ("eth0".to_string(), address("10.0.0.1")),
+            ("lo".to_string(), address("::1")),
+            ("eth0".to_string(), address("fe80::1")),
+        ]);
+        assert_eq!(
+            interfaces,
+            vec![
+                NetworkInterface::new(
+                    "lo",
+                    vec!["127.0.0.1".parse().unwrap(), "::1".parse().unwrap()]
+                ),
+                NetworkInterface::new(
+                    "eth0",
+                    vec!["10.0.0.1".parse().unwrap(), "fe80::1".parse().unwrap()]
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_thread_random() {
+        for _ in 0..100 {
+            assert!((10..20).contains(&ThreadRandom.random_range(10..20)));
+        }
+        assert_eq!(ThreadRandom.random_range(5..5), 5);
+    }
+}
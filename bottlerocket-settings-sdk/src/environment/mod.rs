@@ -0,0 +1,242 @@
+//! Provides the [`Environment`] passed to
+//! [`SettingsModel::generate_with_environment`](crate::SettingsModel), which gives settings
+//! generators access to data about the host they run on.
+//!
+//! Each kind of data is read through a provider trait, so that generators can be tested without
+//! depending on the host:
+//!
+//! * [`MetadataProvider`] - the instance metadata service, e.g. `meta-data/local-ipv4`
+//! * [`InterfaceProvider`] - local network interfaces and their addresses
+//! * [`FileProvider`] - files on the host, e.g. `/etc/eks/max-pods`
+//! * [`RandomProvider`] - random numbers
+//!
+//! With the `host` feature, [`Environment::host`] reads from the host itself. For tests,
+//! [`Environment::in_memory`] starts with empty providers, which can be replaced with the stand-ins
+//! in the [`stand_in`] module or, with the `test-util` feature, with an [`ImdsClient`] pointed at a
+//! `MetadataStub`.
+//!
+//! ```
+//! use bottlerocket_settings_sdk::environment::stand_in::{FixedRandom, StaticMetadata};
+//! use bottlerocket_settings_sdk::environment::Environment;
+//!
+//! let environment = Environment::in_memory()
+//!     .with_metadata(StaticMetadata::new([("meta-data/local-ipv4", "10.0.0.1")]))
+//!     .with_random(FixedRandom::new([7]));
+//!
+//! assert_eq!(
+//!     environment.metadata().metadata("meta-data/local-ipv4").unwrap(),
+//!     Some("10.0.0.1".to_string())
+//! );
+//! assert_eq!(environment.random().random_range(0..2048), 7);
+//! ```
+use serde::{Deserialize, Serialize};
+use std::fmt::Debug;
+use std::net::IpAddr;
+use std::ops::Range;
+use std::path::Path;
+
+mod files;
+#[cfg(feature = "host")]
+mod host;
+pub mod stand_in;
+#[cfg(feature = "test-util")]
+mod stub;
+pub use error::EnvironmentError;
+pub use files::HostFiles;
+#[cfg(feature = "host")]
+pub use host::{HostInterfaces, ImdsClient, ThreadRandom};
+#[cfg(feature = "test-util")]
+pub use stub::MetadataStub;
+
+/// A source of instance metadata, such as the EC2 instance metadata service.
+pub trait MetadataProvider: Debug {
+    /// Returns the metadata at `path`, relative to the root of the latest metadata version, e.g.
+    /// `meta-data/local-ipv4`.
+    ///
+    /// Returns `None` if there is no metadata at the path.
+    fn metadata(&self, path: &str) -> Result<Option<String>, EnvironmentError>;
+}
+
+/// A source of information about the host's network interfaces.
+pub trait InterfaceProvider: Debug {
+    /// Returns each network interface, along with its addresses.
+    fn interfaces(&self) -> Result<Vec<NetworkInterface>, EnvironmentError>;
+}
+
+/// A source of files on the host.
+pub trait FileProvider: Debug {
+    /// Returns the contents of the file at `path`, or `None` if it doesn't exist.
+    fn read_to_string(&self, path: &Path) -> Result<Option<String>, EnvironmentError>;
+}
+
+/// A source of random numbers.
+pub trait RandomProvider: Debug {
+    /// Returns a number within `range`.
+    fn random_range(&self, range: Range<u64>) -> u64;
+}
+
+/// A network interface on the host.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NetworkInterface {
+    name: String,
+    addresses: Vec<IpAddr>,
+}
+
+impl NetworkInterface {
+    /// Creates a new [`NetworkInterface`] with the given addresses.
+    pub fn new<N: Into<String>>(name: N, addresses: Vec<IpAddr>) -> Self {
+        Self {
+            name: name.into(),
+            addresses,
+        }
+    }
+
+    /// Returns the name of the interface, e.g. `eth0`.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the addresses assigned to the interface.
+    pub fn addresses(&self) -> &[IpAddr] {
+        &self.addresses
+    }
+}
+
+/// The data sources available to settings generators.
+#[derive(Debug)]
+pub struct Environment {
+    metadata: Box<dyn MetadataProvider>,
+    interfaces: Box<dyn InterfaceProvider>,
+    files: Box<dyn FileProvider>,
+    random: Box<dyn RandomProvider>,
+}
+
+impl Environment {
+    /// Returns an [`Environment`] which reads from the host and its instance metadata service.
+    #[cfg(feature = "host")]
+    pub fn host() -> Self {
+        Self {
+            metadata: Box::new(ImdsClient::default()),
+            interfaces: Box::new(HostInterfaces),
+            files: Box::new(HostFiles),
+            random: Box::new(ThreadRandom),
+        }
+    }
+
+    /// Returns an [`Environment`] with no metadata, interfaces, or files, whose random numbers are
+    /// always the lowest in the requested range.
+    ///
+    /// This is useful as a starting point for tests, which can replace the providers they need.
+    pub fn in_memory() -> Self {
+        Self {
+            metadata: Box::new(stand_in::StaticMetadata::default()),
+            interfaces: Box::new(stand_in::StaticInterfaces::default()),
+            files: Box::new(stand_in::StaticFiles::default()),
+            random: Box::new(stand_in::FixedRandom::new([0])),
+        }
+    }
+
+    /// Uses the given source of instance metadata.
+    pub fn with_metadata<P: MetadataProvider + 'static>(mut self, metadata: P) -> Self {
+        self.metadata = Box::new(metadata);
+        self
+    }
+
+    /// Uses the given source of network interfaces.
+    pub fn with_interfaces<P: InterfaceProvider + 'static>(mut self, interfaces: P) -> Self {
+        self.interfaces = Box::new(interfaces);
+        self
+    }
+
+    /// Uses the given source of files.
+    pub fn with_files<P: FileProvider + 'static>(mut self, files: P) -> Self {
+        self.files = Box::new(files);
+        self
+    }
+
+    /// Uses the given source of random numbers.
+    pub fn with_random<P: RandomProvider + 'static>(mut self, random: P) -> Self {
+        self.random = Box::new(random);
+        self
+    }
+
+    /// Returns the source of instance metadata.
+    pub fn metadata(&self) -> &dyn MetadataProvider {
+        self.metadata.as_ref()
+    }
+
+    /// Returns the source of network interfaces.
+    pub fn interfaces(&self) -> &dyn InterfaceProvider {
+        self.interfaces.as_ref()
+    }
+
+    /// Returns the source of files.
+    pub fn files(&self) -> &dyn FileProvider {
+        self.files.as_ref()
+    }
+
+    /// Returns the source of random numbers.
+    pub fn random(&self) -> &dyn RandomProvider {
+        self.random.as_ref()
+    }
+}
+
+/// Reads from the host with the `host` feature, and is otherwise the same as
+/// [`Environment::in_memory`].
+impl Default for Environment {
+    fn default() -> Self {
+        #[cfg(feature = "host")]
+        return Self::host();
+        #[cfg(not(feature = "host"))]
+        return Self::in_memory();
+    }
+}
+
+mod error {
+    #![allow(missing_docs)]
+    use snafu::Snafu;
+    use std::path::PathBuf;
+
+    /// The error type returned when reading data from the [`Environment`](super::Environment).
+    #[derive(Debug, Snafu)]
+    #[snafu(visibility(pub(super)))]
+    pub enum EnvironmentError {
+        #[cfg(feature = "host")]
+        #[snafu(display("Failed to list network interfaces: {}", source))]
+        ListInterfaces { source: nix::Error },
+
+        #[cfg(feature = "host")]
+        #[snafu(display("Failed to request metadata '{}': {}", path, source))]
+        MetadataRequest {
+            path: String,
+            source: Box<ureq::Transport>,
+        },
+
+        #[cfg(feature = "host")]
+        #[snafu(display("Metadata request for '{}' failed with status {}", path, status))]
+        MetadataStatus { path: String, status: u16 },
+
+        #[snafu(display("Failed to parse interfaces from '{}': {}", path.display(), source))]
+        ParseInterfaces {
+            path: PathBuf,
+            source: serde_json::Error,
+        },
+
+        #[snafu(display("Failed to read '{}': {}", path.display(), source))]
+        ReadFile {
+            path: PathBuf,
+            source: std::io::Error,
+        },
+
+        #[cfg(feature = "host")]
+        #[snafu(display(
+            "Failed to read the response to metadata request '{}': {}",
+            path,
+            source
+        ))]
+        ReadMetadata {
+            path: String,
+            source: std::io::Error,
+        },
+    }
+}
@@ -0,0 +1,276 @@
+//! Stand-in providers for testing settings generators without depending on the host.
+//!
+//! The `Static*` providers and [`FixedRandom`] hold their data in memory, while
+//! [`DirectoryMetadata`], [`JsonInterfaces`], and [`RootedFiles`] read fixtures from files.
+use super::files::read_optional;
+use super::{
+    error, EnvironmentError, FileProvider, InterfaceProvider, MetadataProvider, NetworkInterface,
+    RandomProvider,
+};
+use snafu::ResultExt;
+use std::cell::Cell;
+use std::collections::HashMap;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+/// Serves instance metadata from memory, keyed by path, e.g. `meta-data/local-ipv4`.
+#[derive(Debug, Clone, Default)]
+pub struct StaticMetadata {
+    metadata: HashMap<String, String>,
+}
+
+impl StaticMetadata {
+    /// Creates a new [`StaticMetadata`] serving the given metadata.
+    pub fn new<I, P, V>(metadata: I) -> Self
+    where
+        I: IntoIterator<Item = (P, V)>,
+        P: Into<String>,
+        V: Into<String>,
+    {
+        Self {
+            metadata: metadata
+                .into_iter()
+                .map(|(path, value)| (path.into(), value.into()))
+                .collect(),
+        }
+    }
+}
+
+impl MetadataProvider for StaticMetadata {
+    fn metadata(&self, path: &str) -> Result<Option<String>, EnvironmentError> {
+        Ok(self.metadata.get(path).cloned())
+    }
+}
+
+/// Serves instance metadata from files in a directory, where each metadata path names a file, e.g.
+/// `<root>/meta-data/local-ipv4`.
+#[derive(Debug, Clone)]
+pub struct DirectoryMetadata {
+    root: PathBuf,
+}
+
+impl DirectoryMetadata {
+    /// Creates a new [`DirectoryMetadata`] serving the files under `root`.
+    pub fn new<P: Into<PathBuf>>(root: P) -> Self {
+        Self { root: root.into() }
+    }
+}
+
+impl MetadataProvider for DirectoryMetadata {
+    fn metadata(&self, path: &str) -> Result<Option<String>, EnvironmentError> {
+        let contents = read_optional(&self.root.join(path.trim_start_matches('/')))?;
+        // Fixture files usually end with a newline, which the metadata service wouldn't return.
+        Ok(contents.map(|contents| contents.trim_end_matches('\n').to_string()))
+    }
+}
+
+/// Lists a fixed set of network interfaces.
+#[derive(Debug, Clone, Default)]
+pub struct StaticInterfaces {
+    interfaces: Vec<NetworkInterface>,
+}
+
+impl StaticInterfaces {
+    /// Creates a new [`StaticInterfaces`] listing the given interfaces.
+    pub fn new(interfaces: Vec<NetworkInterface>) -> Self {
+        Self { interfaces }
+    }
+}
+
+impl InterfaceProvider for StaticInterfaces {
+    fn interfaces(&self) -> Result<Vec<NetworkInterface>, EnvironmentError> {
+        Ok(self.interfaces.clone())
+    }
+}
+
+/// Lists the network interfaces described in a JSON file, e.g.
+/// `[{"name": "eth0", "addresses": ["10.0.0.1"]}]`.
+#[derive(Debug, Clone)]
+pub struct JsonInterfaces {
+    path: PathBuf,
+}
+
+impl JsonInterfaces {
+    /// Creates a new [`JsonInterfaces`] reading the file at `path`.
+    pub fn new<P: Into<PathBuf>>(path: P) -> Self {
+        Self { path: path.into() }
+    }
+}
+
+impl InterfaceProvider for JsonInterfaces {
+    fn interfaces(&self) -> Result<Vec<NetworkInterface>, EnvironmentError> {
+        let contents = std::fs::read_to_string(&self.path).context(error::ReadFileSnafu {
+            path: self.path.clone(),
+        })?;
+        serde_json::from_str(&contents).context(error::ParseInterfacesSnafu {
+            path: self.path.clone(),
+        })
+    }
+}
+
+/// Serves files from memory, keyed by their path on the host.
+#[derive(Debug, Clone, Default)]
+pub struct StaticFiles {
+    files: HashMap<PathBuf, String>,
+}
+
+impl StaticFiles {
+    /// Creates a new [`StaticFiles`] serving the given files.
+    pub fn new<I, P, C>(files: I) -> Self
+    where
+        I: IntoIterator<Item = (P, C)>,
+        P: Into<PathBuf>,
+        C: Into<String>,
+    {
+        Self {
+            files: files
+                .into_iter()
+                .map(|(path, contents)| (path.into(), contents.into()))
+                .collect(),
+        }
+    }
+}
+
+impl FileProvider for StaticFiles {
+    fn read_to_string(&self, path: &Path) -> Result<Option<String>, EnvironmentError> {
+        Ok(self.files.get(path).cloned())
+    }
+}
+
+/// Serves files from a directory standing in for the root of the host's filesystem, so that
+/// `/etc/hostname` is read from `<root>/etc/hostname`.
+#[derive(Debug, Clone)]
+pub struct RootedFiles {
+    root: PathBuf,
+}
+
+impl RootedFiles {
+    /// Creates a new [`RootedFiles`] serving the files under `root`.
+    pub fn new<P: Into<PathBuf>>(root: P) -> Self {
+        Self { root: root.into() }
+    }
+}
+
+impl FileProvider for RootedFiles {
+    fn read_to_string(&self, path: &Path) -> Result<Option<String>, EnvironmentError> {
+        let relative = path.strip_prefix("/").unwrap_or(path);
+        read_optional(&self.root.join(relative))
+    }
+}
+
+/// Returns each of a fixed sequence of numbers in turn, repeating the sequence once it runs out.
+///
+/// Numbers outside of the requested range are wrapped into it.
+#[derive(Debug, Clone)]
+pub struct FixedRandom {
+    values: Vec<u64>,
+    next: Cell<usize>,
+}
+
+impl FixedRandom {
+    /// Creates a new [`FixedRandom`] returning the given numbers.
+    pub fn new<I: IntoIterator<Item = u64>>(values: I) -> Self {
+        Self {
+            values: values.into_iter().collect(),
+            next: Cell::new(0),
+        }
+    }
+}
+
+impl RandomProvider for FixedRandom {
+    fn random_range(&self, range: Range<u64>) -> u64 {
+        let index = self.next.get();
+        self.next.set(index + 1);
+        let value = match self.values.len() {
+            0 => 0,
+            len => self.values.get(index % len).copied().unwrap_or_default(),
+        };
+
+        match range.end.checked_sub(range.start) {
+            Some(len) if len > 0 => range.start + value % len,
+            _ => range.start,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::net::IpAddr;
+
+    /// Returns the path of a new, empty directory for a test's fixtures.
+    fn fixture_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "settings-sdk-stand-in-{}-{}",
+            name,
+            std::process::id()
+        ));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_directory_metadata() {
+        let root = fixture_dir("metadata");
+        std::fs::create_dir_all(root.join("meta-data")).unwrap();
+        std::fs::write(root.join("meta-data/local-ipv4"), "10.0.0.1\n").unwrap();
+
+        let metadata = DirectoryMetadata::new(&root);
+        assert_eq!(
+            metadata.metadata("meta-data/local-ipv4").unwrap(),
+            Some("10.0.0.1".to_string())
+        );
+        assert_eq!(metadata.metadata("meta-data/instance-type").unwrap(), None);
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn test_rooted_files() {
+        let root = fixture_dir("files");
+        std::fs::create_dir_all(root.join("etc")).unwrap();
+        std::fs::write(root.join("etc/hostname"), "localhost").unwrap();
+
+        let files = RootedFiles::new(&root);
+        assert_eq!(
+            files.read_to_string(Path::new("/etc/hostname")).unwrap(),
+            Some("localhost".to_string())
+        );
+        assert_eq!(files.read_to_string(Path::new("/etc/hosts")).unwrap(), None);
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn test_json_interfaces() {
+        let root = fixture_dir("interfaces");
+        let path = root.join("interfaces.json");
+        std::fs::write(
+            &path,
+            r#"[{"name": "eth0", "addresses": ["10.0.0.1", "fe80::1"]}]"#,
+        )
+        .unwrap();
+
+        let interfaces = JsonInterfaces::new(&path).interfaces().unwrap();
+        assert_eq!(
+            interfaces,
+            vec![NetworkInterface::new(
+                "eth0",
+                vec![
+                    "10.0.0.1".parse::<IpAddr>().unwrap(),
+                    "fe80::1".parse::<IpAddr>().unwrap()
+                ]
+            )]
+        );
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn test_fixed_random() {
+        let random = FixedRandom::new([3, 2050]);
+        assert_eq!(random.random_range(0..2048), 3);
+        assert_eq!(random.random_range(0..2048), 2);
+        assert_eq!(random.random_range(10..20), 13);
+        assert_eq!(random.random_range(5..5), 5);
+        assert_eq!(FixedRandom::new([]).random_range(1..3), 1);
+    }
+}
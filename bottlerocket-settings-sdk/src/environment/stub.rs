@@ -0,0 +1,126 @@
+//! A local stub of the instance metadata service, for testing the metadata client.
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use tiny_http::{Method, Request, Response, Server};
+
+/// A local HTTP server which responds to instance metadata requests like the EC2 instance metadata
+/// service, for use in tests.
+///
+/// The stub requires IMDSv2 session tokens, and responds with `404 Not Found` for any metadata it
+/// wasn't given. It stops serving when dropped.
+///
+/// ```
+/// use bottlerocket_settings_sdk::environment::{ImdsClient, MetadataProvider, MetadataStub};
+///
+/// let stub = MetadataStub::start([("meta-data/local-ipv4", "10.0.0.1")]).unwrap();
+/// let client = ImdsClient::new(stub.endpoint());
+///
+/// assert_eq!(
+///     client.metadata("meta-data/local-ipv4").unwrap(),
+///     Some("10.0.0.1".to_string())
+/// );
+/// assert_eq!(client.metadata("meta-data/instance-type").unwrap(), None);
+/// ```
+pub struct MetadataStub {
+    endpoint: String,
+    server: Arc<Server>,
+    handle: Option<JoinHandle<()>>,
+}
+
+/// The session token issued by the stub.
+const STUB_TOKEN: &str = "metadata-stub-token";
+
+impl MetadataStub {
+    /// Starts serving the given metadata, keyed by path relative to the latest metadata version,
+    /// e.g. `meta-data/local-ipv4`.
+    pub fn start<I, P, V>(metadata: I) -> std::io::Result<Self>
+    where
+        I: IntoIterator<Item = (P, V)>,
+        P: Into<String>,
+        V: Into<String>,
+    {
+        let metadata: HashMap<String, String> = metadata
+            .into_iter()
+            .map(|(path, value)| (path.into(), value.into()))
+            .collect();
+
+        let server = Arc::new(Server::http("127.0.0.1:0").map_err(std::io::Error::other)?);
+        let address = server
+            .server_addr()
+            .to_ip()
+            .ok_or_else(|| std::io::Error::other("stub is not listening on an IP address"))?;
+
+        let requests = Arc::clone(&server);
+        let handle = std::thread::spawn(move || {
+            for request in requests.incoming_requests() {
+                // A failure to respond only affects the test which made the request.
+                let _ = serve(request, &metadata);
+            }
+        });
+
+        Ok(Self {
+            endpoint: format!("http://{}", address),
+            server,
+            handle: Some(handle),
+        })
+    }
+
+    /// Returns the URL at which the stub is serving, e.g. `http://127.0.0.1:41234`.
+    pub fn endpoint(&self) -> &str {
+        &self.endpoint
+    }
+}
+
+impl Debug for MetadataStub {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MetadataStub")
+            .field("endpoint", &self.endpoint)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Drop for MetadataStub {
+    fn drop(&mut self) {
+        self.server.unblock();
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+/// Responds to a single request to the stub.
+fn serve(request: Request, metadata: &HashMap<String, String>) -> std::io::Result<()> {
+    let token = request
+        .headers()
+        .iter()
+        .find(|header| header.field.equiv("X-aws-ec2-metadata-token"))
+        .map(|header| header.value.as_str());
+
+    let (status, body) = match (request.method(), request.url().strip_prefix("/latest/")) {
+        (Method::Put, Some("api/token")) => (200, STUB_TOKEN),
+        (Method::Get, Some(_)) if token != Some(STUB_TOKEN) => (401, ""),
+        (Method::Get, Some(path)) => match metadata.get(path) {
+            Some(value) => (200, value.as_str()),
+            None => (404, ""),
+        },
+        _ => (400, ""),
+    };
+    let body = body.to_string();
+    request.respond(Response::from_string(body).with_status_code(status))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_stub_requires_token() {
+        let stub = MetadataStub::start([("meta-data/local-ipv4", "10.0.0.1")]).unwrap();
+
+        let response =
+            ureq::get(&format!("{}/latest/meta-data/local-ipv4", stub.endpoint())).call();
+        assert!(matches!(response, Err(ureq::Error::Status(401, _))));
+    }
+}
@@ -1,12 +1,14 @@
 //! A basic setting extension for use in doc comments.
 use super::{EmptyError, Result};
-use crate::{GenerateResult, LinearlyMigrateable, NoMigration, SettingsModel};
+use crate::{GenerateResult, KnownNames, LinearlyMigrateable, NoMigration, SettingsModel};
 use serde::{Deserialize, Serialize};
 
 /// A setting with no data for use in doc comments.
 #[derive(Serialize, Deserialize, Debug)]
 pub struct EmptySetting;
 
+impl KnownNames for EmptySetting {}
+
 impl SettingsModel for EmptySetting {
     type PartialKind = Self;
     type ErrorKind = EmptyError;
@@ -21,7 +21,7 @@
 //! ```
 use super::SettingsExtensionError;
 use crate::model::erased::AsTypeErasedModel;
-use crate::{Migrator, SettingsExtension};
+use crate::{Environment, Migrator, SettingsExtension};
 use tracing::instrument;
 
 #[macro_export]
@@ -81,6 +81,15 @@ macro_rules! extension_builder {
                 Self(inner_builder)
             }
 
+            /// Uses the given environment when generating settings, rather than reading from the
+            /// host.
+            $vis fn with_environment(self, environment: $crate::Environment) -> Self {
+                let Self(inner_builder) = self;
+                let inner_builder = inner_builder.with_environment(environment);
+
+                Self(inner_builder)
+            }
+
             /// Constructs a `SettingsExtension` with the given options.
             $vis fn build(
                 self,
@@ -110,6 +119,7 @@ where
     name: &'static str,
     models: Option<Vec<Mo>>,
     migrator: Mi,
+    environment: Option<Environment>,
 }
 
 impl<Mi, Mo> SettingsExtensionBuilder<Mi, Mo>
@@ -123,6 +133,7 @@ where
             name,
             migrator,
             models: None,
+            environment: None,
         }
     }
 
@@ -132,12 +143,22 @@ where
         self
     }
 
+    /// Use the given [`Environment`] when generating settings, rather than reading from the host.
+    pub fn with_environment(mut self, environment: Environment) -> Self {
+        self.environment = Some(environment);
+        self
+    }
+
     /// Constructs a [`SettingsExtension`] using the configurations supplied to the builder.
     #[instrument(skip(self), err)]
     pub fn build(self) -> Result<SettingsExtension<Mi, Mo>, SettingsExtensionError<Mi::ErrorKind>> {
         let models = self.models.unwrap_or_default();
         let migrator = self.migrator;
 
-        SettingsExtension::new(self.name, models, migrator)
+        let extension = SettingsExtension::new(self.name, models, migrator)?;
+        Ok(match self.environment {
+            Some(environment) => extension.with_environment(environment),
+            None => extension,
+        })
     }
 }
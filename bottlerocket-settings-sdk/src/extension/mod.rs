@@ -3,6 +3,7 @@
 use crate::cli;
 use crate::migrate::{Migrator, ModelStore};
 use crate::model::erased::AsTypeErasedModel;
+use crate::Environment;
 use argh::FromArgs;
 use snafu::{ensure, OptionExt, ResultExt};
 use std::collections::{HashMap, HashSet};
@@ -33,6 +34,7 @@ where
     name: &'static str,
     models: HashMap<Version, Mo>,
     migrator: Mi,
+    environment: Environment,
 }
 
 impl<Mi, Mo> SettingsExtension<Mi, Mo>
@@ -42,6 +44,9 @@ where
 {
     /// Creates a new [`SettingsExtension`].
     ///
+    /// Settings are generated using the [default environment](Environment::default), which reads
+    /// from the host; see [`SettingsExtension::with_environment`].
+    ///
     /// Returns an error if the given models have a version naming collision, or if any written
     /// migrations are deemed invalid.
     pub fn new(
@@ -55,6 +60,7 @@ where
             name,
             models,
             migrator,
+            environment: Environment::default(),
         };
 
         extension.validate_migrations()?;
@@ -62,6 +68,17 @@ where
         Ok(extension)
     }
 
+    /// Uses the given [`Environment`] when generating settings, rather than reading from the host.
+    pub fn with_environment(mut self, environment: Environment) -> Self {
+        self.environment = environment;
+        self
+    }
+
+    /// Returns the [`Environment`] used when generating settings.
+    pub fn environment(&self) -> &Environment {
+        &self.environment
+    }
+
     /// Converts a list of models into a map of Version => Model while checking for uniqueness.
     fn build_model_map(
         models: Vec<Mo>,
@@ -185,6 +202,7 @@ where
             .field("name", &self.name)
             .field("model-versions", &self.models.keys().collect::<Vec<_>>())
             .field("migrator", &self.migrator)
+            .field("environment", &self.environment)
             .finish()
     }
 }
@@ -9,6 +9,7 @@ use crate::cli::proto1::{
 };
 use crate::migrate::Migrator;
 use crate::model::erased::AsTypeErasedModel;
+use crate::model::{DeprecationWarning, FieldError, GenerateResult};
 use crate::SettingsExtension;
 use snafu::{OptionExt, ResultExt};
 use std::fmt::Debug;
@@ -46,13 +47,35 @@ where
 {
     let json_stringify =
         |value| serde_json::to_string_pretty(&value).context(error::SerializeResultSnafu);
+    // Warnings are reported on stderr, so that the output of `set` and `validate` stays empty.
+    // Deprecated field names are reported before any problems the model finds with the value.
+    let report_warnings = |setting_version: &str, value: serde_json::Value| {
+        let deprecations = extension.deprecation_warnings(setting_version, &value)?;
+        let value_warnings = extension.value_warnings(setting_version, value)?;
+        for warning in deprecations
+            .iter()
+            .map(ToString::to_string)
+            .chain(value_warnings.iter().map(ToString::to_string))
+        {
+            eprintln!("Warning: {}", warning);
+        }
+        Ok(String::new())
+    };
 
     match cmd {
-        Proto1Command::Set(s) => extension.set(s).map(|_| String::new()),
+        Proto1Command::Set(s) => {
+            let (setting_version, value) = (s.setting_version.clone(), s.value.clone());
+            extension.set(s)?;
+            report_warnings(&setting_version, value)
+        }
         Proto1Command::Generate(g) => extension.generate(g).and_then(json_stringify),
         Proto1Command::Migrate(m) => extension.migrate(m).and_then(json_stringify),
         Proto1Command::FloodMigrate(m) => extension.flood_migrate(m).and_then(json_stringify),
-        Proto1Command::Validate(v) => extension.validate(v).map(|_| String::new()),
+        Proto1Command::Validate(v) => {
+            let (setting_version, value) = (v.setting_version.clone(), v.value.clone());
+            extension.validate(v)?;
+            report_warnings(&setting_version, value)
+        }
         Proto1Command::Helper(h) => extension.template_helper(h).and_then(json_stringify),
     }
 }
@@ -84,6 +107,16 @@ pub trait Proto1: Debug {
         &self,
         args: TemplateHelperCommand,
     ) -> Result<serde_json::Value, SettingsExtensionError<Self::MigratorErrorKind>>;
+    fn deprecation_warnings(
+        &self,
+        setting_version: &str,
+        value: &serde_json::Value,
+    ) -> Result<Vec<DeprecationWarning>, SettingsExtensionError<Self::MigratorErrorKind>>;
+    fn value_warnings(
+        &self,
+        setting_version: &str,
+        value: serde_json::Value,
+    ) -> Result<Vec<FieldError>, SettingsExtensionError<Self::MigratorErrorKind>>;
 }
 
 impl<Mi, Mo> Proto1 for SettingsExtension<Mi, Mo>
@@ -109,16 +142,32 @@ where
         &self,
         args: GenerateCommand,
     ) -> Result<serde_json::Value, SettingsExtensionError<Self::MigratorErrorKind>> {
-        self.model(&args.setting_version)
+        let model = self
+            .model(&args.setting_version)
             .context(error::NoSuchModelSnafu {
                 setting_version: args.setting_version,
             })?
-            .as_model()
-            .generate(args.existing_partial, args.required_settings)
-            .context(error::GenerateSnafu)
-            .and_then(|generated_data| {
-                serde_json::to_value(generated_data).context(error::SerializeResultSnafu)
-            })
+            .as_model();
+        let generated_data = match model.generate_with_environment(
+            args.existing_partial.clone(),
+            args.required_settings,
+            self.environment(),
+        ) {
+            Ok(generated_data) => generated_data,
+            Err(e) => match e.missing_settings() {
+                // The missing settings are reported on stderr, so that the result on stdout is
+                // unchanged.
+                Some(missing) => {
+                    eprintln!(
+                        "Waiting for settings to be generated: {}",
+                        missing.join(", ")
+                    );
+                    GenerateResult::NeedsData(args.existing_partial)
+                }
+                None => return Err(e).context(error::GenerateSnafu),
+            },
+        };
+        serde_json::to_value(generated_data).context(error::SerializeResultSnafu)
     }
 
     #[instrument(err)]
@@ -201,4 +250,28 @@ where
             .execute_template_helper(&args.helper_name, args.arg)
             .context(error::TemplateHelperSnafu)
     }
+
+    fn deprecation_warnings(
+        &self,
+        setting_version: &str,
+        value: &serde_json::Value,
+    ) -> Result<Vec<DeprecationWarning>, SettingsExtensionError<Self::MigratorErrorKind>> {
+        Ok(self
+            .model(setting_version)
+            .context(error::NoSuchModelSnafu { setting_version })?
+            .as_model()
+            .deprecation_warnings(value))
+    }
+
+    fn value_warnings(
+        &self,
+        setting_version: &str,
+        value: serde_json::Value,
+    ) -> Result<Vec<FieldError>, SettingsExtensionError<Self::MigratorErrorKind>> {
+        self.model(setting_version)
+            .context(error::NoSuchModelSnafu { setting_version })?
+            .as_model()
+            .value_warnings(value)
+            .context(error::ValidateSnafu)
+    }
 }
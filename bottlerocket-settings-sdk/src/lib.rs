@@ -14,12 +14,17 @@ tool wishes to invoke a settings extension and parse the output.
   When enabled, this causes the SDK library to expose the `SettingsExtension` type, as well as all
   other utilities required to build a `SettingsExtension` or serve it on the CLI.
 
+* **host** -
+  When enabled, this allows settings to be generated from data about the host, such as its files,
+  network interfaces, and instance metadata, via `Environment::host`.
+
 * **proto1** -
   When enabled, this allows extensions built against the SDK to serve the Settings Extension CLI
   protocol version "proto1".
 */
 #[cfg(feature = "extension")]
 pub mod cli;
+pub mod environment;
 #[cfg(feature = "extension")]
 pub mod extension;
 pub mod helper;
@@ -29,15 +34,27 @@ pub mod model;
 
 #[cfg(feature = "extension")]
 pub use crate::extension::SettingsExtension;
+pub use environment::Environment;
 pub use helper::{template_helper, HelperDef, HelperError};
 #[cfg(feature = "extension")]
 pub use migrate::{
-    LinearMigrator, LinearMigratorExtensionBuilder, LinearMigratorModel, LinearlyMigrateable,
-    Migrator, NoMigration, NullMigrator, NullMigratorExtensionBuilder,
+    migrate_entries, LinearMigrator, LinearMigratorExtensionBuilder, LinearMigratorModel,
+    LinearlyMigrateable, Migrator, NoMigration, NullMigrator, NullMigratorExtensionBuilder,
 };
 
-pub use model::{BottlerocketSetting, GenerateResult, SettingsModel};
+pub use model::{
+    BottlerocketSetting, CrossValidationError, CrossValidationInputs, DependentSettings,
+    DeprecationWarning, FieldChecks, FieldError, FieldErrors, FieldPath, GenerateResult,
+    GenerationDependencyError, GenerationInputs, KnownNames, Names, PathSegment, SettingsModel,
+    ValidateFields, ValidatedSettings,
+};
 
 #[doc(hidden)]
 #[cfg(feature = "extension")]
 pub mod example;
+
+/// Dependencies of the code generated by the `#[model]` macro.
+#[doc(hidden)]
+pub mod __private {
+    pub use serde_json;
+}
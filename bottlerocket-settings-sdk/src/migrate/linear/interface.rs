@@ -34,11 +34,12 @@ pub struct LinearMigrator;
 ///     all_scores: HashMap<String, i64>,
 /// }
 ///
-/// # use bottlerocket_settings_sdk::{GenerateResult, SettingsModel};
+/// # use bottlerocket_settings_sdk::{GenerateResult, KnownNames, SettingsModel};
 /// # use bottlerocket_settings_sdk::example::EmptyError;
 /// #
 /// # type Result<T> = std::result::Result<T, EmptyError>;
 /// #
+/// # impl KnownNames for ScoreV1 {}
 /// # impl SettingsModel for ScoreV1 {
 /// #     type PartialKind = Self;
 /// #     type ErrorKind = EmptyError;
@@ -63,6 +64,7 @@ pub struct LinearMigrator;
 /// #     }
 /// # }
 /// #
+/// # impl KnownNames for ScoreV2 {}
 /// # impl SettingsModel for ScoreV2 {
 /// #     type PartialKind = Self;
 /// #     type ErrorKind = EmptyError;
@@ -5,11 +5,14 @@
 //! The [`Migrator`](self::Migrator) trait, is also provided, which allows settings extensions
 //! to customize how they are migrated between different versions.
 use crate::model::erased::AsTypeErasedModel;
-use crate::{GenerateResult, SettingsModel};
+use crate::{GenerateResult, KnownNames, SettingsModel};
 use serde::{Deserialize, Serialize};
+use snafu::{IntoError, ResultExt};
 use std::any::Any;
+use std::collections::HashMap;
 use std::convert::Infallible;
 use std::fmt::Debug;
+use std::hash::Hash;
 
 pub mod linear;
 pub use linear::{
@@ -97,6 +100,30 @@ pub trait ModelStore {
     }
 }
 
+/// Migrates each entry of a map of named settings, such as containers keyed by name, for use in
+/// [`LinearlyMigrateable`] implementations whose settings are collections.
+///
+/// Each value is converted with `migrate`. If one fails, `context` is called with its name to build
+/// the error, so that the error says which entry couldn't be migrated.
+pub fn migrate_entries<K, T, U, S, C, E>(
+    entries: &HashMap<K, T>,
+    migrate: impl Fn(&T) -> Result<U, S>,
+    context: impl Fn(&K) -> C,
+) -> Result<HashMap<K, U>, E>
+where
+    K: Clone + Eq + Hash,
+    C: IntoError<E, Source = S>,
+    E: std::error::Error + snafu::ErrorCompat,
+{
+    entries
+        .iter()
+        .map(|(name, value)| {
+            let migrated = migrate(value).with_context(|_| context(name))?;
+            Ok((name.clone(), migrated))
+        })
+        .collect()
+}
+
 /// A marker type used to indicate that no migration should be performed.
 #[derive(Serialize, Deserialize, Debug, Default, PartialEq, Clone)]
 pub struct NoMigration;
@@ -115,6 +142,8 @@ impl NoMigration {
     }
 }
 
+impl KnownNames for NoMigration {}
+
 // `NoMigration` must implement `SettingsModel` so that it's type can be used as a marker.
 // In cases that are parameterized on `SettingsModel` types where `NoMigration` is valid, the
 // implementor must check for the presence of `NoMigration` with `TypeId::of`
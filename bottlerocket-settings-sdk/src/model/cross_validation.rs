@@ -0,0 +1,208 @@
+//! Provides [`CrossValidationInputs`], which gives settings models typed access to the other
+//! settings they cross-validate against.
+//!
+//! A model declares the settings it depends on as a struct implementing [`CrossValidationInputs`],
+//! with a field for each settings extension, named as the extension is:
+//!
+//! ```
+//! # use bottlerocket_settings_sdk::model::CrossValidationInputs;
+//! # use serde::Deserialize;
+//! # #[derive(Debug, Deserialize)]
+//! # struct NetworkSettingsV1 { hostname: Option<String> }
+//! #[derive(Debug, Deserialize)]
+//! struct KubernetesDependencies {
+//!     network: NetworkSettingsV1,
+//! }
+//!
+//! impl CrossValidationInputs for KubernetesDependencies {
+//!     fn required_settings() -> &'static [&'static str] {
+//!         &["network"]
+//!     }
+//! }
+//! ```
+//!
+//! Typed inputs are opt-in: models which use them read the settings passed to
+//! [`SettingsModel::validate`](super::SettingsModel::validate) with [`ValidatedSettings`], which
+//! reports settings that are missing or can't be deserialized as a [`CrossValidationError`]:
+//!
+//! ```
+//! # use bottlerocket_settings_sdk::model::{CrossValidationError, CrossValidationInputs};
+//! # use bottlerocket_settings_sdk::ValidatedSettings;
+//! # use serde::Deserialize;
+//! # #[derive(Debug, Deserialize)]
+//! # struct NetworkSettingsV1 { hostname: Option<String> }
+//! # #[derive(Debug, Deserialize)]
+//! # struct KubernetesDependencies { network: NetworkSettingsV1 }
+//! # impl CrossValidationInputs for KubernetesDependencies {
+//! #     fn required_settings() -> &'static [&'static str] { &["network"] }
+//! # }
+//! fn validate(validated_settings: Option<serde_json::Value>) -> Result<(), CrossValidationError> {
+//!     let dependencies: KubernetesDependencies =
+//!         ValidatedSettings::new(validated_settings).get()?;
+//!     // Check the value against `dependencies.network` here...
+//!     Ok(())
+//! }
+//! ```
+use super::dependencies;
+use bottlerocket_deserialize_helper::DeserializeError;
+use serde::de::DeserializeOwned;
+use std::fmt::Display;
+
+/// The other settings that a model cross-validates against, deserialized from the settings passed
+/// to `validate`.
+///
+/// Fields should be named after the settings extensions they hold. Unknown fields must be allowed,
+/// since the validated settings may include extensions that the model doesn't use.
+pub trait CrossValidationInputs: DeserializeOwned {
+    /// Returns the names of the settings extensions which must be present to cross-validate.
+    ///
+    /// Settings which are optional should be declared as `Option` fields and left out of this list.
+    fn required_settings() -> &'static [&'static str];
+}
+
+/// The settings that have already been validated, which a setting can be cross-validated against.
+///
+/// These are provided as a JSON Map, where the key is the extension name and the value is the value
+/// of that setting.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ValidatedSettings {
+    settings: Option<serde_json::Value>,
+}
+
+impl ValidatedSettings {
+    /// Creates a new [`ValidatedSettings`] from the settings passed to `validate`.
+    pub fn new(settings: Option<serde_json::Value>) -> Self {
+        Self { settings }
+    }
+
+    /// Deserializes the settings declared by `T`.
+    ///
+    /// Returns [`CrossValidationError::MissingSettings`] if any of the required settings aren't
+    /// present, or [`CrossValidationError::MalformedSetting`] if a setting can't be deserialized.
+    pub fn get<T: CrossValidationInputs>(&self) -> Result<T, CrossValidationError> {
+        let missing =
+            dependencies::missing_settings(self.settings.as_ref(), T::required_settings());
+        if !missing.is_empty() {
+            return Err(CrossValidationError::MissingSettings { names: missing });
+        }
+
+        dependencies::deserialize_settings(self.settings.as_ref())
+            .map_err(|(name, source)| CrossValidationError::MalformedSetting { name, source })
+    }
+
+    /// Returns the underlying settings value, if any settings were given.
+    pub fn as_value(&self) -> Option<&serde_json::Value> {
+        self.settings.as_ref()
+    }
+
+    /// Consumes the [`ValidatedSettings`], returning the underlying settings value.
+    pub fn into_inner(self) -> Option<serde_json::Value> {
+        self.settings
+    }
+}
+
+/// The error returned when the settings that a setting is cross-validated against aren't usable.
+#[derive(Debug)]
+pub enum CrossValidationError {
+    /// Settings which the model depends on were not provided.
+    MissingSettings {
+        /// The names of the missing settings extensions.
+        names: Vec<String>,
+    },
+
+    /// A setting which the model depends on could not be deserialized.
+    MalformedSetting {
+        /// The name of the settings extension at fault, if the problem is with a single setting.
+        name: Option<String>,
+        /// The underlying deserialization error, boxed to keep this error small.
+        source: Box<DeserializeError>,
+    },
+}
+
+impl Display for CrossValidationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::MissingSettings { names } => {
+                write!(f, "missing required settings: {}", names.join(", "))
+            }
+            Self::MalformedSetting {
+                name: Some(name),
+                source,
+            } => write!(f, "setting '{}' is malformed: {}", name, source),
+            Self::MalformedSetting { name: None, source } => {
+                write!(f, "settings are malformed: {}", source)
+            }
+        }
+    }
+}
+
+impl std::error::Error for CrossValidationError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::MalformedSetting { source, .. } => Some(source.as_ref()),
+            Self::MissingSettings { .. } => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use serde::Deserialize;
+    use serde_json::json;
+
+    #[derive(Debug, Deserialize)]
+    struct Network {
+        hostname: String,
+    }
+
+    #[derive(Debug, Deserialize)]
+    struct Dependencies {
+        network: Network,
+        ntp: Option<serde_json::Value>,
+    }
+
+    impl CrossValidationInputs for Dependencies {
+        fn required_settings() -> &'static [&'static str] {
+            &["network"]
+        }
+    }
+
+    #[test]
+    fn test_get_dependencies() {
+        let settings = ValidatedSettings::new(Some(json!({
+            "network": {"hostname": "localhost"},
+            "motd": "hello",
+        })));
+        let dependencies: Dependencies = settings.get().unwrap();
+        assert_eq!(dependencies.network.hostname, "localhost");
+        assert!(dependencies.ntp.is_none());
+    }
+
+    #[test]
+    fn test_missing_dependencies() {
+        for settings in [None, Some(json!({})), Some(json!({"network": null}))] {
+            let err = ValidatedSettings::new(settings)
+                .get::<Dependencies>()
+                .unwrap_err();
+            assert!(
+                matches!(&err, CrossValidationError::MissingSettings { names } if names == &["network"])
+            );
+            assert_eq!(err.to_string(), "missing required settings: network");
+        }
+    }
+
+    #[test]
+    fn test_malformed_dependency() {
+        let err = ValidatedSettings::new(Some(json!({"network": {"hostname": 42}})))
+            .get::<Dependencies>()
+            .unwrap_err();
+        assert!(matches!(
+            &err,
+            CrossValidationError::MalformedSetting { name: Some(name), .. } if name == "network"
+        ));
+        assert!(err
+            .to_string()
+            .starts_with("setting 'network' is malformed: invalid value for 'network.hostname'"));
+    }
+}
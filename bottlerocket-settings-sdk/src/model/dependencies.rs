@@ -0,0 +1,42 @@
+//! Reads the other settings that a model depends on, as passed to `generate` and `validate`.
+use bottlerocket_deserialize_helper::{DeserializeError, PathSegment};
+use serde::de::DeserializeOwned;
+
+/// Returns the names of the `required` settings which aren't present in `settings`.
+///
+/// Settings are provided as a JSON Map, where the key is the extension name and the value is the
+/// value of that setting. Null values are treated as missing.
+pub(super) fn missing_settings(
+    settings: Option<&serde_json::Value>,
+    required: &[&str],
+) -> Vec<String> {
+    let settings = settings.and_then(serde_json::Value::as_object);
+    required
+        .iter()
+        .filter(|name| {
+            settings
+                .and_then(|settings| settings.get(**name))
+                .is_none_or(serde_json::Value::is_null)
+        })
+        .map(|name| name.to_string())
+        .collect()
+}
+
+/// Deserializes `settings` into `T`, returning the name of the setting at fault on failure, if the
+/// problem is with a single setting.
+pub(super) fn deserialize_settings<T: DeserializeOwned>(
+    settings: Option<&serde_json::Value>,
+) -> Result<T, (Option<String>, Box<DeserializeError>)> {
+    let settings = settings
+        .filter(|settings| settings.is_object())
+        .cloned()
+        .unwrap_or_else(|| serde_json::Value::Object(serde_json::Map::new()));
+
+    bottlerocket_deserialize_helper::from_value(settings).map_err(|e| {
+        let name = match e.segments().first() {
+            Some(PathSegment::Key(name)) => Some(name.clone()),
+            _ => None,
+        };
+        (name, Box::new(e))
+    })
+}
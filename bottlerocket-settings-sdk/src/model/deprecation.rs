@@ -0,0 +1,104 @@
+//! Provides [`DeprecationWarning`], which is used to tell users that a setting value refers to a
+//! field by a deprecated name.
+//!
+//! Deprecated names are usually declared with `#[deprecated_alias = "..."]` field attributes on
+//! models using the `#[model]` macro, which finds them with the model's
+//! [`ValidateFields`](super::ValidateFields) implementation.
+use super::FieldPath;
+use std::fmt::Display;
+
+/// A field that was referred to by a deprecated name.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeprecationWarning {
+    alias: FieldPath,
+    name: FieldPath,
+}
+
+impl DeprecationWarning {
+    /// Creates a new [`DeprecationWarning`] for a field named `alias` which should now be `name`.
+    ///
+    /// Both names are paths within the settings value, e.g. `mirrors.0.endpoint`.
+    pub fn new<A, N>(alias: A, name: N) -> Self
+    where
+        A: Into<FieldPath>,
+        N: Into<FieldPath>,
+    {
+        Self {
+            alias: alias.into(),
+            name: name.into(),
+        }
+    }
+
+    /// Finds each field in `value` which uses one of the given deprecated `aliases`.
+    ///
+    /// `aliases` pairs deprecated field names with the names that replace them, and `path` is the
+    /// location of `value` within the settings value being checked.
+    pub fn find_all(
+        value: &serde_json::Value,
+        path: &FieldPath,
+        aliases: &[(&str, &str)],
+    ) -> Vec<Self> {
+        let fields = match value.as_object() {
+            Some(fields) => fields,
+            None => return Vec::new(),
+        };
+
+        aliases
+            .iter()
+            .filter(|(alias, _)| fields.contains_key(*alias))
+            .map(|(alias, name)| Self::new(path.join(*alias), path.join(*name)))
+            .collect()
+    }
+
+    /// Returns the path of the field, using the deprecated name that was given.
+    pub fn alias(&self) -> &FieldPath {
+        &self.alias
+    }
+
+    /// Returns the path of the field, using the name that should be used instead.
+    pub fn name(&self) -> &FieldPath {
+        &self.name
+    }
+}
+
+impl Display for DeprecationWarning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "'{}' is deprecated; use '{}' instead",
+            self.alias, self.name
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_find_all() {
+        let aliases = [("creds", "credentials"), ("kernel", "kernel-parameters")];
+
+        let root = FieldPath::default();
+
+        assert_eq!(
+            DeprecationWarning::find_all(&json!({"creds": [], "mirrors": []}), &root, &aliases),
+            vec![DeprecationWarning::new(
+                FieldPath::new(["creds"]),
+                FieldPath::new(["credentials"])
+            )]
+        );
+        assert_eq!(
+            DeprecationWarning::find_all(&json!({"kernel": {}}), &root.join("boot"), &aliases),
+            vec![DeprecationWarning::new(
+                FieldPath::new(["boot", "kernel"]),
+                FieldPath::new(["boot", "kernel-parameters"])
+            )]
+        );
+        assert!(
+            DeprecationWarning::find_all(&json!({"credentials": []}), &root, &aliases).is_empty()
+        );
+        assert!(DeprecationWarning::find_all(&json!("creds"), &root, &aliases).is_empty());
+    }
+}
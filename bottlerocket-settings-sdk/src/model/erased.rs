@@ -5,8 +5,13 @@
 //!
 //! This module contains traits which erase the underlying [`SettingsModel`] types, allowing the
 //! SDK to refer to the [`SettingsModel`]s as a collection of trait objects.
-use super::{error, BottlerocketSetting, BottlerocketSettingError, GenerateResult, SettingsModel};
-use snafu::{OptionExt, ResultExt};
+use super::{
+    error, BottlerocketSetting, BottlerocketSettingError, DeprecationWarning, FieldError,
+    GenerateResult, SettingsModel,
+};
+use crate::Environment;
+use serde::Serialize;
+use snafu::{ensure, OptionExt, ResultExt};
 use std::any::Any;
 use std::fmt::Debug;
 use tracing::{debug, instrument};
@@ -50,12 +55,24 @@ pub trait TypeErasedModel: Debug {
     /// completed. On each generation cycle, the settings extension is provided any values that it
     /// has previously generated, as well as all of the data that has thus far been generated by its
     /// dependencies.
+    ///
+    /// If the model is waiting for settings it depends on to be generated, the returned error names
+    /// them through [`BottlerocketSettingError::missing_settings`].
     fn generate(
         &self,
         existing_partial: Option<serde_json::Value>,
         dependent_settings: Option<serde_json::Value>,
     ) -> Result<GenerateResult<serde_json::Value, serde_json::Value>, BottlerocketSettingError>;
 
+    /// Generates default values at system start, like `generate`, reading any data about the host
+    /// from `environment` rather than from the [default environment](Environment::default).
+    fn generate_with_environment(
+        &self,
+        existing_partial: Option<serde_json::Value>,
+        dependent_settings: Option<serde_json::Value>,
+        environment: &Environment,
+    ) -> Result<GenerateResult<serde_json::Value, serde_json::Value>, BottlerocketSettingError>;
+
     /// Validates this setting, allowing for cross-validation with other settings.
     ///
     /// Cross-validated settings are provided as a JSON Map, where the key is the extension name and
@@ -66,6 +83,16 @@ pub trait TypeErasedModel: Debug {
         validated_settings: Option<serde_json::Value>,
     ) -> Result<(), BottlerocketSettingError>;
 
+    /// Returns a warning for each deprecated field name used in `value`.
+    fn deprecation_warnings(&self, value: &serde_json::Value) -> Vec<DeprecationWarning>;
+
+    /// Returns the problems the model reports with `value` which don't make it invalid, such as a
+    /// certificate that has expired.
+    fn value_warnings(
+        &self,
+        value: serde_json::Value,
+    ) -> Result<Vec<FieldError>, BottlerocketSettingError>;
+
     /// Parses a JSON value into the underlying model type, then erases its type via the `Any` trait.
     ///
     /// This is useful for implementing efficient migrations.
@@ -115,20 +142,40 @@ impl<T: SettingsModel + 'static> TypeErasedModel for BottlerocketSetting<T> {
         );
         let current = current
             .map(|v| {
-                serde_json::from_value(v.clone()).context(error::DeserializeInputSnafu {
-                    version: T::get_version(),
-                    input: v,
-                    input_type: "current-value",
-                })
+                bottlerocket_deserialize_helper::from_model_value(v).context(
+                    error::DeserializeInputSnafu {
+                        version: T::get_version(),
+                        input_type: "current-value",
+                    },
+                )
             })
             .transpose()?;
 
-        let target =
-            serde_json::from_value(target.clone()).context(error::DeserializeInputSnafu {
+        let target: T = bottlerocket_deserialize_helper::from_model_value(target).context(
+            error::DeserializeInputSnafu {
                 version: T::get_version(),
-                input: target,
                 input_type: "target-value",
+            },
+        )?;
+        let checks = T::field_checks();
+        checks
+            .check_fields(&target)
+            .context(error::FieldValidationSnafu {
+                version: T::get_version(),
             })?;
+        if let Some(current) = current.as_ref() {
+            let changed = changed_immutable_fields(current, &target, checks.immutable_fields())
+                .context(error::SerializeValueSnafu {
+                    version: T::get_version(),
+                })?;
+            ensure!(
+                changed.is_empty(),
+                error::ImmutableFieldSnafu {
+                    version: T::get_version(),
+                    fields: changed,
+                }
+            );
+        }
         T::set(current, target)
             .map_err(Into::into)
             .context(error::SetSettingSnafu {
@@ -136,12 +183,26 @@ impl<T: SettingsModel + 'static> TypeErasedModel for BottlerocketSetting<T> {
             })
     }
 
-    #[instrument(skip(self), err)]
     fn generate(
         &self,
         existing_partial: Option<serde_json::Value>,
         dependent_settings: Option<serde_json::Value>,
     ) -> Result<GenerateResult<serde_json::Value, serde_json::Value>, BottlerocketSettingError>
+    {
+        self.generate_with_environment(
+            existing_partial,
+            dependent_settings,
+            &Environment::default(),
+        )
+    }
+
+    #[instrument(skip(self, environment), err)]
+    fn generate_with_environment(
+        &self,
+        existing_partial: Option<serde_json::Value>,
+        dependent_settings: Option<serde_json::Value>,
+        environment: &Environment,
+    ) -> Result<GenerateResult<serde_json::Value, serde_json::Value>, BottlerocketSettingError>
     {
         debug!(
             existing_partial = existing_partial.as_ref().map(|v| v.to_string()),
@@ -152,14 +213,16 @@ impl<T: SettingsModel + 'static> TypeErasedModel for BottlerocketSetting<T> {
 
         let existing_partial = existing_partial
             .map(|v| {
-                serde_json::from_value(v.clone()).context(error::DeserializeInputSnafu {
-                    version: T::get_version(),
-                    input: v,
-                    input_type: "existing-partial",
-                })
+                bottlerocket_deserialize_helper::from_value(v).context(
+                    error::DeserializeInputSnafu {
+                        version: T::get_version(),
+                        input_type: "existing-partial",
+                    },
+                )
             })
             .transpose()?;
-        T::generate(existing_partial, dependent_settings)
+
+        T::generate_with_environment(existing_partial, dependent_settings, environment)
             .map_err(Into::into)
             .context(error::GenerateSettingSnafu {
                 version: T::get_version(),
@@ -185,11 +248,16 @@ impl<T: SettingsModel + 'static> TypeErasedModel for BottlerocketSetting<T> {
             "Validating value value for setting."
         );
 
-        let value =
-            serde_json::from_value(value.clone()).context(error::DeserializeInputSnafu {
+        let value: T = bottlerocket_deserialize_helper::from_model_value(value).context(
+            error::DeserializeInputSnafu {
                 version: T::get_version(),
-                input: value,
                 input_type: "value",
+            },
+        )?;
+        T::field_checks()
+            .check_fields(&value)
+            .context(error::FieldValidationSnafu {
+                version: T::get_version(),
             })?;
         T::validate(value, validated_settings)
             .map_err(Into::into)
@@ -222,6 +290,23 @@ impl<T: SettingsModel + 'static> TypeErasedModel for BottlerocketSetting<T> {
             })
     }
 
+    fn deprecation_warnings(&self, value: &serde_json::Value) -> Vec<DeprecationWarning> {
+        T::field_checks().find_deprecated_aliases(value)
+    }
+
+    fn value_warnings(
+        &self,
+        value: serde_json::Value,
+    ) -> Result<Vec<FieldError>, BottlerocketSettingError> {
+        let value: T = bottlerocket_deserialize_helper::from_model_value(value).context(
+            error::DeserializeInputSnafu {
+                version: T::get_version(),
+                input_type: "value",
+            },
+        )?;
+        Ok(value.warnings())
+    }
+
     #[instrument(skip(self), err)]
     fn parse_erased(
         &self,
@@ -233,10 +318,34 @@ impl<T: SettingsModel + 'static> TypeErasedModel for BottlerocketSetting<T> {
             "Parsing JSON value for setting."
         );
 
-        serde_json::from_value::<T>(value)
+        bottlerocket_deserialize_helper::from_model_value::<T>(value)
             .context(error::ParseSettingSnafu {
                 version: T::get_version(),
             })
             .map(|parsed| Box::new(parsed) as Box<dyn Any>)
     }
 }
+
+/// Returns the name of each of the given immutable fields which is set in `current`, but is changed
+/// or removed in `target`.
+fn changed_immutable_fields<T: Serialize>(
+    current: &T,
+    target: &T,
+    immutable_fields: &[&'static str],
+) -> Result<Vec<&'static str>, serde_json::Error> {
+    if immutable_fields.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    // Compare serialized values so that field names match those seen by users.
+    let current = serde_json::to_value(current)?;
+    let target = serde_json::to_value(target)?;
+    Ok(immutable_fields
+        .iter()
+        .copied()
+        .filter(|field| match current.get(field) {
+            None | Some(serde_json::Value::Null) => false,
+            current_value => current_value != target.get(field),
+        })
+        .collect())
+}
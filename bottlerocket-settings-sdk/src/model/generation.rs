@@ -0,0 +1,223 @@
+//! Provides [`GenerationInputs`], which gives settings models typed access to the other settings
+//! they need in order to generate their values.
+//!
+//! A model declares the settings its generation depends on as a struct implementing
+//! [`GenerationInputs`], with a field for each settings extension, named as the extension is:
+//!
+//! ```
+//! # use bottlerocket_settings_sdk::model::GenerationInputs;
+//! # use serde::Deserialize;
+//! # #[derive(Debug, Deserialize)]
+//! # struct NetworkSettingsV1 { hostname: Option<String> }
+//! #[derive(Debug, Deserialize)]
+//! struct KubernetesGenerationInputs {
+//!     network: NetworkSettingsV1,
+//! }
+//!
+//! impl GenerationInputs for KubernetesGenerationInputs {
+//!     fn required_settings() -> &'static [&'static str] {
+//!         &["network"]
+//!     }
+//! }
+//! ```
+//!
+//! Typed inputs are opt-in: models which use them read the settings passed to
+//! [`SettingsModel::generate`](super::SettingsModel::generate) with [`DependentSettings`]. Until
+//! all of the required settings have been generated, it returns
+//! [`GenerationDependencyError::MissingSettings`]; when a model fails with that error, the SDK
+//! responds with [`GenerateResult::NeedsData`](super::GenerateResult) and logs the names of the
+//! missing settings, rather than reporting an error:
+//!
+//! ```
+//! # use bottlerocket_settings_sdk::model::{GenerationDependencyError, GenerationInputs};
+//! # use bottlerocket_settings_sdk::DependentSettings;
+//! # use serde::Deserialize;
+//! # #[derive(Debug, Deserialize)]
+//! # struct NetworkSettingsV1 { hostname: Option<String> }
+//! # #[derive(Debug, Deserialize)]
+//! # struct KubernetesGenerationInputs { network: NetworkSettingsV1 }
+//! # impl GenerationInputs for KubernetesGenerationInputs {
+//! #     fn required_settings() -> &'static [&'static str] { &["network"] }
+//! # }
+//! fn generate(
+//!     dependent_settings: Option<serde_json::Value>,
+//! ) -> Result<Option<String>, GenerationDependencyError> {
+//!     let inputs: KubernetesGenerationInputs = DependentSettings::new(dependent_settings).get()?;
+//!     Ok(inputs.network.hostname)
+//! }
+//! ```
+//!
+//! Models must keep the error in the source chain of their own error, for example as the `source`
+//! of an error variant, so that the SDK can find it.
+use super::dependencies;
+use bottlerocket_deserialize_helper::DeserializeError;
+use serde::de::DeserializeOwned;
+use std::fmt::Display;
+
+/// The other settings that a model needs in order to generate its value, deserialized from the
+/// settings passed to `generate`.
+///
+/// Fields should be named after the settings extensions they hold. Unknown fields must be allowed,
+/// since the dependent settings may include extensions that the model doesn't use.
+pub trait GenerationInputs: DeserializeOwned {
+    /// Returns the names of the settings extensions which must be generated before this setting.
+    ///
+    /// Settings which are optional should be declared as `Option` fields and left out of this list.
+    fn required_settings() -> &'static [&'static str];
+}
+
+/// The settings that have been generated so far by the dependencies of a setting.
+///
+/// These are provided as a JSON Map, where the key is the extension name and the value is the value
+/// of that setting.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DependentSettings {
+    settings: Option<serde_json::Value>,
+}
+
+impl DependentSettings {
+    /// Creates a new [`DependentSettings`] from the settings passed to `generate`.
+    pub fn new(settings: Option<serde_json::Value>) -> Self {
+        Self { settings }
+    }
+
+    /// Deserializes the settings declared by `T`.
+    ///
+    /// Returns [`GenerationDependencyError::MissingSettings`] if any of the required settings
+    /// haven't been generated yet, or [`GenerationDependencyError::MalformedSetting`] if a setting
+    /// can't be deserialized.
+    pub fn get<T: GenerationInputs>(&self) -> Result<T, GenerationDependencyError> {
+        let missing =
+            dependencies::missing_settings(self.settings.as_ref(), T::required_settings());
+        if !missing.is_empty() {
+            return Err(GenerationDependencyError::MissingSettings { names: missing });
+        }
+
+        dependencies::deserialize_settings(self.settings.as_ref())
+            .map_err(|(name, source)| GenerationDependencyError::MalformedSetting { name, source })
+    }
+
+    /// Returns the underlying settings value, if any settings were given.
+    pub fn as_value(&self) -> Option<&serde_json::Value> {
+        self.settings.as_ref()
+    }
+
+    /// Consumes the [`DependentSettings`], returning the underlying settings value.
+    pub fn into_inner(self) -> Option<serde_json::Value> {
+        self.settings
+    }
+}
+
+/// The error returned when the settings needed to generate a setting aren't usable.
+#[derive(Debug)]
+pub enum GenerationDependencyError {
+    /// Settings which the model depends on haven't been generated yet.
+    MissingSettings {
+        /// The names of the missing settings extensions.
+        names: Vec<String>,
+    },
+
+    /// A setting which the model depends on could not be deserialized.
+    MalformedSetting {
+        /// The name of the settings extension at fault, if the problem is with a single setting.
+        name: Option<String>,
+        /// The underlying deserialization error, boxed to keep this error small.
+        source: Box<DeserializeError>,
+    },
+}
+
+impl GenerationDependencyError {
+    /// Returns the names of the settings which haven't been generated yet, if any.
+    pub fn missing(&self) -> &[String] {
+        match self {
+            Self::MissingSettings { names } => names,
+            Self::MalformedSetting { .. } => &[],
+        }
+    }
+}
+
+impl Display for GenerationDependencyError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::MissingSettings { names } => {
+                write!(f, "missing required settings: {}", names.join(", "))
+            }
+            Self::MalformedSetting {
+                name: Some(name),
+                source,
+            } => write!(f, "setting '{}' is malformed: {}", name, source),
+            Self::MalformedSetting { name: None, source } => {
+                write!(f, "settings are malformed: {}", source)
+            }
+        }
+    }
+}
+
+impl std::error::Error for GenerationDependencyError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::MalformedSetting { source, .. } => Some(source.as_ref()),
+            Self::MissingSettings { .. } => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use serde::Deserialize;
+    use serde_json::json;
+
+    #[derive(Debug, Deserialize)]
+    struct Network {
+        hostname: String,
+    }
+
+    #[derive(Debug, Deserialize)]
+    struct Inputs {
+        network: Network,
+        aws: Option<serde_json::Value>,
+    }
+
+    impl GenerationInputs for Inputs {
+        fn required_settings() -> &'static [&'static str] {
+            &["network", "kubernetes"]
+        }
+    }
+
+    #[test]
+    fn test_missing_settings_are_named() {
+        let settings = DependentSettings::new(Some(json!({"network": {"hostname": "localhost"}})));
+        let err = settings.get::<Inputs>().unwrap_err();
+        assert_eq!(err.missing(), &["kubernetes"]);
+        assert_eq!(err.to_string(), "missing required settings: kubernetes");
+
+        let err = DependentSettings::new(None).get::<Inputs>().unwrap_err();
+        assert_eq!(err.missing(), &["network", "kubernetes"]);
+    }
+
+    #[test]
+    fn test_get_settings() {
+        let settings = DependentSettings::new(Some(json!({
+            "network": {"hostname": "localhost"},
+            "kubernetes": {},
+        })));
+        let inputs: Inputs = settings.get().unwrap();
+        assert_eq!(inputs.network.hostname, "localhost");
+        assert!(inputs.aws.is_none());
+    }
+
+    #[test]
+    fn test_malformed_setting() {
+        let err = DependentSettings::new(Some(json!({
+            "network": {"hostname": false},
+            "kubernetes": {},
+        })))
+        .get::<Inputs>()
+        .unwrap_err();
+        assert!(err.missing().is_empty());
+        assert!(err
+            .to_string()
+            .starts_with("setting 'network' is malformed: invalid value for 'network.hostname'"));
+    }
+}
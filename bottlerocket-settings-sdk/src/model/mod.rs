@@ -1,26 +1,39 @@
 //! Provides the [`SettingsModel`] trait interface required to model new settings in the
 //! Bottlerocket API using the settings SDK.
-use crate::HelperDef;
+use crate::{Environment, HelperDef};
 use serde::Deserialize;
 use serde::{de::DeserializeOwned, Serialize};
 use std::collections::HashMap;
 use std::fmt::Debug;
 use std::marker::PhantomData;
 
+pub mod cross_validation;
+mod dependencies;
+pub mod deprecation;
 #[doc(hidden)]
 pub mod erased;
+pub mod generation;
+pub mod validation;
+pub use cross_validation::{CrossValidationError, CrossValidationInputs, ValidatedSettings};
+pub use deprecation::DeprecationWarning;
 pub use erased::{AsTypeErasedModel, TypeErasedModel};
 pub use error::BottlerocketSettingError;
+pub use generation::{DependentSettings, GenerationDependencyError, GenerationInputs};
+pub use validation::{FieldChecks, FieldError, FieldErrors, FieldPath, ValidateFields};
+
+pub use bottlerocket_deserialize_helper::{KnownNames, Names, PathSegment};
 
 /// This trait is required to model new settings in the Bottlerocket API using the settings SDK.
 ///
 /// To get started, you can describe the shape ("model") of your data using any struct which
-/// implements [`Serialize`](serde::Serialize), [`Deserialize`](serde::Deserialize), and
-/// [`Debug`](std::fmt::Debug), and then implement [`SettingsModel`]:
+/// implements [`Serialize`](serde::Serialize), [`Deserialize`](serde::Deserialize),
+/// [`Debug`](std::fmt::Debug), and [`KnownNames`], and then implement [`SettingsModel`].
+/// `#[model]` implements [`KnownNames`] so that deserialization errors can suggest the names of
+/// the model's fields; other types can rely on its default of knowing no names:
 ///
 /// ```
 /// # use anyhow::Result;
-/// # use bottlerocket_settings_sdk::{SettingsModel, GenerateResult};
+/// # use bottlerocket_settings_sdk::{KnownNames, SettingsModel, GenerateResult};
 /// # use serde::{Serialize, Deserialize};
 /// # use std::convert::Infallible;
 ///
@@ -31,6 +44,8 @@ pub use error::BottlerocketSettingError;
 ///     favorite_number: i64,
 /// }
 ///
+/// impl KnownNames for MySettings {}
+///
 /// // Implementing `bottlerocket_settings_sdk::SettingsModel` allows the settings SDK to expose
 /// // these settings in the Bottlerocket API.
 /// impl SettingsModel for MySettings {
@@ -64,7 +79,7 @@ pub use error::BottlerocketSettingError;
 ///
 /// Once you have implemented the interface for the model, you must also select
 /// [which migrator](crate::migrate) to use, and implement any traits required for that migrator.
-pub trait SettingsModel: Sized + Serialize + DeserializeOwned + Debug {
+pub trait SettingsModel: Sized + Serialize + DeserializeOwned + Debug + KnownNames {
     /// A type that represents a partially-constructed version of the implementor of this trait.
     ///
     /// This is used during settings generation to represent cases in which a user has given an
@@ -88,20 +103,60 @@ pub trait SettingsModel: Sized + Serialize + DeserializeOwned + Debug {
     /// completed. On each generation cycle, the settings extension is provided any values that it
     /// has previously generated, as well as all of the data that has thus far been generated by its
     /// dependencies.
+    ///
+    /// Models can use [`DependentSettings`] to read their dependencies as typed
+    /// [`GenerationInputs`]. If it reports that some of them haven't been generated yet, the SDK
+    /// responds with [`GenerateResult::NeedsData`] instead of an error.
     fn generate(
         existing_partial: Option<Self::PartialKind>,
         dependent_settings: Option<serde_json::Value>,
     ) -> Result<GenerateResult<Self::PartialKind, Self>, Self::ErrorKind>;
 
+    /// Generates default values at system start, reading any data about the host from
+    /// `environment`.
+    ///
+    /// The SDK calls this rather than `generate`, passing the [`Environment`] the settings
+    /// extension was built with. Models which read instance metadata, network interfaces, files, or
+    /// random numbers should override this and read them through `environment`, so that they can be
+    /// tested with stand-ins. By default, this calls `generate`.
+    fn generate_with_environment(
+        existing_partial: Option<Self::PartialKind>,
+        dependent_settings: Option<serde_json::Value>,
+        _environment: &Environment,
+    ) -> Result<GenerateResult<Self::PartialKind, Self>, Self::ErrorKind> {
+        Self::generate(existing_partial, dependent_settings)
+    }
+
     /// Validates this setting, allowing for cross-validation with other settings.
     ///
     /// Cross-validated settings are provided as a JSON Map, where the key is the extension name and
-    /// the value is the value of that setting.
+    /// the value is the value of that setting. Models can use [`ValidatedSettings`] to read the
+    /// settings they depend on as typed [`CrossValidationInputs`].
     fn validate(
         _value: Self,
         _validated_settings: Option<serde_json::Value>,
     ) -> Result<(), Self::ErrorKind>;
 
+    /// Returns the checks the SDK runs on the fields of this setting.
+    ///
+    /// The SDK checks field constraints on incoming values before `set` and `validate`, rejects
+    /// calls to `set` which would change immutable fields, and warns about fields given deprecated
+    /// names. By default, there are no such checks. Models whose fields declare them with the
+    /// `#[model]` macro's `#[validate(...)]`, `#[deprecated_alias = "..."]`, or `#[immutable]`
+    /// attributes return [`FieldChecks::declared`].
+    fn field_checks() -> FieldChecks<Self> {
+        FieldChecks::none()
+    }
+
+    /// Returns problems with this value which don't make it invalid, but which the user should
+    /// know about, such as a certificate that has expired.
+    ///
+    /// The SDK reports these as warnings from `set` and `validate`, after any deprecation
+    /// warnings.
+    fn warnings(&self) -> Vec<FieldError> {
+        Vec::new()
+    }
+
     /// Returns the set of template helpers associated with this settings model.
     fn template_helpers() -> Result<HashMap<String, Box<dyn HelperDef>>, Self::ErrorKind> {
         Ok(HashMap::new())
@@ -177,7 +232,9 @@ where
 
 mod error {
     #![allow(missing_docs)]
+    use super::{FieldErrors, GenerationDependencyError};
     use crate::HelperError;
+    use bottlerocket_deserialize_helper::DeserializeError;
     use snafu::Snafu;
 
     /// The error type returned when interacting with a user-defined
@@ -186,17 +243,35 @@ mod error {
     #[snafu(visibility(pub))]
     pub enum BottlerocketSettingError {
         #[snafu(display(
-            "Failed to deserialize '{}' input as settings value version '{}': {}\nValue: {}",
+            "Failed to deserialize '{}' input as settings value version '{}': {}",
             input_type,
             version,
             source,
-            serde_json::to_string_pretty(&input).unwrap_or(input.to_string()),
         ))]
         DeserializeInput {
             input_type: &'static str,
-            input: serde_json::Value,
             version: &'static str,
-            source: serde_json::Error,
+            source: DeserializeError,
+        },
+
+        #[snafu(display(
+            "Setting value (version '{}') failed field validation: {}",
+            version,
+            source
+        ))]
+        FieldValidation {
+            version: &'static str,
+            source: FieldErrors,
+        },
+
+        #[snafu(display(
+            "Setting value (version '{}') cannot change immutable fields once they are set: {}",
+            version,
+            fields.join(", ")
+        ))]
+        ImmutableField {
+            version: &'static str,
+            fields: Vec<&'static str>,
         },
 
         #[snafu(display(
@@ -246,7 +321,7 @@ mod error {
         ))]
         ParseSetting {
             version: &'static str,
-            source: serde_json::Error,
+            source: DeserializeError,
         },
 
         #[snafu(display(
@@ -261,6 +336,16 @@ mod error {
             source: serde_json::Error,
         },
 
+        #[snafu(display(
+            "Failed to serialize settings value (version '{}') for comparison: {}",
+            version,
+            source
+        ))]
+        SerializeValue {
+            version: &'static str,
+            source: serde_json::Error,
+        },
+
         #[snafu(display("Failed to run 'set' on setting version '{}': {}", version, source))]
         SetSetting {
             version: &'static str,
@@ -277,4 +362,24 @@ mod error {
             source: Box<dyn std::error::Error + Send + Sync + 'static>,
         },
     }
+
+    impl BottlerocketSettingError {
+        /// Returns the names of the settings which `generate` is waiting for, if the model failed
+        /// because some of the [`GenerationInputs`](super::GenerationInputs) it reads haven't been
+        /// generated yet.
+        pub fn missing_settings(&self) -> Option<&[String]> {
+            let mut chain =
+                std::iter::successors(Some(self as &(dyn std::error::Error + 'static)), |error| {
+                    error.source()
+                });
+            chain.find_map(
+                |error| match error.downcast_ref::<GenerationDependencyError>() {
+                    Some(GenerationDependencyError::MissingSettings { names }) => {
+                        Some(names.as_slice())
+                    }
+                    _ => None,
+                },
+            )
+        }
+    }
 }
@@ -0,0 +1,402 @@
+//! Provides the [`ValidateFields`] trait, which is used to check constraints declared on the fields
+//! of a settings model, as well as the deprecated names its fields can be given.
+//!
+//! Implementations are usually generated by the `#[model]` macro from `#[validate(...)]`,
+//! `#[deprecated_alias = "..."]`, and `#[immutable]` field attributes; see the
+//! `bottlerocket-model-derive` crate for details. Types without any such attributes don't implement
+//! the trait. Settings models return [`FieldChecks::declared`] from
+//! [`SettingsModel::field_checks`](super::SettingsModel::field_checks) to have the SDK run their
+//! checks.
+use super::DeprecationWarning;
+use bottlerocket_deserialize_helper::PathSegment;
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Display;
+
+/// Types that can check constraints on their fields, collecting every violation.
+pub trait ValidateFields {
+    /// Appends any constraint violations found in `self` to `errors`.
+    ///
+    /// `path` is the location of `self` within the settings value being checked, and is used as the
+    /// prefix for the paths of any reported violations.
+    fn collect_field_errors(&self, _path: &FieldPath, _errors: &mut Vec<FieldError>) {}
+
+    /// Checks all field constraints, returning every violation that was found.
+    fn check_fields(&self) -> Result<(), FieldErrors> {
+        let mut errors = Vec::new();
+        self.collect_field_errors(&FieldPath::default(), &mut errors);
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(FieldErrors { errors })
+        }
+    }
+
+    /// Appends a warning for each field of `value` that is given a deprecated name to `warnings`.
+    ///
+    /// `value` is the serialized form of this type, which still holds the names the user gave.
+    /// `path` is its location within the settings value being checked.
+    fn collect_deprecated_aliases(
+        _value: &Value,
+        _path: &FieldPath,
+        _warnings: &mut Vec<DeprecationWarning>,
+    ) {
+    }
+
+    /// Returns a warning for each field of `value`, the serialized form of this type, that is
+    /// given a deprecated name.
+    fn find_deprecated_aliases(value: &Value) -> Vec<DeprecationWarning> {
+        let mut warnings = Vec::new();
+        Self::collect_deprecated_aliases(value, &FieldPath::default(), &mut warnings);
+        warnings
+    }
+
+    /// Returns the names of fields which can't be changed once they have been set.
+    ///
+    /// The SDK rejects calls to `set` which would change or remove any of these fields from the
+    /// current value.
+    fn immutable_fields() -> &'static [&'static str] {
+        &[]
+    }
+}
+
+impl<T: ValidateFields> ValidateFields for Option<T> {
+    fn collect_field_errors(&self, path: &FieldPath, errors: &mut Vec<FieldError>) {
+        if let Some(value) = self {
+            value.collect_field_errors(path, errors);
+        }
+    }
+
+    fn collect_deprecated_aliases(
+        value: &Value,
+        path: &FieldPath,
+        warnings: &mut Vec<DeprecationWarning>,
+    ) {
+        T::collect_deprecated_aliases(value, path, warnings);
+    }
+}
+
+impl<T: ValidateFields> ValidateFields for Box<T> {
+    fn collect_field_errors(&self, path: &FieldPath, errors: &mut Vec<FieldError>) {
+        self.as_ref().collect_field_errors(path, errors);
+    }
+
+    fn collect_deprecated_aliases(
+        value: &Value,
+        path: &FieldPath,
+        warnings: &mut Vec<DeprecationWarning>,
+    ) {
+        T::collect_deprecated_aliases(value, path, warnings);
+    }
+}
+
+impl<T: ValidateFields> ValidateFields for Vec<T> {
+    fn collect_field_errors(&self, path: &FieldPath, errors: &mut Vec<FieldError>) {
+        for (index, value) in self.iter().enumerate() {
+            value.collect_field_errors(&path.join(index), errors);
+        }
+    }
+
+    fn collect_deprecated_aliases(
+        value: &Value,
+        path: &FieldPath,
+        warnings: &mut Vec<DeprecationWarning>,
+    ) {
+        for (index, element) in value.as_array().into_iter().flatten().enumerate() {
+            T::collect_deprecated_aliases(element, &path.join(index), warnings);
+        }
+    }
+}
+
+/// Checks each entry of a serialized map for deprecated names.
+fn collect_map_deprecated_aliases<V: ValidateFields>(
+    value: &Value,
+    path: &FieldPath,
+    warnings: &mut Vec<DeprecationWarning>,
+) {
+    for (key, entry) in value.as_object().into_iter().flatten() {
+        V::collect_deprecated_aliases(entry, &path.join(key.as_str()), warnings);
+    }
+}
+
+impl<K: Display, V: ValidateFields, S> ValidateFields for HashMap<K, V, S> {
+    fn collect_field_errors(&self, path: &FieldPath, errors: &mut Vec<FieldError>) {
+        // Sort by key so that errors are reported in a stable order.
+        let mut entries: Vec<_> = self
+            .iter()
+            .map(|(key, value)| (key.to_string(), value))
+            .collect();
+        entries.sort_by(|(lhs, _), (rhs, _)| lhs.cmp(rhs));
+
+        for (key, value) in entries {
+            value.collect_field_errors(&path.join(key), errors);
+        }
+    }
+
+    fn collect_deprecated_aliases(
+        value: &Value,
+        path: &FieldPath,
+        warnings: &mut Vec<DeprecationWarning>,
+    ) {
+        collect_map_deprecated_aliases::<V>(value, path, warnings);
+    }
+}
+
+impl<K: Display, V: ValidateFields> ValidateFields for BTreeMap<K, V> {
+    fn collect_field_errors(&self, path: &FieldPath, errors: &mut Vec<FieldError>) {
+        for (key, value) in self {
+            value.collect_field_errors(&path.join(key.to_string()), errors);
+        }
+    }
+
+    fn collect_deprecated_aliases(
+        value: &Value,
+        path: &FieldPath,
+        warnings: &mut Vec<DeprecationWarning>,
+    ) {
+        collect_map_deprecated_aliases::<V>(value, path, warnings);
+    }
+}
+
+/// The checks the SDK runs on the fields of a settings model, as returned by
+/// [`SettingsModel::field_checks`](super::SettingsModel::field_checks).
+pub struct FieldChecks<T> {
+    check_fields: fn(&T) -> Result<(), FieldErrors>,
+    find_deprecated_aliases: fn(&Value) -> Vec<DeprecationWarning>,
+    immutable_fields: fn() -> &'static [&'static str],
+}
+
+impl<T> FieldChecks<T> {
+    /// Returns checks which accept every value, find no deprecated names, and allow every field to
+    /// change.
+    pub fn none() -> Self {
+        Self {
+            check_fields: |_| Ok(()),
+            find_deprecated_aliases: |_| Vec::new(),
+            immutable_fields: || &[],
+        }
+    }
+
+    /// Checks all field constraints of `value`, returning every violation that was found.
+    pub fn check_fields(&self, value: &T) -> Result<(), FieldErrors> {
+        (self.check_fields)(value)
+    }
+
+    /// Returns a warning for each field of `value`, the serialized form of the model, that is given
+    /// a deprecated name.
+    pub fn find_deprecated_aliases(&self, value: &Value) -> Vec<DeprecationWarning> {
+        (self.find_deprecated_aliases)(value)
+    }
+
+    /// Returns the names of fields which can't be changed once they have been set.
+    pub fn immutable_fields(&self) -> &'static [&'static str] {
+        (self.immutable_fields)()
+    }
+}
+
+impl<T: ValidateFields> FieldChecks<T> {
+    /// Returns the checks declared by the model's [`ValidateFields`] implementation.
+    pub fn declared() -> Self {
+        Self {
+            check_fields: T::check_fields,
+            find_deprecated_aliases: T::find_deprecated_aliases,
+            immutable_fields: T::immutable_fields,
+        }
+    }
+}
+
+impl<T> Default for FieldChecks<T> {
+    fn default() -> Self {
+        Self::none()
+    }
+}
+
+impl<T> std::fmt::Debug for FieldChecks<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FieldChecks")
+            .field("immutable_fields", &self.immutable_fields())
+            .finish_non_exhaustive()
+    }
+}
+
+/// The location of a field within a settings value, as the field names, map keys, and sequence
+/// indexes leading to it from the root of the value.
+///
+/// Paths are displayed with `.` between segments, e.g. `static-pods.my-pod.manifest`, but the
+/// segments are kept separate, since map keys such as `docker.io` can contain dots themselves.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FieldPath {
+    segments: Vec<PathSegment>,
+}
+
+impl FieldPath {
+    /// Creates a path from its segments, starting from the root of the value.
+    pub fn new<I, S>(segments: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<PathSegment>,
+    {
+        Self {
+            segments: segments.into_iter().map(Into::into).collect(),
+        }
+    }
+
+    /// Returns the path of `child`, a field name, map key, or index within the value at this path.
+    pub fn join<S: Into<PathSegment>>(&self, child: S) -> Self {
+        let mut segments = self.segments.clone();
+        segments.push(child.into());
+        Self { segments }
+    }
+
+    /// Returns the steps along the path, from the root of the value.
+    pub fn segments(&self) -> &[PathSegment] {
+        &self.segments
+    }
+}
+
+impl From<&str> for FieldPath {
+    /// Creates the path of a field at the root of the value.
+    fn from(name: &str) -> Self {
+        Self::new([name])
+    }
+}
+
+impl From<&FieldPath> for FieldPath {
+    fn from(path: &FieldPath) -> Self {
+        path.clone()
+    }
+}
+
+impl Display for FieldPath {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let segments: Vec<String> = self.segments.iter().map(ToString::to_string).collect();
+        f.write_str(&segments.join("."))
+    }
+}
+
+/// A single constraint violation found in a settings value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldError {
+    path: FieldPath,
+    message: String,
+}
+
+impl FieldError {
+    /// Creates a new [`FieldError`] for the field at `path`.
+    pub fn new<P, M>(path: P, message: M) -> Self
+    where
+        P: Into<FieldPath>,
+        M: Into<String>,
+    {
+        Self {
+            path: path.into(),
+            message: message.into(),
+        }
+    }
+
+    /// Returns the path of the offending field, e.g. `static-pods.my-pod.manifest`.
+    pub fn path(&self) -> &FieldPath {
+        &self.path
+    }
+
+    /// Returns a description of the violated constraint.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl Display for FieldError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "'{}' {}", self.path, self.message)
+    }
+}
+
+/// The set of constraint violations found in a settings value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldErrors {
+    errors: Vec<FieldError>,
+}
+
+impl FieldErrors {
+    /// Returns each of the violations that were found.
+    pub fn errors(&self) -> &[FieldError] {
+        &self.errors
+    }
+}
+
+impl From<Vec<FieldError>> for FieldErrors {
+    fn from(errors: Vec<FieldError>) -> Self {
+        Self { errors }
+    }
+}
+
+impl Display for FieldErrors {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let errors: Vec<String> = self.errors.iter().map(ToString::to_string).collect();
+        f.write_str(&errors.join("; "))
+    }
+}
+
+impl std::error::Error for FieldErrors {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    struct Positive(i64);
+
+    impl ValidateFields for Positive {
+        fn collect_field_errors(&self, path: &FieldPath, errors: &mut Vec<FieldError>) {
+            if self.0 <= 0 {
+                errors.push(FieldError::new(path, "must be positive"));
+            }
+        }
+    }
+
+    #[test]
+    fn test_collections_extend_paths() {
+        let values = HashMap::from([
+            ("b", vec![Positive(1), Positive(-1)]),
+            ("a", vec![Positive(0)]),
+        ]);
+
+        let mut errors = Vec::new();
+        values.collect_field_errors(&FieldPath::new(["scores"]), &mut errors);
+
+        assert_eq!(
+            errors,
+            vec![
+                FieldError::new(FieldPath::new(["scores", "a"]).join(0), "must be positive"),
+                FieldError::new(FieldPath::new(["scores", "b"]).join(1), "must be positive"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_map_keys_are_single_segments() {
+        let values = BTreeMap::from([("docker.io", Positive(0))]);
+
+        let err = values.check_fields().unwrap_err();
+
+        assert_eq!(
+            err.errors(),
+            &[FieldError::new(
+                FieldPath::new(["docker.io"]),
+                "must be positive"
+            )]
+        );
+        assert_eq!(err.to_string(), "'docker.io' must be positive");
+    }
+
+    #[test]
+    fn test_check_fields_display() {
+        let err = vec![Some(Positive(-1)), None, Some(Positive(-2))]
+            .check_fields()
+            .unwrap_err();
+
+        assert_eq!(
+            err.to_string(),
+            "'0' must be positive; '2' must be positive"
+        );
+    }
+}
@@ -1,6 +1,6 @@
 use bottlerocket_settings_sdk::{
-    BottlerocketSetting, GenerateResult, LinearMigratorExtensionBuilder, LinearlyMigrateable,
-    NoMigration, SettingsModel,
+    BottlerocketSetting, GenerateResult, KnownNames, LinearMigratorExtensionBuilder,
+    LinearlyMigrateable, NoMigration, SettingsModel,
 };
 use serde::{Deserialize, Serialize};
 use snafu::Snafu;
@@ -33,6 +33,8 @@ pub struct ModelA;
 #[derive(Debug, Serialize, Deserialize, Default, PartialEq, Eq)]
 pub struct ModelB;
 
+impl KnownNames for ModelA {}
+
 impl SettingsModel for ModelA {
     type PartialKind = Self;
     type ErrorKind = MyError;
@@ -71,6 +73,8 @@ impl LinearlyMigrateable for ModelA {
     }
 }
 
+impl KnownNames for ModelB {}
+
 impl SettingsModel for ModelB {
     type PartialKind = Self;
     type ErrorKind = MyError;
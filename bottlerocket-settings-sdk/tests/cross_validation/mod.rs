@@ -0,0 +1,132 @@
+use super::*;
+use anyhow::ensure;
+use bottlerocket_settings_sdk::{
+    BottlerocketSetting, CrossValidationInputs, GenerateResult, KnownNames, NullMigrator,
+    NullMigratorExtensionBuilder, SettingsModel, ValidatedSettings,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+/// A model whose port must not be one of the ports reserved by the network settings.
+#[derive(Debug, Serialize, Deserialize, Default)]
+#[serde(rename_all = "kebab-case")]
+pub struct ServerV1 {
+    port: Option<u16>,
+}
+
+/// The network settings that `ServerV1` cross-validates against.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct NetworkV1 {
+    #[serde(default)]
+    reserved_ports: Vec<u16>,
+}
+
+/// The settings that `ServerV1` depends on.
+#[derive(Debug, Deserialize)]
+pub struct ServerDependencies {
+    network: NetworkV1,
+}
+
+impl CrossValidationInputs for ServerDependencies {
+    fn required_settings() -> &'static [&'static str] {
+        &["network"]
+    }
+}
+
+type Result<T> = anyhow::Result<T>;
+
+impl KnownNames for ServerV1 {}
+
+impl SettingsModel for ServerV1 {
+    type PartialKind = Self;
+    type ErrorKind = anyhow::Error;
+
+    fn get_version() -> &'static str {
+        "v1"
+    }
+
+    fn set(_current_value: Option<Self>, _target: Self) -> Result<()> {
+        Ok(())
+    }
+
+    fn generate(
+        existing_partial: Option<Self::PartialKind>,
+        _dependent_settings: Option<serde_json::Value>,
+    ) -> Result<GenerateResult<Self::PartialKind, Self>> {
+        Ok(GenerateResult::Complete(
+            existing_partial.unwrap_or_default(),
+        ))
+    }
+
+    fn validate(value: Self, validated_settings: Option<serde_json::Value>) -> Result<()> {
+        let dependencies: ServerDependencies = ValidatedSettings::new(validated_settings).get()?;
+        if let Some(port) = value.port {
+            ensure!(
+                !dependencies.network.reserved_ports.contains(&port),
+                "port {} is reserved by the network",
+                port
+            );
+        }
+        Ok(())
+    }
+}
+
+fn server_extension(
+) -> SettingsExtension<NullMigrator, Box<dyn bottlerocket_settings_sdk::model::TypeErasedModel>> {
+    NullMigratorExtensionBuilder::with_name("server")
+        .with_models(vec![BottlerocketSetting::<ServerV1>::model()])
+        .build()
+        .expect("Failed to build server settings extension")
+}
+
+#[test]
+fn test_cross_validation_passes() {
+    assert!(validate_cli(
+        server_extension(),
+        "v1",
+        json!({"port": 8080}),
+        Some(json!({"network": {"reserved-ports": [22]}, "motd": "hi"})),
+    )
+    .is_ok());
+}
+
+#[test]
+fn test_cross_validation_rejects() {
+    let err = validate_cli(
+        server_extension(),
+        "v1",
+        json!({"port": 22}),
+        Some(json!({"network": {"reserved-ports": [22]}})),
+    )
+    .unwrap_err();
+    assert!(format!("{:#}", err)
+        .contains("Failed to run 'validate' on setting version 'v1': port 22 is reserved"));
+}
+
+#[test]
+fn test_missing_dependency() {
+    for required_settings in [None, Some(json!({"motd": "hi"}))] {
+        let err = validate_cli(
+            server_extension(),
+            "v1",
+            json!({"port": 22}),
+            required_settings,
+        )
+        .unwrap_err();
+        assert!(format!("{:#}", err).contains("missing required settings: network"));
+    }
+}
+
+#[test]
+fn test_malformed_dependency() {
+    let err = validate_cli(
+        server_extension(),
+        "v1",
+        json!({"port": 22}),
+        Some(json!({"network": {"reserved-ports": "22"}})),
+    )
+    .unwrap_err();
+    assert!(format!("{:#}", err)
+        .contains("setting 'network' is malformed: invalid value for 'network.reserved-ports'"));
+}
@@ -0,0 +1,111 @@
+use super::*;
+use bottlerocket_model_derive::model;
+use bottlerocket_settings_sdk::{
+    BottlerocketSetting, DeprecationWarning, FieldChecks, GenerateResult, NullMigrator,
+    NullMigratorExtensionBuilder, SettingsModel,
+};
+use serde_json::json;
+use std::collections::HashMap;
+use std::convert::Infallible;
+
+/// A model whose `server-name` field was previously called `server`.
+#[model(impl_default = true)]
+struct ServerV1 {
+    #[deprecated_alias = "server"]
+    server_name: String,
+    #[validate(nested)]
+    #[deprecated_alias = "backend"]
+    backends: HashMap<String, Backend>,
+}
+
+/// A nested model whose `port` field was previously called `listen-port`.
+#[model(impl_default = true)]
+struct Backend {
+    #[deprecated_alias = "listen-port"]
+    port: u16,
+}
+
+type Result<T> = std::result::Result<T, Infallible>;
+
+impl SettingsModel for ServerV1 {
+    type PartialKind = Self;
+    type ErrorKind = Infallible;
+
+    fn get_version() -> &'static str {
+        "v1"
+    }
+
+    fn set(_current_value: Option<Self>, _target: Self) -> Result<()> {
+        Ok(())
+    }
+
+    fn generate(
+        existing_partial: Option<Self::PartialKind>,
+        _dependent_settings: Option<serde_json::Value>,
+    ) -> Result<GenerateResult<Self::PartialKind, Self>> {
+        Ok(GenerateResult::Complete(
+            existing_partial.unwrap_or_default(),
+        ))
+    }
+
+    fn validate(_value: Self, _validated_settings: Option<serde_json::Value>) -> Result<()> {
+        Ok(())
+    }
+
+    fn field_checks() -> FieldChecks<Self> {
+        FieldChecks::declared()
+    }
+}
+
+fn server_extension(
+) -> SettingsExtension<NullMigrator, Box<dyn bottlerocket_settings_sdk::model::TypeErasedModel>> {
+    NullMigratorExtensionBuilder::with_name("server")
+        .with_models(vec![BottlerocketSetting::<ServerV1>::model()])
+        .build()
+        .expect("Failed to build server settings extension")
+}
+
+fn deprecations(value: serde_json::Value) -> Vec<DeprecationWarning> {
+    let extension = server_extension();
+    let model = extension.model("v1").unwrap().as_model();
+    model.deprecation_warnings(&value)
+}
+
+#[test]
+fn test_reports_deprecated_aliases() {
+    assert_eq!(
+        deprecations(json!({"server": "example.com"})),
+        vec![DeprecationWarning::new("server", "server-name")]
+    );
+    assert!(deprecations(json!({"server-name": "example.com"})).is_empty());
+}
+
+#[test]
+fn test_deprecated_aliases_accepted() {
+    // Warnings are reported on stderr, leaving the output of `set` and `validate` empty.
+    assert!(set_cli(server_extension(), "v1", json!({"server": "example.com"})).is_ok());
+    assert!(validate_cli(
+        server_extension(),
+        "v1",
+        json!({"server": "example.com"}),
+        None
+    )
+    .is_ok());
+}
+
+#[test]
+fn test_nested_deprecated_aliases() {
+    let warnings: Vec<String> = deprecations(json!({
+        "backend": {"api": {"listen-port": 81}, "db": {"port": 82}},
+    }))
+    .iter()
+    .map(ToString::to_string)
+    .collect();
+    assert_eq!(
+        warnings,
+        vec![
+            "'backend' is deprecated; use 'backends' instead",
+            "'backend.api.listen-port' is deprecated; use 'backend.api.port' instead",
+        ]
+    );
+}
@@ -0,0 +1,129 @@
+use super::*;
+use bottlerocket_settings_sdk::environment::stand_in::{FixedRandom, StaticFiles};
+use bottlerocket_settings_sdk::environment::{ImdsClient, MetadataStub};
+use bottlerocket_settings_sdk::{
+    BottlerocketSetting, Environment, GenerateResult, KnownNames, NullMigrator,
+    NullMigratorExtensionBuilder, SettingsModel,
+};
+use serde::{Deserialize, Serialize};
+use std::convert::Infallible;
+use std::path::Path;
+
+/// A model whose values are generated from data about the host.
+#[derive(Debug, Serialize, Deserialize, Default, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub struct InstanceV1 {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    local_ip: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    hostname: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    seed: Option<u64>,
+}
+
+type Result<T> = std::result::Result<T, Infallible>;
+
+impl KnownNames for InstanceV1 {}
+
+impl SettingsModel for InstanceV1 {
+    type PartialKind = Self;
+    type ErrorKind = Infallible;
+
+    fn get_version() -> &'static str {
+        "v1"
+    }
+
+    fn set(_current_value: Option<Self>, _target: Self) -> Result<()> {
+        Ok(())
+    }
+
+    fn generate(
+        existing_partial: Option<Self::PartialKind>,
+        _dependent_settings: Option<serde_json::Value>,
+    ) -> Result<GenerateResult<Self::PartialKind, Self>> {
+        Ok(GenerateResult::Complete(
+            existing_partial.unwrap_or_default(),
+        ))
+    }
+
+    fn generate_with_environment(
+        existing_partial: Option<Self::PartialKind>,
+        _dependent_settings: Option<serde_json::Value>,
+        environment: &Environment,
+    ) -> Result<GenerateResult<Self::PartialKind, Self>> {
+        let partial = existing_partial.unwrap_or_default();
+        Ok(GenerateResult::Complete(InstanceV1 {
+            local_ip: environment
+                .metadata()
+                .metadata("meta-data/local-ipv4")
+                .ok()
+                .flatten(),
+            hostname: environment
+                .files()
+                .read_to_string(Path::new("/etc/hostname"))
+                .ok()
+                .flatten()
+                .map(|hostname| hostname.trim().to_string()),
+            seed: Some(
+                partial
+                    .seed
+                    .unwrap_or_else(|| environment.random().random_range(0..100)),
+            ),
+        }))
+    }
+
+    fn validate(_value: Self, _validated_settings: Option<serde_json::Value>) -> Result<()> {
+        Ok(())
+    }
+}
+
+fn instance_extension(
+    environment: Environment,
+) -> SettingsExtension<NullMigrator, Box<dyn bottlerocket_settings_sdk::model::TypeErasedModel>> {
+    NullMigratorExtensionBuilder::with_name("instance")
+        .with_models(vec![BottlerocketSetting::<InstanceV1>::model()])
+        .with_environment(environment)
+        .build()
+        .expect("Failed to build instance settings extension")
+}
+
+#[test]
+fn test_generate_with_environment() {
+    let stub = MetadataStub::start([("meta-data/local-ipv4", "10.0.0.1")]).unwrap();
+    let environment = Environment::in_memory()
+        .with_metadata(ImdsClient::new(stub.endpoint()))
+        .with_files(StaticFiles::new([("/etc/hostname", "ip-10-0-0-1\n")]))
+        .with_random(FixedRandom::new([42]));
+
+    let result: GenerateResult<InstanceV1, InstanceV1> =
+        generate_cli(instance_extension(environment), "v1", None, None).unwrap();
+
+    assert_eq!(
+        result,
+        GenerateResult::Complete(InstanceV1 {
+            local_ip: Some("10.0.0.1".to_string()),
+            hostname: Some("ip-10-0-0-1".to_string()),
+            seed: Some(42),
+        })
+    );
+}
+
+#[test]
+fn test_generate_with_empty_environment() {
+    let result: GenerateResult<InstanceV1, InstanceV1> = generate_cli(
+        instance_extension(Environment::in_memory()),
+        "v1",
+        None,
+        None,
+    )
+    .unwrap();
+
+    assert_eq!(
+        result,
+        GenerateResult::Complete(InstanceV1 {
+            local_ip: None,
+            hostname: None,
+            seed: Some(0),
+        })
+    );
+}
@@ -0,0 +1,89 @@
+use super::*;
+use bottlerocket_model_derive::model;
+use bottlerocket_settings_sdk::{
+    BottlerocketSetting, FieldChecks, GenerateResult, NullMigrator, NullMigratorExtensionBuilder,
+    SettingsModel,
+};
+use serde_json::json;
+use std::convert::Infallible;
+
+/// A model whose field constraints are checked by the SDK before `set` and `validate` are called.
+#[model(impl_default = true)]
+struct PortRangeV1 {
+    #[validate(range(max = 1024))]
+    low: u16,
+    #[validate(range(min = 1), requires = "low")]
+    high: u16,
+}
+
+type Result<T> = std::result::Result<T, Infallible>;
+
+impl SettingsModel for PortRangeV1 {
+    type PartialKind = Self;
+    type ErrorKind = Infallible;
+
+    fn get_version() -> &'static str {
+        "v1"
+    }
+
+    fn set(_current_value: Option<Self>, _target: Self) -> Result<()> {
+        Ok(())
+    }
+
+    fn generate(
+        existing_partial: Option<Self::PartialKind>,
+        _dependent_settings: Option<serde_json::Value>,
+    ) -> Result<GenerateResult<Self::PartialKind, Self>> {
+        Ok(GenerateResult::Complete(
+            existing_partial.unwrap_or_default(),
+        ))
+    }
+
+    fn validate(_value: Self, _validated_settings: Option<serde_json::Value>) -> Result<()> {
+        Ok(())
+    }
+
+    fn field_checks() -> FieldChecks<Self> {
+        FieldChecks::declared()
+    }
+}
+
+fn port_range_extension(
+) -> SettingsExtension<NullMigrator, Box<dyn bottlerocket_settings_sdk::model::TypeErasedModel>> {
+    NullMigratorExtensionBuilder::with_name("port-range")
+        .with_models(vec![BottlerocketSetting::<PortRangeV1>::model()])
+        .build()
+        .expect("Failed to build port-range settings extension")
+}
+
+#[test]
+fn test_set_checks_fields() {
+    assert!(set_cli(port_range_extension(), "v1", json!({"low": 10, "high": 20})).is_ok());
+
+    let err = set_cli(
+        port_range_extension(),
+        "v1",
+        json!({"low": 2000, "high": 0}),
+    )
+    .unwrap_err();
+    let message = format!("{:#}", err);
+    assert!(message.contains("'high' must be at least 1"), "{}", message);
+    assert!(
+        message.contains("'low' must be at most 1024"),
+        "{}",
+        message
+    );
+    assert!(
+        message.contains("'low' must be at most 1024"),
+        "{}",
+        message
+    );
+}
+
+#[test]
+fn test_validate_checks_fields() {
+    assert!(validate_cli(port_range_extension(), "v1", json!({"low": 10}), None).is_ok());
+
+    let err = validate_cli(port_range_extension(), "v1", json!({"high": 10}), None).unwrap_err();
+    assert!(format!("{:#}", err).contains("'high' requires 'low' to be set"));
+}
@@ -0,0 +1,148 @@
+use super::*;
+use anyhow::Context;
+use bottlerocket_settings_sdk::{
+    model::TypeErasedModel, BottlerocketSetting, DependentSettings, GenerateResult,
+    GenerationInputs, KnownNames, NullMigrator, NullMigratorExtensionBuilder, SettingsModel,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+/// A model whose node name is generated from the network's hostname.
+#[derive(Debug, Serialize, Deserialize, Default, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub struct NodeV1 {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    node_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    labels: Option<Vec<String>>,
+}
+
+/// The network settings that `NodeV1` is generated from.
+#[derive(Debug, Deserialize)]
+pub struct NetworkV1 {
+    hostname: String,
+}
+
+/// The settings that must be generated before `NodeV1`.
+#[derive(Debug, Deserialize)]
+pub struct NodeInputs {
+    network: NetworkV1,
+}
+
+impl GenerationInputs for NodeInputs {
+    fn required_settings() -> &'static [&'static str] {
+        &["network"]
+    }
+}
+
+type Result<T> = anyhow::Result<T>;
+
+impl KnownNames for NodeV1 {}
+
+impl SettingsModel for NodeV1 {
+    type PartialKind = Self;
+    type ErrorKind = anyhow::Error;
+
+    fn get_version() -> &'static str {
+        "v1"
+    }
+
+    fn set(_current_value: Option<Self>, _target: Self) -> Result<()> {
+        Ok(())
+    }
+
+    fn generate(
+        existing_partial: Option<Self::PartialKind>,
+        dependent_settings: Option<serde_json::Value>,
+    ) -> Result<GenerateResult<Self::PartialKind, Self>> {
+        let inputs: NodeInputs = DependentSettings::new(dependent_settings)
+            .get()
+            .context("Failed to read the network settings")?;
+        let partial = existing_partial.unwrap_or_default();
+        Ok(GenerateResult::Complete(NodeV1 {
+            node_name: Some(partial.node_name.unwrap_or(inputs.network.hostname)),
+            ..partial
+        }))
+    }
+
+    fn validate(_value: Self, _validated_settings: Option<serde_json::Value>) -> Result<()> {
+        Ok(())
+    }
+}
+
+fn node_extension(
+) -> SettingsExtension<NullMigrator, Box<dyn bottlerocket_settings_sdk::model::TypeErasedModel>> {
+    NullMigratorExtensionBuilder::with_name("node")
+        .with_models(vec![BottlerocketSetting::<NodeV1>::model()])
+        .build()
+        .expect("Failed to build node settings extension")
+}
+
+#[test]
+fn test_generate_from_dependencies() {
+    let result: GenerateResult<NodeV1, NodeV1> = generate_cli(
+        node_extension(),
+        "v1",
+        None,
+        Some(json!({"network": {"hostname": "ip-10-0-0-1"}})),
+    )
+    .unwrap();
+
+    assert_eq!(
+        result,
+        GenerateResult::Complete(NodeV1 {
+            node_name: Some("ip-10-0-0-1".to_string()),
+            labels: None,
+        })
+    );
+}
+
+#[test]
+fn test_needs_data_without_dependencies() {
+    let partial = json!({"labels": ["a"]});
+    for required_settings in [None, Some(json!({"motd": "hi"}))] {
+        let result: GenerateResult<NodeV1, NodeV1> = generate_cli(
+            node_extension(),
+            "v1",
+            Some(partial.clone()),
+            required_settings,
+        )
+        .unwrap();
+
+        assert_eq!(
+            result,
+            GenerateResult::NeedsData(Some(NodeV1 {
+                node_name: None,
+                labels: Some(vec!["a".to_string()]),
+            }))
+        );
+    }
+}
+
+#[test]
+fn test_missing_settings() {
+    let model = BottlerocketSetting::<NodeV1>::model();
+    for dependent_settings in [None, Some(json!({"motd": "hi"}))] {
+        let err = model.generate(None, dependent_settings).unwrap_err();
+        assert_eq!(err.missing_settings(), Some(&["network".to_string()][..]));
+    }
+
+    let err = model
+        .generate(None, Some(json!({"network": {"hostname": 42}})))
+        .unwrap_err();
+    assert_eq!(err.missing_settings(), None);
+}
+
+#[test]
+fn test_malformed_dependency() {
+    let err = generate_cli::<_, _, NodeV1, NodeV1>(
+        node_extension(),
+        "v1",
+        None,
+        Some(json!({"network": {"hostname": 42}})),
+    )
+    .unwrap_err();
+
+    assert!(format!("{:#}", err)
+        .contains("Failed to run 'generate' on setting version 'v1': Failed to read the network"));
+}
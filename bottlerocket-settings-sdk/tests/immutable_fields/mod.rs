@@ -0,0 +1,128 @@
+use super::*;
+use bottlerocket_settings_sdk::{
+    BottlerocketSetting, FieldChecks, GenerateResult, KnownNames, NullMigrator,
+    NullMigratorExtensionBuilder, SettingsModel, ValidateFields,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::convert::Infallible;
+
+/// A model whose `seed` can't be changed once it has been set.
+#[derive(Debug, Serialize, Deserialize, Default, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub struct SeedV1 {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    seed: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    ignore_waves: Option<bool>,
+}
+
+type Result<T> = std::result::Result<T, Infallible>;
+
+impl ValidateFields for SeedV1 {
+    fn immutable_fields() -> &'static [&'static str] {
+        &["seed"]
+    }
+}
+
+impl KnownNames for SeedV1 {}
+
+impl SettingsModel for SeedV1 {
+    type PartialKind = Self;
+    type ErrorKind = Infallible;
+
+    fn get_version() -> &'static str {
+        "v1"
+    }
+
+    fn set(_current_value: Option<Self>, _target: Self) -> Result<()> {
+        Ok(())
+    }
+
+    fn generate(
+        existing_partial: Option<Self::PartialKind>,
+        _dependent_settings: Option<serde_json::Value>,
+    ) -> Result<GenerateResult<Self::PartialKind, Self>> {
+        let partial = existing_partial.unwrap_or_default();
+        Ok(GenerateResult::Complete(SeedV1 {
+            seed: Some(partial.seed.unwrap_or(42)),
+            ..partial
+        }))
+    }
+
+    fn validate(_value: Self, _validated_settings: Option<serde_json::Value>) -> Result<()> {
+        Ok(())
+    }
+
+    fn field_checks() -> FieldChecks<Self> {
+        FieldChecks::declared()
+    }
+}
+
+fn seed_extension(
+) -> SettingsExtension<NullMigrator, Box<dyn bottlerocket_settings_sdk::model::TypeErasedModel>> {
+    NullMigratorExtensionBuilder::with_name("seed")
+        .with_models(vec![BottlerocketSetting::<SeedV1>::model()])
+        .build()
+        .expect("Failed to build seed settings extension")
+}
+
+fn set_with_current_cli(
+    value: serde_json::Value,
+    current_value: serde_json::Value,
+) -> anyhow::Result<String> {
+    seed_extension()
+        .try_run_with_args([
+            "extension",
+            "proto1",
+            "set",
+            "--setting-version",
+            "v1",
+            "--value",
+            value.to_string().as_str(),
+            "--current-value",
+            current_value.to_string().as_str(),
+        ])
+        .context("Failed to run settings extension CLI")
+}
+
+#[test]
+fn test_initial_set_is_allowed() {
+    assert!(set_cli(seed_extension(), "v1", json!({"seed": 7})).is_ok());
+    assert!(set_with_current_cli(json!({"seed": 7}), json!({"ignore-waves": true})).is_ok());
+}
+
+#[test]
+fn test_unchanged_immutable_field_is_allowed() {
+    assert!(set_with_current_cli(
+        json!({"seed": 7, "ignore-waves": true}),
+        json!({"seed": 7, "ignore-waves": false}),
+    )
+    .is_ok());
+}
+
+#[test]
+fn test_changed_immutable_field_is_rejected() {
+    let err = set_with_current_cli(json!({"seed": 8}), json!({"seed": 7})).unwrap_err();
+    assert!(
+        format!("{:#}", err).contains("cannot change immutable fields once they are set: seed"),
+        "{:#}",
+        err
+    );
+
+    // Removing the value counts as changing it.
+    set_with_current_cli(json!({"ignore-waves": true}), json!({"seed": 7})).unwrap_err();
+}
+
+#[test]
+fn test_generate_is_unaffected() {
+    let generated: GenerateResult<SeedV1, SeedV1> =
+        generate_cli(seed_extension(), "v1", None, None).unwrap();
+    assert_eq!(
+        generated,
+        GenerateResult::Complete(SeedV1 {
+            seed: Some(42),
+            ignore_waves: None,
+        })
+    );
+}
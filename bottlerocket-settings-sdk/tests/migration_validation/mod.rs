@@ -7,6 +7,8 @@ mod common {
             #[derive(Debug, Serialize, Deserialize, Default, PartialEq, Eq)]
             pub struct $name;
 
+            impl bottlerocket_settings_sdk::KnownNames for $name {}
+
             impl SettingsModel for $name {
                 type PartialKind = Self;
                 type ErrorKind = anyhow::Error;
@@ -95,3 +95,16 @@ fn test_migration_types_mutually_exclusive() {
 
     assert!(extension.try_run_with_args(args).is_err())
 }
+
+#[test]
+fn test_deserialize_error_path() {
+    // When a value fails to deserialize,
+    // then the error reports the location of the offending field.
+    let err = set_cli(motd_settings_extension(), "v2", json!(["hello", 42])).unwrap_err();
+    let message = format!("{:#}", err);
+    assert!(
+        message.contains("invalid value for '[1]': invalid type: integer `42`"),
+        "{}",
+        message
+    );
+}
@@ -2,8 +2,8 @@ use std::convert::Infallible;
 
 use super::*;
 use bottlerocket_settings_sdk::{
-    provide_template_helpers, GenerateResult, HelperDef, LinearlyMigrateable, NoMigration,
-    SettingsModel,
+    provide_template_helpers, GenerateResult, HelperDef, KnownNames, LinearlyMigrateable,
+    NoMigration, SettingsModel,
 };
 use bottlerocket_template_helper::template_helper;
 use serde::{Deserialize, Serialize};
@@ -15,6 +15,8 @@ pub struct MotdV1(pub Option<String>);
 
 type Result<T> = std::result::Result<T, Infallible>;
 
+impl KnownNames for MotdV1 {}
+
 impl SettingsModel for MotdV1 {
     /// We only have one value, so there's no such thing as a partial
     type PartialKind = Self;
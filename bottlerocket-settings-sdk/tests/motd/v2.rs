@@ -3,8 +3,8 @@ use std::collections::HashMap;
 use super::*;
 use anyhow::Result;
 use bottlerocket_settings_sdk::{
-    provide_template_helpers, GenerateResult, HelperDef, LinearlyMigrateable, NoMigration,
-    SettingsModel,
+    provide_template_helpers, GenerateResult, HelperDef, KnownNames, LinearlyMigrateable,
+    NoMigration, SettingsModel,
 };
 use bottlerocket_template_helper::template_helper;
 use serde::{Deserialize, Serialize};
@@ -13,6 +13,8 @@ use serde_json::json;
 #[derive(Debug, Serialize, Deserialize, Default, PartialEq, Eq)]
 pub struct MotdV2(#[serde(default)] pub Vec<String>);
 
+impl KnownNames for MotdV2 {}
+
 impl SettingsModel for MotdV2 {
     /// We only have one value, so there's no such thing as a partial
     type PartialKind = Self;
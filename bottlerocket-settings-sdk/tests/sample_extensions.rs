@@ -16,8 +16,15 @@ fn setup_logging() {
 }
 
 mod colliding_versions;
+mod cross_validation;
+mod deprecated_aliases;
+mod environment;
+mod field_validation;
+mod generation_dependencies;
+mod immutable_fields;
 mod migration_validation;
 mod motd;
+mod value_warnings;
 
 /// We also define some helpers for invoking the CLI interface generated by the SDK.
 mod helpers {
@@ -34,7 +41,7 @@ mod helpers {
         Mo: AsTypeErasedModel,
     {
         extension
-            .try_run_with_args(&[
+            .try_run_with_args([
                 "extension",
                 "proto1",
                 "set",
@@ -46,7 +53,6 @@ mod helpers {
             .context("Failed to run settings extension CLI")
             .map(|s| {
                 assert!(s.is_empty());
-                ()
             })
     }
 
@@ -129,7 +135,6 @@ mod helpers {
             .context("Failed to run settings extension CLI")
             .map(|s| {
                 assert!(s.is_empty());
-                ()
             })
     }
 
@@ -207,8 +212,7 @@ mod helpers {
     {
         let template_args: Vec<String> = args
             .into_iter()
-            .map(|arg| vec!["--arg".to_string(), arg.to_string()])
-            .flatten()
+            .flat_map(|arg| vec!["--arg".to_string(), arg.to_string()])
             .collect();
 
         let args = [
@@ -0,0 +1,97 @@
+use super::*;
+use bottlerocket_model_derive::model;
+use bottlerocket_settings_sdk::{
+    BottlerocketSetting, FieldChecks, FieldError, GenerateResult, NullMigrator,
+    NullMigratorExtensionBuilder, SettingsModel,
+};
+use serde_json::json;
+use std::convert::Infallible;
+
+/// A model which warns when its `retries` field is set to zero, while still accepting the value.
+#[model(impl_default = true)]
+struct RetryV1 {
+    #[deprecated_alias = "retry"]
+    retries: u32,
+}
+
+type Result<T> = std::result::Result<T, Infallible>;
+
+impl SettingsModel for RetryV1 {
+    type PartialKind = Self;
+    type ErrorKind = Infallible;
+
+    fn get_version() -> &'static str {
+        "v1"
+    }
+
+    fn set(_current_value: Option<Self>, _target: Self) -> Result<()> {
+        Ok(())
+    }
+
+    fn generate(
+        existing_partial: Option<Self::PartialKind>,
+        _dependent_settings: Option<serde_json::Value>,
+    ) -> Result<GenerateResult<Self::PartialKind, Self>> {
+        Ok(GenerateResult::Complete(
+            existing_partial.unwrap_or_default(),
+        ))
+    }
+
+    fn validate(_value: Self, _validated_settings: Option<serde_json::Value>) -> Result<()> {
+        Ok(())
+    }
+
+    fn field_checks() -> FieldChecks<Self> {
+        FieldChecks::declared()
+    }
+
+    fn warnings(&self) -> Vec<FieldError> {
+        match self.retries {
+            Some(0) => vec![FieldError::new(
+                "retries",
+                "is 0, so failures won't be retried",
+            )],
+            _ => Vec::new(),
+        }
+    }
+}
+
+fn retry_extension(
+) -> SettingsExtension<NullMigrator, Box<dyn bottlerocket_settings_sdk::model::TypeErasedModel>> {
+    NullMigratorExtensionBuilder::with_name("retry")
+        .with_models(vec![BottlerocketSetting::<RetryV1>::model()])
+        .build()
+        .expect("Failed to build retry settings extension")
+}
+
+fn warnings(value: serde_json::Value) -> Vec<String> {
+    let extension = retry_extension();
+    let model = extension.model("v1").unwrap().as_model();
+    model
+        .value_warnings(value)
+        .expect("Failed to check value warnings")
+        .iter()
+        .map(ToString::to_string)
+        .collect()
+}
+
+#[test]
+fn test_value_warnings() {
+    assert_eq!(
+        warnings(json!({"retries": 0})),
+        vec!["'retries' is 0, so failures won't be retried"]
+    );
+    assert!(warnings(json!({"retries": 3})).is_empty());
+    // Deprecated names are reported by `set` and `validate`, not as value warnings.
+    assert_eq!(
+        warnings(json!({"retry": 0})),
+        vec!["'retries' is 0, so failures won't be retried"]
+    );
+}
+
+#[test]
+fn test_set_and_validate_accept_values_with_warnings() {
+    // Warnings are reported on stderr, leaving the output empty.
+    assert!(set_cli(retry_extension(), "v1", json!({"retries": 0})).is_ok());
+    assert!(validate_cli(retry_extension(), "v1", json!({"retry": 0}), None).is_ok());
+}